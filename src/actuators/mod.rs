@@ -19,13 +19,17 @@ pub(crate) struct Control {
 pub(crate) struct Switch {
     pub esc: bool,
     pub reload: bool,
+    /// Calibration du magnétomètre sur la voiture (collecte tant que le switch est actif)
+    #[serde(default)]
+    pub mag_calibration: bool,
 }
 
 impl Switch {
     pub (crate) fn empty() -> Switch {
         Switch {
             esc: false,
-            reload: false
+            reload: false,
+            mag_calibration: false,
         }
     }
 }
\ No newline at end of file
@@ -27,7 +27,7 @@ impl Motor {
         let pwm = Pwm::with_frequency(Channel::Pwm0, 50.0, MOTOR_NEUTRAL, Polarity::Normal, true).map_err(|x| anyhow!(x))?;
 
         Ok(Motor {
-            pwm: pwm,
+            pwm,
             is_safe: false,
             elapsed_time: Instant::now(),
             last_error: 0.0,
@@ -44,7 +44,7 @@ impl Motor {
 
     /// Consigne de vitesse (via PID)
     pub fn set_speed(&mut self, wanted_speed: f64, sensor_speed: f64) -> anyhow::Result<f64> {
-        if self.force_raw_speed == true {
+        if self.force_raw_speed {
             let set_speed = self.set_speed_esc(wanted_speed)?;
             return Ok(set_speed);
         }
@@ -60,7 +60,7 @@ impl Motor {
         let error = wanted_speed - sensor_speed;
 
         // Calcul de l'intégrale
-        self.error_integral = self.error_integral + (error * elapsed_time.as_secs_f64());
+        self.error_integral += error * elapsed_time.as_secs_f64();
 
         // Calcul de la dérivée
         let derivative = (error - self.last_error) / elapsed_time.as_secs_f64();
@@ -84,7 +84,7 @@ impl Motor {
         }
 
         // Validation SYSTEMATIQUE des données.
-        if speed < -1.0 || speed > 1.0  {
+        if !(-1.0..=1.0).contains(&speed)  {
             speed = 0.0;    
         }
 
@@ -93,7 +93,7 @@ impl Motor {
 
         // Reverse
         if speed < 0.0 {
-            cycle = MOTOR_NEUTRAL - ((-1.0 * speed) * (MOTOR_NEUTRAL - MOTOR_MAX_REV));
+            cycle = MOTOR_NEUTRAL - (-speed * (MOTOR_NEUTRAL - MOTOR_MAX_REV));
         }
 
         // Forward
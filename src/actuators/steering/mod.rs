@@ -17,7 +17,7 @@ impl Steering {
         let pwm = Pwm::with_frequency(Channel::Pwm1, 50.0, STEER_MID, Polarity::Normal, true).map_err(|x| anyhow!(x))?;
 
         Ok(Steering {
-            pwm: pwm,
+            pwm,
             is_safe: false,
         })
     }
@@ -28,7 +28,7 @@ impl Steering {
         }
 
         // Validation SYSTEMATIQUE des données.
-        if steer < -1.0 || steer > 1.0 {
+        if !(-1.0..=1.0).contains(&steer) {
             steer = 0.0;
         }
 
@@ -38,7 +38,7 @@ impl Steering {
 
         // Gauche
         if steer < 0.0 {
-            cycle = STEER_MID + ((-1.0 * steer) * (STEER_LEFT - STEER_MID));
+            cycle = STEER_MID + (-steer * (STEER_LEFT - STEER_MID));
         }
 
         // Droite
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 
 #[derive(Debug, Parser, Clone)]
@@ -6,4 +8,12 @@ pub struct Cli {
     pub db_url: String,
     pub db_username: String,
     pub db_password: String,
+
+    /// Enregistre toutes les transactions I2C dans ce fichier
+    #[arg(long)]
+    pub i2c_record: Option<PathBuf>,
+
+    /// Rejoue un enregistrement I2C à la place du bus réel
+    #[arg(long)]
+    pub i2c_replay: Option<PathBuf>,
 }
\ No newline at end of file
@@ -1,24 +1,76 @@
 use nalgebra::{Matrix3, Vector3};
 use serde::{Deserialize, Serialize};
 
+use crate::sensors::ahrs::AhrsFilter;
+use crate::sensors::imu::calibration::{CalibrationMode, GyroTempCompensation, ImuCalibration};
+use crate::sensors::imu::{AccelRange, DlpfBandwidth, GyroRange, ImuChip};
+use crate::sensors::mag::{HmcDataRate, HmcGain, HmcMode, HmcSamples, MagChip};
+use crate::sensors::orientation::Orientation;
+
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Config {
     pub(crate) kp: f64,
     pub(crate) ki: f64,
     pub(crate) kd: f64,
     pub(crate) mag_decl: f32,
+    /// Déclinaison calculée (WMM) à partir de la position GPS, `mag_decl` reste utilisée sans fix
+    /// Désactivé : `mag_decl` est toujours utilisée.
+    pub(crate) mag_decl_auto: bool,
     pub(crate) hard_cal: Vector3<f32>,
     pub(crate) soft_cal: Matrix3<f32>,
     pub(crate) force_raw_speed: bool,
+    pub(crate) i2c_bus: u8,
+    pub(crate) i2c_retries: u8,
+    pub(crate) i2c_retry_delay_ms: u64,
+    pub(crate) ahrs_filter: AhrsFilter,
+    pub(crate) madgwick_beta: f32,
+    pub(crate) mahony_kp: f32,
+    pub(crate) mahony_ki: f32,
+    /// Puce IMU montée sur la voiture, détectée au démarrage si absente
+    pub(crate) imu_chip: Option<ImuChip>,
+    pub(crate) imu_rate_hz: u16,
+    pub(crate) imu_dlpf: DlpfBandwidth,
+    pub(crate) imu_accel_range: AccelRange,
+    pub(crate) imu_gyro_range: GyroRange,
+    pub(crate) imu_int_pin: Option<u8>,
+    pub(crate) imu_raw_data: bool,
+    pub(crate) imu_calibration_mode: CalibrationMode,
+    pub(crate) imu_calibration: Option<ImuCalibration>,
+    pub(crate) imu_gyro_temp_comp: GyroTempCompensation,
+    pub(crate) imu_events: bool,
+    pub(crate) imu_motion_threshold_g: f32,
+    pub(crate) imu_motion_duration_ms: u8,
+    pub(crate) imu_free_fall_threshold_g: f32,
+    pub(crate) imu_free_fall_duration_ms: u8,
+    pub(crate) imu_zero_motion_threshold_g: f32,
+    pub(crate) imu_zero_motion_duration_ms: u16,
+    /// Absente des configurations antérieures : montage de la première voiture (`legacy_imu_orientation`)
+    #[serde(default = "Config::legacy_imu_orientation")]
+    pub(crate) imu_orientation: Orientation,
+    /// Magnétomètre monté sur la voiture, détecté au démarrage si absent
+    pub(crate) mag_chip: Option<MagChip>,
+    /// Absente des configurations antérieures : montage de la première voiture (`legacy_mag_orientation`)
+    #[serde(default = "Config::legacy_mag_orientation")]
+    pub(crate) mag_orientation: Orientation,
+    pub(crate) mag_aux_bus: bool,
+    /// Réglages du HMC5883L : échantillons moyennés, fréquence (mode continu), gain et mode de mesure
+    /// `hard_cal` est exprimée en LSB, elle doit être refaite après un changement de gain.
+    pub(crate) mag_averaging: HmcSamples,
+    pub(crate) mag_data_rate: HmcDataRate,
+    pub(crate) mag_gain: HmcGain,
+    pub(crate) mag_mode: HmcMode,
 }
 
 impl Config {
+    #[allow(clippy::excessive_precision)]
     pub fn new() -> Self {
-        let config = Config { 
+        Config {
             kp: 1.0,
             ki: 1.0,
             kd: 1.0,
             mag_decl: 2.44,
+            mag_decl_auto: true,
             hard_cal: Vector3::new(569.68423502, 246.04798002, -166.97661026),
             soft_cal: Matrix3::new(
                 1.08480289,
@@ -32,8 +84,99 @@ impl Config {
                 0.99634431,
             ),
             force_raw_speed: false,
-        };
+            i2c_bus: 1,
+            i2c_retries: 3,
+            i2c_retry_delay_ms: 2,
+            ahrs_filter: AhrsFilter::Madgwick,
+            madgwick_beta: 0.1,
+            mahony_kp: 1.0,
+            mahony_ki: 0.0,
+            imu_chip: None,
+            imu_rate_hz: 200,
+            imu_dlpf: DlpfBandwidth::Bw42,
+            imu_accel_range: AccelRange::Fs2,
+            imu_gyro_range: GyroRange::Fs250,
+            imu_int_pin: None,
+            imu_raw_data: false,
+            imu_calibration_mode: CalibrationMode::Hardware,
+            imu_calibration: None,
+            imu_gyro_temp_comp: GyroTempCompensation::Linear,
+            imu_events: true,
+            imu_motion_threshold_g: 0.5,
+            imu_motion_duration_ms: 10,
+            imu_free_fall_threshold_g: 0.3,
+            imu_free_fall_duration_ms: 50,
+            imu_zero_motion_threshold_g: 0.02,
+            imu_zero_motion_duration_ms: 640,
+            imu_orientation: Orientation::Normal,
+            mag_chip: None,
+            mag_orientation: Orientation::Normal,
+            mag_aux_bus: false,
+            mag_averaging: HmcSamples::S1,
+            mag_data_rate: HmcDataRate::Hz15,
+            mag_gain: HmcGain::G1_3,
+            mag_mode: HmcMode::Continuous,
+        }
+    }
+}
+
+impl Config {
+    /// IMU de la première voiture : tournée d'un demi-tour (angles autrefois inversés dans le code)
+    fn legacy_imu_orientation() -> Orientation {
+        Orientation::Yaw180
+    }
+
+    /// Magnétomètre de la première voiture : tourné d'un quart de tour (cap autrefois `180 - atan2(x, y)`)
+    fn legacy_mag_orientation() -> Orientation {
+        Orientation::Yaw90
+    }
+}
 
-        config
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::ahrs::Ahrs;
+    use crate::sensors::mag::calibration::MagCorrection;
+    use serde::de::value::{Error, MapDeserializer};
+
+    #[test]
+    fn legacy_record_keeps_first_car_mounting() {
+        // Configuration enregistrée avant `imu_orientation` et `mag_orientation`
+        let record = MapDeserializer::<_, Error>::new(std::iter::empty::<(&str, &str)>());
+        let mut config = Config::deserialize(record).unwrap();
+        assert_eq!(config.imu_orientation, Orientation::Yaw180);
+        assert_eq!(config.mag_orientation, Orientation::Yaw90);
+        assert_eq!(Config::new().imu_orientation, Orientation::Normal);
+
+        // Cap d'origine : 180 - atan2(x, y) sur la mesure corrigée
+        config.mag_decl = 0.0;
+        config.hard_cal = Vector3::zeros();
+        config.soft_cal = Matrix3::identity();
+        let correction = MagCorrection::new(&config);
+        for raw in [Vector3::new(300i16, 0, -200), Vector3::new(-120, 250, 40), Vector3::new(10, -400, 0)] {
+            let old = (180.0 - (raw.x as f32).atan2(raw.y as f32).to_degrees()).rem_euclid(360.0);
+            let error = (correction.heading_from_raw(raw) - old + 180.0).rem_euclid(360.0) - 180.0;
+            assert!(error.abs() < 1e-3, "{:?}: {} / {}", raw, correction.heading_from_raw(raw), old);
+        }
+
+        // Angles d'origine : tangage et roulis du capteur, inversés
+        let mut sensor = Ahrs::new(&config);
+        let mut car = Ahrs::new(&config);
+        let orientation = config.imu_orientation.matrix();
+        let accel = Vector3::new(0.17, -0.26, 0.95);
+        let gyro = Vector3::new(0.2, -0.1, 0.05);
+        for _ in 0..100 {
+            sensor.update(gyro, accel, None, 0.005);
+            car.update(orientation * gyro, orientation * accel, None, 0.005);
+        }
+
+        let (old, new) = (sensor.euler(), car.euler());
+        assert!((new.y - -old.y).abs() < 1e-3 && (new.x - -old.x).abs() < 1e-3, "{:?} / {:?}", new, old);
+    }
+}
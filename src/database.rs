@@ -2,6 +2,8 @@ use surrealdb::engine::remote::ws::Client;
 use surrealdb::engine::remote::ws::Wss;
 use surrealdb::opt::auth::Root;
 use surrealdb::Surreal;
+use serde::Serialize;
+use nalgebra::{Matrix3, Vector3};
 
 use crate::actuators::Control;
 use crate::actuators::Switch;
@@ -10,6 +12,8 @@ use crate::sensors::reader::ModemData;
 use crate::sensors::reader::SensorsData;
 
 use crate::config::Config;
+use crate::sensors::imu::calibration::ImuCalibration;
+use crate::sensors::mag::calibration::MagCalibration;
 
 pub(crate) struct Database {
     db: Surreal<Client>,
@@ -21,8 +25,8 @@ impl Database {
         let db = Surreal::new::<Wss>(&args.db_url).await?;
 
         db.signin(Root {
-            username: &args.db_username.as_str(),
-            password: &args.db_password.as_str(),
+            username: args.db_username.as_str(),
+            password: args.db_password.as_str(),
         }).await?;
 
         db.use_ns("voiturerc").use_db("voiturerc").await?;
@@ -42,6 +46,39 @@ impl Database {
         }
     }
 
+    // Enregistre la calibration de l'IMU dans la configuration de la voiture
+    pub(crate) async fn save_imu_calibration(&self, calibration: ImuCalibration) -> anyhow::Result<()> {
+        #[derive(Serialize)]
+        struct Patch {
+            imu_calibration: ImuCalibration,
+        }
+
+        let _: Option<Config> = self
+            .db
+            .update(("config", self.uuid.clone()))
+            .merge(Patch { imu_calibration: calibration })
+            .await?;
+
+        Ok(())
+    }
+
+    // Enregistre la calibration du magnétomètre dans la configuration de la voiture
+    pub(crate) async fn save_mag_calibration(&self, calibration: MagCalibration) -> anyhow::Result<()> {
+        #[derive(Serialize)]
+        struct Patch {
+            hard_cal: Vector3<f32>,
+            soft_cal: Matrix3<f32>,
+        }
+
+        let _: Option<Config> = self
+            .db
+            .update(("config", self.uuid.clone()))
+            .merge(Patch { hard_cal: calibration.hard_cal, soft_cal: calibration.soft_cal })
+            .await?;
+
+        Ok(())
+    }
+
     // Envoi les données du modem
     pub(crate) async fn send_modem(&self, quality: u32) -> anyhow::Result<()> {
         let _: Option<ModemData> = self
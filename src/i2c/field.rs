@@ -0,0 +1,300 @@
+use std::marker::PhantomData;
+
+// Description typée des registres et de leurs champs de bits.
+// Les combinaisons invalides (champ hors du registre, valeur trop large pour le champ, écriture
+// d'un registre en lecture seule, ...) sont refusées à la compilation.
+//
+// Exemple :
+//   pub const GYRO_CONFIG: Register<RW> = Register::r8(0x1B);
+//   pub const GYRO_FS_SEL: Field<RW, GyroRange> = Field::new(GYRO_CONFIG, 3, 2);
+//   i2c.ecriture_field(GYRO_FS_SEL, GyroRange::Fs500)?;
+
+/// Mode d'accès d'un registre
+pub trait Access: Copy {}
+
+/// Registre accessible en lecture
+pub trait Readable: Access {}
+
+/// Registre accessible en écriture
+pub trait Writable: Access {}
+
+/// Lecture seule
+#[derive(Clone, Copy, Debug)]
+pub struct RO;
+
+/// Lecture et écriture
+#[derive(Clone, Copy, Debug)]
+pub struct RW;
+
+impl Access for RO {}
+impl Access for RW {}
+impl Readable for RO {}
+impl Readable for RW {}
+impl Writable for RW {}
+
+/// Registre de 8 ou 16 bits (octet de poids fort en premier)
+#[derive(Clone, Copy, Debug)]
+pub struct Register<A: Access> {
+    address: u8,
+    width: u8,
+    access: PhantomData<A>,
+}
+
+impl<A: Access> Register<A> {
+    /// Registre 8 bits
+    pub const fn r8(address: u8) -> Self {
+        Self { address, width: 8, access: PhantomData }
+    }
+
+    /// Registre 16 bits
+    pub const fn r16(address: u8) -> Self {
+        Self { address, width: 16, access: PhantomData }
+    }
+
+    /// Adresse du registre
+    pub const fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Taille du registre en bits
+    pub const fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Taille du registre en octets
+    pub const fn len(&self) -> usize {
+        (self.width / 8) as usize
+    }
+}
+
+/// Valeur pouvant être stockée dans un champ de bits
+pub trait FieldValue: Sized + Copy {
+    /// Nombre de bits minimum du champ pour contenir toutes les valeurs
+    const BITS: u8;
+
+    /// Valeur brute du champ
+    fn to_bits(self) -> u16;
+
+    /// Valeur depuis le champ brut, `None` si elle n'est pas définie
+    fn from_bits(bits: u16) -> Option<Self>;
+}
+
+impl FieldValue for bool {
+    const BITS: u8 = 1;
+
+    fn to_bits(self) -> u16 {
+        self as u16
+    }
+
+    fn from_bits(bits: u16) -> Option<Self> {
+        Some(bits != 0)
+    }
+}
+
+// Les entiers bruts ne sont contrôlés qu'à l'exécution (la valeur doit tenir dans le champ)
+impl FieldValue for u8 {
+    const BITS: u8 = 1;
+
+    fn to_bits(self) -> u16 {
+        self as u16
+    }
+
+    fn from_bits(bits: u16) -> Option<Self> {
+        u8::try_from(bits).ok()
+    }
+}
+
+impl FieldValue for u16 {
+    const BITS: u8 = 1;
+
+    fn to_bits(self) -> u16 {
+        self
+    }
+
+    fn from_bits(bits: u16) -> Option<Self> {
+        Some(bits)
+    }
+}
+
+/// Champ de bits d'un registre
+#[derive(Clone, Copy, Debug)]
+pub struct Field<A: Access, V: FieldValue> {
+    register: Register<A>,
+    offset: u8,
+    len: u8,
+    value: PhantomData<V>,
+}
+
+impl<A: Access, V: FieldValue> Field<A, V> {
+    /// Champ de `len` bits dont le bit de poids faible est `offset`
+    pub const fn new(register: Register<A>, offset: u8, len: u8) -> Self {
+        assert!(len > 0, "Champ vide");
+        assert!(offset + len <= register.width, "Champ en dehors du registre");
+        assert!(V::BITS <= len, "Valeurs trop larges pour le champ");
+        Self { register, offset, len, value: PhantomData }
+    }
+
+    /// Champ d'un seul bit
+    pub const fn bit(register: Register<A>, bit: u8) -> Self {
+        Self::new(register, bit, 1)
+    }
+
+    /// Registre contenant le champ
+    pub const fn register(&self) -> Register<A> {
+        self.register
+    }
+
+    /// Position du bit de poids faible
+    pub const fn offset(&self) -> u8 {
+        self.offset
+    }
+
+    /// Taille du champ en bits
+    pub const fn len(&self) -> u8 {
+        self.len
+    }
+
+    /// Masque du champ (non décalé)
+    pub const fn mask(&self) -> u16 {
+        ((1u32 << self.len) - 1) as u16
+    }
+
+    /// Valeur brute du champ dans le contenu du registre
+    pub const fn bits(&self, data: u16) -> u16 {
+        (data >> self.offset) & self.mask()
+    }
+
+    /// Valeur du champ dans le contenu (déjà lu) du registre, `None` si elle n'est pas définie
+    pub fn decode(&self, data: u16) -> Option<V> {
+        V::from_bits(self.bits(data))
+    }
+}
+
+/// Déclare une énumération utilisable comme valeur d'un champ de bits
+/// ```ignore
+/// field_enum! {
+///     /// Echelle du gyroscope
+///     pub enum GyroRange { Fs250 = 0x00, Fs500 = 0x01 }
+/// }
+/// ```
+macro_rules! field_enum {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($(#[$vmeta:meta])* $variant:ident = $value:expr),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(u16)]
+        $vis enum $name {
+            $($(#[$vmeta])* $variant = $value),+
+        }
+
+        impl $crate::i2c::field::FieldValue for $name {
+            const BITS: u8 = {
+                let values = [$($value as u16),+];
+                let mut max = 0;
+                let mut n = 0;
+                while n < values.len() {
+                    if values[n] > max {
+                        max = values[n];
+                    }
+                    n += 1;
+                }
+                (u16::BITS - max.leading_zeros()) as u8
+            };
+
+            fn to_bits(self) -> u16 {
+                self as u16
+            }
+
+            fn from_bits(bits: u16) -> Option<Self> {
+                $(if bits == $value {
+                    return Some(Self::$variant);
+                })+
+                None
+            }
+        }
+    };
+}
+
+pub(crate) use field_enum;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+    use crate::i2c::{I2CBit, I2CBus};
+
+    field_enum! {
+        enum Mode {
+            Off = 0b00,
+            Slow = 0b01,
+            Fast = 0b11,
+        }
+    }
+
+    const CTRL: Register<RW> = Register::r8(0x10);
+    const CTRL_MODE: Field<RW, Mode> = Field::new(CTRL, 3, 2);
+    const CTRL_LEVEL: Field<RW, u8> = Field::new(CTRL, 5, 3);
+    const CONFIG: Register<RW> = Register::r16(0x01);
+    const CONFIG_GAIN: Field<RW, u8> = Field::new(CONFIG, 9, 3);
+    const CONFIG_SPLIT: Field<RW, u8> = Field::new(CONFIG, 7, 2);
+    const STATUS: Register<RO> = Register::r8(0x20);
+
+    fn sim_bus(register_size: usize) -> SimBus {
+        let mut bus = SimBus::new();
+        bus.attach(0x48, SimDevice::with_register_size(register_size));
+        bus.set_slave_address(0x48).unwrap();
+        bus
+    }
+
+    #[test]
+    fn layout_and_decoding() {
+        assert_eq!((CTRL.width(), CTRL.len()), (8, 1));
+        assert_eq!((CONFIG.width(), CONFIG.len()), (16, 2));
+        assert_eq!(Mode::BITS, 2);
+        assert_eq!(<bool as FieldValue>::BITS, 1);
+
+        // Bits 3..5 de 0b1111_0111 : 0b10, valeur non définie
+        assert_eq!(CTRL_MODE.mask(), 0b11);
+        assert_eq!(CTRL_MODE.bits(0b1111_0111), 0b10);
+        assert_eq!(CTRL_MODE.decode(0b1111_0111), None);
+        assert_eq!(CTRL_MODE.decode(0b0001_1000), Some(Mode::Fast));
+        assert_eq!(CTRL_LEVEL.decode(0b1010_0000), Some(0b101));
+        assert_eq!(CONFIG_GAIN.decode(0x8583), Some(0b010));
+    }
+
+    #[test]
+    fn read_modify_write_and_range() {
+        let mut bus = sim_bus(1);
+        bus.device(0x48).unwrap().set_registers(CTRL.address(), &[0xFF]);
+
+        // Seuls les bits du champ changent
+        bus.ecriture_field(CTRL_MODE, Mode::Off).unwrap();
+        assert_eq!(bus.lecture_reg(CTRL).unwrap(), 0b1110_0111);
+        bus.ecriture_field(CTRL_MODE, Mode::Slow).unwrap();
+        assert_eq!(bus.lecture_field(CTRL_MODE).unwrap(), Mode::Slow);
+
+        // Valeur non définie relue : erreur
+        bus.device(0x48).unwrap().set_registers(STATUS.address(), &[0b0001_0000]);
+        const STATUS_MODE: Field<RO, Mode> = Field::new(STATUS, 3, 2);
+        assert!(bus.lecture_field(STATUS_MODE).is_err());
+
+        // Les entiers bruts ne sont contrôlés qu'à l'exécution : rien n'est écrit
+        assert!(bus.ecriture_field(CTRL_LEVEL, 0b1000).is_err());
+        assert!(bus.ecriture_reg(CTRL, 0x100).is_err());
+        assert_eq!(bus.lecture_reg(CTRL).unwrap(), 0b1110_1111);
+    }
+
+    #[test]
+    fn sixteen_bit_register_big_endian() {
+        let mut bus = sim_bus(2);
+        bus.ecriture_reg(CONFIG, 0x8583).unwrap();
+        assert_eq!(bus.device(0x48).unwrap().registers(CONFIG.address(), 2), &[0x85, 0x83]);
+
+        bus.ecriture_field(CONFIG_GAIN, 0b001).unwrap();
+        assert_eq!(bus.device(0x48).unwrap().registers(CONFIG.address(), 2), &[0x83, 0x83]);
+
+        // Champ à cheval sur les deux octets du registre
+        assert_eq!(bus.lecture_field(CONFIG_SPLIT).unwrap(), 0b11);
+        bus.ecriture_field(CONFIG_SPLIT, 0b00).unwrap();
+        assert_eq!(bus.lecture_reg(CONFIG).unwrap(), 0x8203);
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::ops::Range;
+use std::os::unix::io::AsRawFd;
+
+use anyhow::anyhow;
+
+use crate::i2c::I2CBus;
+
+// Voir documentation : https://www.kernel.org/doc/html/latest/i2c/dev-interface.html
+const I2C_SLAVE: u64 = 0x0703;
+const I2C_FUNCS: u64 = 0x0705;
+const I2C_RDWR: u64 = 0x0707;
+const I2C_SMBUS: u64 = 0x0720;
+
+const I2C_FUNC_I2C: libc::c_ulong = 0x0000_0001;
+const I2C_M_RD: u16 = 0x0001;
+
+const I2C_SMBUS_READ: u8 = 1;
+const I2C_SMBUS_WRITE: u8 = 0;
+const I2C_SMBUS_I2C_BLOCK_DATA: u32 = 8;
+const I2C_SMBUS_BLOCK_MAX: usize = 32;
+
+/// Découpe une transaction SMBus en blocs de 32 octets (registre et plage du tampon de chaque bloc)
+/// Le registre n'est pas incrémenté entre les blocs : une FIFO (ex: FIFO_R_W) se lit toujours au même registre.
+/// Un bloc de registres consécutifs de plus de 32 octets doit donc être découpé par le pilote.
+fn smbus_chunks(command: u8, len: usize) -> impl Iterator<Item = (u8, Range<usize>)> {
+    (0..len).step_by(I2C_SMBUS_BLOCK_MAX).map(move |start| (command, start..len.min(start + I2C_SMBUS_BLOCK_MAX)))
+}
+
+#[repr(C)]
+struct I2cMsg {
+    addr: u16,
+    flags: u16,
+    len: u16,
+    buf: *mut u8,
+}
+
+#[repr(C)]
+struct I2cRdwrIoctlData {
+    msgs: *mut I2cMsg,
+    nmsgs: u32,
+}
+
+#[repr(C)]
+struct I2cSmbusData {
+    block: [u8; I2C_SMBUS_BLOCK_MAX + 2],
+}
+
+#[repr(C)]
+struct I2cSmbusIoctlData {
+    read_write: u8,
+    command: u8,
+    size: u32,
+    data: *mut I2cSmbusData,
+}
+
+/// Bus I2C générique via `/dev/i2c-N` (indépendant du Raspberry Pi)
+/// Utilise des transactions I2C complètes si l'adaptateur le permet, sinon des blocs SMBus (ex: `i2c-stub`).
+pub struct LinuxI2c {
+    bus: u8,
+    file: File,
+    address: u16,
+    plain_i2c: bool,
+}
+
+impl LinuxI2c {
+    /// Ouvre le bus `/dev/i2c-<bus>`
+    pub fn new(bus: u8) -> anyhow::Result<Self> {
+        let file = Self::open(bus)?;
+
+        let mut funcs: libc::c_ulong = 0;
+        // SAFETY: I2C_FUNCS écrit un unsigned long dans `funcs`.
+        if unsafe { libc::ioctl(file.as_raw_fd(), I2C_FUNCS as _, &mut funcs) } < 0 {
+            return Err(anyhow!("[I2C] I2C_FUNCS (/dev/i2c-{}): {}", bus, io::Error::last_os_error()));
+        }
+
+        Ok(Self { bus, file, address: 0, plain_i2c: (funcs & I2C_FUNC_I2C) != 0 })
+    }
+
+    fn open(bus: u8) -> anyhow::Result<File> {
+        let path = format!("/dev/i2c-{}", bus);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(|e| anyhow!("[I2C] Impossible d'ouvrir {}: {}", path, e))?;
+        Ok(file)
+    }
+
+    fn ioctl<T>(&self, request: u64, data: *mut T) -> anyhow::Result<()> {
+        // SAFETY: `data` pointe vers une structure #[repr(C)] attendue par `request`.
+        if unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, data) } < 0 {
+            return Err(anyhow!("[I2C] Erreur ioctl ({:#04x}): {}", self.address, io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    fn rdwr(&self, msgs: &mut [I2cMsg]) -> anyhow::Result<()> {
+        let mut data = I2cRdwrIoctlData {
+            msgs: msgs.as_mut_ptr(),
+            nmsgs: msgs.len() as u32,
+        };
+        self.ioctl(I2C_RDWR, &mut data)
+    }
+
+    fn smbus_block(&self, read_write: u8, command: u8, block: &mut I2cSmbusData) -> anyhow::Result<()> {
+        let mut data = I2cSmbusIoctlData {
+            read_write,
+            command,
+            size: I2C_SMBUS_I2C_BLOCK_DATA,
+            data: block,
+        };
+        self.ioctl(I2C_SMBUS, &mut data)
+    }
+}
+
+impl I2CBus for LinuxI2c {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        // SAFETY: I2C_SLAVE prend l'adresse par valeur.
+        if unsafe { libc::ioctl(self.file.as_raw_fd(), I2C_SLAVE as _, address as libc::c_ulong) } < 0 {
+            return Err(anyhow!("[I2C] Esclave {:#04x} indisponible: {}", address, io::Error::last_os_error()));
+        }
+
+        self.address = address;
+        Ok(())
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        if self.plain_i2c {
+            // Ecriture du registre puis lecture avec un "repeated start"
+            let mut command = [command];
+            let mut msgs = [
+                I2cMsg { addr: self.address, flags: 0, len: 1, buf: command.as_mut_ptr() },
+                I2cMsg { addr: self.address, flags: I2C_M_RD, len: buffer.len() as u16, buf: buffer.as_mut_ptr() },
+            ];
+            return self.rdwr(&mut msgs);
+        }
+
+        // SMBus : 32 octets max par transaction
+        for (command, range) in smbus_chunks(command, buffer.len()) {
+            let chunk = &mut buffer[range];
+            let mut block = I2cSmbusData { block: [0; I2C_SMBUS_BLOCK_MAX + 2] };
+            block.block[0] = chunk.len() as u8;
+            self.smbus_block(I2C_SMBUS_READ, command, &mut block)?;
+            chunk.copy_from_slice(&block.block[1..=chunk.len()]);
+        }
+
+        Ok(())
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        if self.plain_i2c {
+            let mut data = Vec::with_capacity(buffer.len() + 1);
+            data.push(command);
+            data.extend_from_slice(buffer);
+
+            let mut msgs = [I2cMsg { addr: self.address, flags: 0, len: data.len() as u16, buf: data.as_mut_ptr() }];
+            return self.rdwr(&mut msgs);
+        }
+
+        for (command, range) in smbus_chunks(command, buffer.len()) {
+            let chunk = &buffer[range];
+            let mut block = I2cSmbusData { block: [0; I2C_SMBUS_BLOCK_MAX + 2] };
+            block.block[0] = chunk.len() as u8;
+            block.block[1..=chunk.len()].copy_from_slice(chunk);
+            self.smbus_block(I2C_SMBUS_WRITE, command, &mut block)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ferme et ré-ouvre le bus, puis sélectionne de nouveau l'esclave
+    /// Limite : un esclave bloqué qui maintient SDA à 0 n'est pas libéré. La récupération du bus (impulsions
+    /// sur SCL) dépend du pilote de l'adaptateur (`i2c-gpio`, `i2c-bcm2835`, ...) et n'est pas accessible via
+    /// `/dev/i2c-N` : seule une coupure d'alimentation du capteur le débloque alors.
+    fn reset(&mut self) -> anyhow::Result<()> {
+        self.file = Self::open(self.bus)?;
+        if self.address != 0 {
+            self.set_slave_address(self.address)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smbus_chunks_keep_register() {
+        let chunks: Vec<_> = smbus_chunks(0x74, 70).collect();
+        assert_eq!(chunks, vec![(0x74, 0..32), (0x74, 32..64), (0x74, 64..70)]);
+
+        assert_eq!(smbus_chunks(0x3B, 14).collect::<Vec<_>>(), vec![(0x3B, 0..14)]);
+        assert_eq!(smbus_chunks(0x3B, 32).collect::<Vec<_>>(), vec![(0x3B, 0..32)]);
+        assert_eq!(smbus_chunks(0x3B, 0).count(), 0);
+    }
+}
@@ -0,0 +1,241 @@
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+use crate::i2c::I2CBus;
+
+/// Politique de nouvelle tentative en cas d'erreur sur le bus (NACK, EIO, ...)
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Nombre de nouvelles tentatives après une erreur (0 : aucune)
+    pub retries: u8,
+    /// Attente entre deux tentatives
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { retries: 0, delay: Duration::ZERO }
+    }
+}
+
+struct SharedBus<B> {
+    bus: B,
+    address: Option<u16>,
+    retry: RetryPolicy,
+}
+
+/// Gestionnaire d'un bus I2C partagé entre plusieurs capteurs (et plusieurs tâches)
+/// Chaque transaction est sérialisée et l'esclave est sélectionné automatiquement.
+pub struct I2CManager<B> {
+    shared: Arc<Mutex<SharedBus<B>>>,
+}
+
+impl<B: I2CBus> I2CManager<B> {
+    /// Gestionnaire sans nouvelle tentative (le programme utilise toujours `with_retry`)
+    #[cfg(test)]
+    pub fn new(bus: B) -> Self {
+        Self::with_retry(bus, RetryPolicy::default())
+    }
+
+    /// Gestionnaire qui retente les transactions en erreur, puis réinitialise le bus en dernier recours
+    pub fn with_retry(bus: B, retry: RetryPolicy) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(SharedBus { bus, address: None, retry })),
+        }
+    }
+
+    /// Créer un accès au périphérique présent à l'adresse donnée
+    pub fn device(&self, address: u16) -> I2CDevice<B> {
+        I2CDevice {
+            shared: self.shared.clone(),
+            address,
+        }
+    }
+}
+
+impl<B> Clone for I2CManager<B> {
+    fn clone(&self) -> Self {
+        Self { shared: self.shared.clone() }
+    }
+}
+
+/// Accès à un périphérique du bus, lié à une seule adresse
+pub struct I2CDevice<B> {
+    shared: Arc<Mutex<SharedBus<B>>>,
+    address: u16,
+}
+
+impl<B: I2CBus> I2CDevice<B> {
+    /// Exécute une transaction sur le bus avec l'esclave sélectionné
+    fn with_bus<R>(&self, f: impl FnMut(&mut B) -> anyhow::Result<R>) -> anyhow::Result<R> {
+        self.with_bus_retry(true, f)
+    }
+
+    /// Exécute une transaction, retentée selon la politique du bus si `retry`
+    fn with_bus_retry<R>(&self, retry: bool, mut f: impl FnMut(&mut B) -> anyhow::Result<R>) -> anyhow::Result<R> {
+        let mut attempt = 0;
+        loop {
+            let delay = {
+                let mut shared = self.shared.lock().map_err(|_| anyhow!("[I2C] Bus inutilisable (verrou empoisonné)"))?;
+                let retry = match retry {
+                    true => shared.retry,
+                    false => RetryPolicy::default(),
+                };
+
+                let result = Self::select(&mut shared, self.address).and_then(|_| f(&mut shared.bus));
+                match result {
+                    Ok(r) => return Ok(r),
+                    Err(e) if attempt >= retry.retries => return Err(e),
+                    Err(_) => {
+                        attempt += 1;
+
+                        // Dernière tentative : réinitialise le bus avant de réessayer
+                        if attempt == retry.retries {
+                            eprintln!("[I2C] Erreurs répétées ({:#04x}), réinitialisation du bus ...", self.address);
+                            if let Err(e) = shared.bus.reset() {
+                                eprintln!("[I2C] Réinitialisation impossible: {}", e);
+                            }
+                        }
+
+                        shared.address = None;
+                        retry.delay
+                    }
+                }
+            };
+
+            // Attente sans le verrou : les autres périphériques du bus restent accessibles
+            sleep(delay);
+        }
+    }
+
+    /// Sélectionne l'esclave si ce n'est pas déjà le cas
+    fn select(shared: &mut SharedBus<B>, address: u16) -> anyhow::Result<()> {
+        if shared.address != Some(address) {
+            // L'adresse n'est plus connue en cas d'erreur, elle sera redéfinie à la prochaine transaction
+            shared.address = None;
+            shared.bus.set_slave_address(address)?;
+            shared.address = Some(address);
+        }
+        Ok(())
+    }
+}
+
+impl<B: I2CBus> I2CBus for I2CDevice<B> {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        if address != self.address {
+            return Err(anyhow!(
+                "[I2C] Accès lié à l'adresse {:#04x}, impossible d'utiliser {:#04x}",
+                self.address,
+                address
+            ));
+        }
+        Ok(())
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        self.with_bus(|bus| bus.block_read(command, buffer))
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        self.with_bus(|bus| bus.block_write(command, buffer))
+    }
+
+    fn fifo_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        // Une nouvelle tentative consommerait d'autres octets de la FIFO
+        self.with_bus_retry(false, |bus| bus.fifo_read(command, buffer))
+    }
+
+    fn read_modify_write(&mut self, command: u8, buffer: &mut [u8], modify: &mut dyn FnMut(&mut [u8])) -> anyhow::Result<()> {
+        self.with_bus(|bus| bus.read_modify_write(command, buffer, modify))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::field::{Field, Register, RW};
+    use crate::i2c::sim::{SimBus, SimDevice, SimResponse};
+    use crate::i2c::I2CBit;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Bus simulé qui compte les réinitialisations
+    struct CountingBus {
+        sim: SimBus,
+        resets: Arc<AtomicUsize>,
+    }
+
+    impl I2CBus for CountingBus {
+        fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+            self.sim.set_slave_address(address)
+        }
+
+        fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+            self.sim.block_read(command, buffer)
+        }
+
+        fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+            self.sim.block_write(command, buffer)
+        }
+
+        fn reset(&mut self) -> anyhow::Result<()> {
+            self.resets.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn shared_bus(retries: u8, nacks: usize) -> (I2CManager<CountingBus>, Arc<AtomicUsize>) {
+        let mut sim = SimBus::new();
+        for (address, value) in [(0x10, 0xAA), (0x20, 0x55)] {
+            let mut device = SimDevice::new();
+            device.set_registers(0x00, &[value]);
+            for _ in 0..nacks {
+                device.script_read(0x00, SimResponse::Nack);
+            }
+            sim.attach(address, device);
+        }
+
+        let resets = Arc::new(AtomicUsize::new(0));
+        let bus = CountingBus { sim, resets: resets.clone() };
+        (I2CManager::with_retry(bus, RetryPolicy { retries, delay: Duration::ZERO }), resets)
+    }
+
+    #[test]
+    fn devices_bound_to_their_address() {
+        let (manager, _) = shared_bus(0, 0);
+        let mut first = manager.device(0x10);
+        let mut second = manager.device(0x20);
+
+        // Les accès entrelacés resélectionnent le bon esclave
+        assert_eq!(first.lecture_word(0x00).unwrap(), 0xAA);
+        assert_eq!(second.lecture_word(0x00).unwrap(), 0x55);
+        assert_eq!(first.lecture_word(0x00).unwrap(), 0xAA);
+
+        assert!(first.set_slave_address(0x10).is_ok());
+        assert!(first.set_slave_address(0x20).is_err());
+
+        // Lecture, modification et écriture d'un champ en une seule transaction sur le bon esclave
+        const FIELD: Field<RW, u8> = Field::new(Register::r8(0x00), 4, 4);
+        second.ecriture_field(FIELD, 0x0A).unwrap();
+        assert_eq!(second.lecture_word(0x00).unwrap(), 0xA5);
+        assert_eq!(first.lecture_word(0x00).unwrap(), 0xAA);
+    }
+
+    #[test]
+    fn errors_retried_then_bus_reset() {
+        // Deux NACK : la seconde nouvelle tentative réussit, après une réinitialisation du bus
+        let (manager, resets) = shared_bus(2, 2);
+        let mut device = manager.device(0x10);
+        assert_eq!(device.lecture_word(0x00).unwrap(), 0xAA);
+        assert_eq!(resets.load(Ordering::SeqCst), 1);
+
+        // Sans nouvelle tentative, l'erreur remonte et le bus n'est pas réinitialisé
+        let (manager, resets) = shared_bus(0, 1);
+        let mut device = manager.device(0x10);
+        assert!(device.lecture_word(0x00).is_err());
+        assert_eq!(device.lecture_word(0x00).unwrap(), 0xAA);
+        assert_eq!(resets.load(Ordering::SeqCst), 0);
+    }
+}
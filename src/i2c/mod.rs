@@ -0,0 +1,404 @@
+pub mod field;
+pub mod manager;
+pub mod record;
+#[cfg(test)]
+pub mod sim;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::i2c::field::{Field, FieldValue, Readable, Register, Writable};
+
+#[cfg(feature = "real-sensors")]
+use rppal::i2c::I2c;
+
+/// Bus I2C minimal sur lequel reposent tous les pilotes.
+/// Permet d'utiliser les pilotes avec un autre matériel que le Raspberry Pi (ou un bus simulé).
+pub trait I2CBus {
+    /// Sélectionne l'esclave concerné par les prochaines transactions
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()>;
+
+    /// Lecture de plusieurs octets à partir du registre donné
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()>;
+
+    /// Ecriture de plusieurs octets à partir du registre donné
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()>;
+
+    /// Réinitialise l'accès au bus après des erreurs répétées (rien à faire par défaut)
+    fn reset(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Lecture d'un registre FIFO : les octets lus sont consommés, la transaction n'est jamais rejouée
+    /// après une erreur (un bus partagé ne la retente pas).
+    fn fifo_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        self.block_read(command, buffer)
+    }
+
+    /// Lecture, modification puis écriture du registre donné
+    /// Un bus partagé l'exécute sans laisser d'autre transaction s'intercaler.
+    fn read_modify_write(&mut self, command: u8, buffer: &mut [u8], modify: &mut dyn FnMut(&mut [u8])) -> anyhow::Result<()> {
+        self.block_read(command, buffer)?;
+        modify(buffer);
+        self.block_write(command, buffer)
+    }
+}
+
+impl<B: I2CBus + ?Sized> I2CBus for Box<B> {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        (**self).set_slave_address(address)
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        (**self).block_read(command, buffer)
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        (**self).block_write(command, buffer)
+    }
+
+    fn reset(&mut self) -> anyhow::Result<()> {
+        (**self).reset()
+    }
+
+    fn fifo_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        (**self).fifo_read(command, buffer)
+    }
+
+    fn read_modify_write(&mut self, command: u8, buffer: &mut [u8], modify: &mut dyn FnMut(&mut [u8])) -> anyhow::Result<()> {
+        (**self).read_modify_write(command, buffer, modify)
+    }
+}
+
+impl<B: I2CBus + ?Sized> I2CBus for &mut B {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        (**self).set_slave_address(address)
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        (**self).block_read(command, buffer)
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        (**self).block_write(command, buffer)
+    }
+
+    fn reset(&mut self) -> anyhow::Result<()> {
+        (**self).reset()
+    }
+
+    fn fifo_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        (**self).fifo_read(command, buffer)
+    }
+
+    fn read_modify_write(&mut self, command: u8, buffer: &mut [u8], modify: &mut dyn FnMut(&mut [u8])) -> anyhow::Result<()> {
+        (**self).read_modify_write(command, buffer, modify)
+    }
+}
+
+#[cfg(feature = "real-sensors")]
+impl I2CBus for I2c {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        I2c::set_slave_address(self, address).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        I2c::block_read(self, command, buffer).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        I2c::block_write(self, command, buffer).map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// Ouvre le bus I2C des capteurs `/dev/i2c-<bus>`
+/// `record` enregistre toutes les transactions, `replay` remplace le bus par un enregistrement.
+#[cfg(target_os = "linux")]
+pub fn open_bus(bus: u8, record: Option<&Path>, replay: Option<&Path>) -> anyhow::Result<Box<dyn I2CBus + Send>> {
+    if let Some(path) = replay {
+        println!("[I2C] Rejeu de l'enregistrement {}", path.display());
+        return Ok(Box::new(record::I2CReplay::open(path)?));
+    }
+
+    let linux = linux::LinuxI2c::new(bus)?;
+    match record {
+        Some(path) => Ok(Box::new(record::I2CRecorder::new(linux, path)?)),
+        None => Ok(Box::new(linux)),
+    }
+}
+
+/// Accès aux registres (octets, mots et champs de bits) au-dessus d'un bus I2C
+// Les accès bit à bit historiques ne sont plus utilisés par les pilotes (remplacés par `Field`)
+#[allow(dead_code)]
+pub trait I2CBit: I2CBus {
+    /// Ecrit un octet (word) sur la position donnée d'un registre 8 bits
+    fn ecriture_word(&mut self, command: u8, data: u8) -> anyhow::Result<()> {
+        self.block_write(command, &[data])
+    }
+
+    /// Lecture d'un octet (word) sur la position donnée d'un registre 8 bits
+    fn lecture_word(&mut self, command: u8) -> anyhow::Result<u8> {
+        let buffer: &mut [u8] = &mut [0];
+        self.block_read(command, buffer)?;
+        Ok(buffer[0])
+    }
+
+    /// Ecrit de 2 octets (dword) sur la position donnée d'un registre 16 bits
+    fn ecriture_dword(&mut self, command: u8, data: u16) -> anyhow::Result<()> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        buffer[0] = (data >> 8) as u8;
+        buffer[1] = data as u8;
+
+        self.block_write(command, buffer)
+    }
+
+    /// Lecture de 2 octets (dword) sur la position donnée d'un registre 16 bits
+    fn lecture_dword(&mut self, command: u8) -> anyhow::Result<u16> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        self.block_read(command, buffer)?;
+
+        Ok(((buffer[0] as u16) << 8) | buffer[1] as u16)
+    }
+
+    /// Lecture de N registres 8 bits consécutifs en une seule transaction
+    fn lecture_words<const N: usize>(&mut self, command: u8) -> anyhow::Result<[u8; N]> {
+        let mut buffer = [0u8; N];
+        self.block_read(command, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Lecture de N valeurs 16 bits signées consécutives (octet de poids fort en premier)
+    fn lecture_i16_be<const N: usize>(&mut self, command: u8) -> anyhow::Result<[i16; N]> {
+        let mut buffer = vec![0u8; N * 2];
+        self.block_read(command, &mut buffer)?;
+
+        let mut values = [0i16; N];
+        for (value, bytes) in values.iter_mut().zip(buffer.chunks_exact(2)) {
+            *value = i16::from_be_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(values)
+    }
+
+    /// Ecriture de N valeurs 16 bits signées consécutives (octet de poids fort en premier)
+    fn ecriture_i16_be<const N: usize>(&mut self, command: u8, values: [i16; N]) -> anyhow::Result<()> {
+        let buffer: Vec<u8> = values.iter().flat_map(|value| value.to_be_bytes()).collect();
+        self.block_write(command, &buffer)
+    }
+
+    /// Lecture de N valeurs 16 bits signées consécutives (octet de poids faible en premier)
+    fn lecture_i16_le<const N: usize>(&mut self, command: u8) -> anyhow::Result<[i16; N]> {
+        let mut buffer = vec![0u8; N * 2];
+        self.block_read(command, &mut buffer)?;
+
+        let mut values = [0i16; N];
+        for (value, bytes) in values.iter_mut().zip(buffer.chunks_exact(2)) {
+            *value = i16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(values)
+    }
+
+    /// Ecrit un bit sur la position donnée d'un registre 8 bits
+    fn ecriture_bit8(&mut self, command: u8, bit: u8, state: bool) -> anyhow::Result<()> {
+        let buffer: &mut [u8] = &mut [0];
+        self.block_read(command, buffer)?;
+        //println!("SET BIT: {:#04x} {} {}", command, bit, state);
+        //println!("OLD: {:08b}", buffer[0]);
+        if state {
+            buffer[0] |= 1 << bit;
+        } else {
+            buffer[0] &= !(1 << bit);
+        }
+        //println!("NEW: {:08b}", buffer[0]);
+
+        self.block_write(command, buffer)
+    }
+
+    /// Ecrit un bit sur la position donnée d'un registre 16 bits
+    fn ecriture_bit16(&mut self, command: u8, bit: u8, state: bool) -> anyhow::Result<()> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        self.block_read(command, buffer)?;
+        let mut data: u16 = ((buffer[0] as u16) << 8) | buffer[1] as u16;
+
+        if state {
+            data |= 1 << bit;
+        } else {
+            data &= !(1 << bit);
+        }
+
+        buffer[0] = (data >> 8) as u8;
+        buffer[1] = data as u8;
+
+        self.block_write(command, buffer)
+    }
+
+    /// Lis un bit sur la position donnée d'un registre 8 bits
+    fn lecture_bit8(&mut self, command: u8, bit: u8) -> anyhow::Result<bool> {
+        let buffer: &mut [u8] = &mut [0];
+        self.block_read(command, buffer)?;
+
+        Ok((buffer[0] & (1 << bit)) == (1 << bit))
+    }
+
+    /// Lis un bit sur la position donnée d'un registre 16 bits
+    fn lecture_bit16(&mut self, command: u8, bit: u8) -> anyhow::Result<bool> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        self.block_read(command, buffer)?;
+        let data: u16 = ((buffer[0] as u16) << 8) | buffer[1] as u16;
+
+        Ok((data & (1 << bit)) == (1 << bit))
+    }
+
+    /// Lis un ensemble de bits sur une position donnée d'un registre 8 bits
+    fn lecture_bits8(&mut self, command: u8, bit: u8, lenght: u8) -> anyhow::Result<u8> {
+        let buffer: &mut [u8] = &mut [0];
+        self.block_read(command, buffer)?;
+
+        let filtre = ((1u8 << lenght) - 1) << bit;
+
+        Ok((buffer[0] & filtre) >> bit)
+    }
+
+    /// Lis un ensemble de bits sur une position donnée d'un registre 16 bits
+    fn lecture_bits16(&mut self, command: u8, bit: u8, lenght: u8) -> anyhow::Result<u16> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        self.block_read(command, buffer)?;
+        let data: u16 = ((buffer[0] as u16) << 8) | buffer[1] as u16;
+
+        let filtre = ((1u16 << lenght) - 1) << bit;
+
+        Ok((data & filtre) >> bit)
+    }
+
+    /// Ecrit un ensemble de bits sur une position donnée d'un registre 8 bits
+    fn ecriture_bits8(&mut self, command: u8, bit: u8, lenght: u8, value_to_write: u8) -> anyhow::Result<()> {
+        let buffer: &mut [u8] = &mut [0];
+        self.block_read(command, buffer)?;
+
+        //println!("SET BITS: C[{:#04x}] B[{}] L[{}] => {:08b} ({:#04x})", command, bit, lenght, value_to_write, value_to_write);
+        //println!("OLD REG: {:08b}", buffer[0]);
+
+        let filtre_nettoyage = !(((1u8 << lenght) - 1) << bit);
+        //println!("FILTRE : {:08b}", filtre_nettoyage);
+
+        buffer[0] &= filtre_nettoyage;
+        buffer[0] |= value_to_write << bit;
+        //println!("NEW REG: {:08b}", buffer[0]);
+
+        self.block_write(command, buffer)
+    }
+
+    /// Ecrit un ensemble de bits sur une position donnée d'un registre 16 bits
+    fn ecriture_bits16(&mut self, command: u8, bit: u8, lenght: u8, value_to_write: u16) -> anyhow::Result<()> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        self.block_read(command, buffer)?;
+        let mut data: u16 = ((buffer[0] as u16) << 8) | buffer[1] as u16;
+
+        //println!("SET BITS: C[{:#04x}] B[{}] L[{}] => {:08b} ({:#04x})", command, bit, lenght, value_to_write, value_to_write);
+        //println!("OLD REG: {:08b}", buffer[0]);
+
+        let filtre_nettoyage = !(((1u16 << lenght) - 1) << bit);
+        //println!("FILTRE : {:08b}", filtre_nettoyage);
+
+        data &= filtre_nettoyage;
+        data |= value_to_write << bit;
+        //println!("NEW REG: {:08b}", buffer[0]);
+
+        buffer[0] = (data >> 8) as u8;
+        buffer[1] = data as u8;
+
+        self.block_write(command, buffer)?;
+        Ok(())
+    }
+
+    /// Lecture d'un registre typé (8 ou 16 bits)
+    fn lecture_reg<A: Readable>(&mut self, register: Register<A>) -> anyhow::Result<u16> {
+        let buffer: &mut [u8] = &mut [0, 0];
+        let buffer = &mut buffer[..register.len()];
+        self.block_read(register.address(), buffer)?;
+
+        Ok(buffer.iter().fold(0u16, |data, &b| (data << 8) | b as u16))
+    }
+
+    /// Ecriture d'un registre typé (8 ou 16 bits)
+    fn ecriture_reg<A: Writable>(&mut self, register: Register<A>, data: u16) -> anyhow::Result<()> {
+        if register.width() < 16 && data >> register.width() != 0 {
+            return Err(anyhow!("[I2C] Valeur {:#06x} trop grande pour le registre {:#04x}", data, register.address()));
+        }
+
+        let bytes = data.to_be_bytes();
+        self.block_write(register.address(), &bytes[2 - register.len()..])
+    }
+
+    /// Lecture d'un champ de bits typé
+    fn lecture_field<A: Readable, V: FieldValue>(&mut self, field: Field<A, V>) -> anyhow::Result<V> {
+        let data = self.lecture_reg(field.register())?;
+
+        field.decode(data).ok_or(anyhow!(
+            "[I2C] Valeur {:#04x} inconnue (registre {:#04x}, bits {}..{})",
+            field.bits(data),
+            field.register().address(),
+            field.offset(),
+            field.offset() + field.len()
+        ))
+    }
+
+    /// Ecrit un champ de bits typé (lecture, modification puis écriture du registre)
+    fn ecriture_field<A: Readable + Writable, V: FieldValue>(&mut self, field: Field<A, V>, value: V) -> anyhow::Result<()> {
+        let bits = value.to_bits();
+        if bits & !field.mask() != 0 {
+            return Err(anyhow!(
+                "[I2C] Valeur {:#04x} trop grande pour le champ (registre {:#04x}, {} bits)",
+                bits,
+                field.register().address(),
+                field.len()
+            ));
+        }
+
+        let register = field.register();
+        let buffer: &mut [u8] = &mut [0, 0];
+        let buffer = &mut buffer[..register.len()];
+        self.read_modify_write(register.address(), buffer, &mut |bytes| {
+            let mut data = bytes.iter().fold(0u16, |data, &b| (data << 8) | b as u16);
+            data &= !(field.mask() << field.offset());
+            data |= bits << field.offset();
+
+            let data = data.to_be_bytes();
+            bytes.copy_from_slice(&data[2 - bytes.len()..]);
+        })
+    }
+}
+
+impl<T: I2CBus + ?Sized> I2CBit for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+
+    fn sim_bus() -> SimBus {
+        let mut bus = SimBus::new();
+        bus.attach(0x68, SimDevice::new());
+        bus.set_slave_address(0x68).unwrap();
+        bus
+    }
+
+    #[test]
+    fn signed_words_byte_order() {
+        let mut bus = sim_bus();
+        bus.device(0x68).unwrap().set_registers(0x3B, &[0x12, 0x34, 0xFF, 0xFE, 0x80, 0x00]);
+
+        // Octet de poids fort en premier (MPU6050, HMC5883L), puis en dernier (QMC5883L, BNO055)
+        assert_eq!(bus.lecture_i16_be::<3>(0x3B).unwrap(), [0x1234, -2, i16::MIN]);
+        assert_eq!(bus.lecture_i16_le::<3>(0x3B).unwrap(), [0x3412, -257, 0x0080]);
+        assert_eq!(bus.lecture_i16_be::<1>(0x3D).unwrap(), [-2]);
+
+        bus.ecriture_i16_be(0x13, [-33, 0x0102]).unwrap();
+        let device = bus.device(0x68).unwrap();
+        assert_eq!(device.registers(0x13, 4), &[0xFF, 0xDF, 0x01, 0x02]);
+        assert_eq!(bus.lecture_i16_be::<2>(0x13).unwrap(), [-33, 0x0102]);
+    }
+}
@@ -0,0 +1,348 @@
+#![allow(unused)]
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+
+use crate::i2c::I2CBus;
+
+// Format d'un enregistrement (une transaction par ligne) :
+//   # rctelemetrie-i2c <début en secondes UNIX>
+//   <µs depuis le début> <R|W> <adresse> <registre> <octets en hexa | !erreur>
+// Exemple : "1520 R 68 3B 00a4ff12"
+
+/// Transaction enregistrée
+#[derive(Clone, Debug, PartialEq)]
+pub struct I2CRecord {
+    pub time: Duration,
+    pub write: bool,
+    pub address: u16,
+    pub command: u8,
+    pub data: Result<Vec<u8>, String>,
+}
+
+impl I2CRecord {
+    fn to_line(&self) -> String {
+        let data = match &self.data {
+            Ok(data) => data.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            Err(e) => format!("!{}", e.replace('\n', " ")),
+        };
+
+        format!(
+            "{} {} {:02X} {:02X} {}",
+            self.time.as_micros(),
+            if self.write { 'W' } else { 'R' },
+            self.address,
+            self.command,
+            data
+        )
+    }
+
+    fn from_line(line: &str) -> anyhow::Result<Self> {
+        let mut parts = line.splitn(5, ' ');
+        let mut next = || parts.next().ok_or(anyhow!("[I2C] Ligne incomplète: {}", line));
+
+        let time = Duration::from_micros(next()?.parse()?);
+        let write = match next()? {
+            "W" => true,
+            "R" => false,
+            op => return Err(anyhow!("[I2C] Opération inconnue: {}", op)),
+        };
+        let address = u16::from_str_radix(next()?, 16)?;
+        let command = u8::from_str_radix(next()?, 16)?;
+        let data = parts.next().unwrap_or("");
+
+        let data = match data.strip_prefix('!') {
+            Some(e) => Err(e.to_string()),
+            None => {
+                if !data.len().is_multiple_of(2) {
+                    return Err(anyhow!("[I2C] Données invalides: {}", data));
+                }
+                let bytes = (0..data.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&data[i..i + 2], 16))
+                    .collect::<Result<Vec<u8>, _>>()?;
+                Ok(bytes)
+            }
+        };
+
+        Ok(Self { time, write, address, command, data })
+    }
+}
+
+/// Enregistre toutes les transactions d'un bus dans un fichier
+pub struct I2CRecorder<B> {
+    bus: B,
+    output: BufWriter<File>,
+    address: u16,
+    start: Instant,
+    last_flush: Instant,
+}
+
+impl<B: I2CBus> I2CRecorder<B> {
+    pub fn new(bus: B, path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut output = BufWriter::new(File::create(path.as_ref())?);
+        let epoch = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        writeln!(output, "# rctelemetrie-i2c {}", epoch)?;
+
+        println!("[I2C] Enregistrement du bus dans {}", path.as_ref().display());
+        Ok(Self {
+            bus,
+            output,
+            address: 0,
+            start: Instant::now(),
+            last_flush: Instant::now(),
+        })
+    }
+
+    fn record(&mut self, write: bool, command: u8, data: Result<Vec<u8>, String>) {
+        let record = I2CRecord {
+            time: self.start.elapsed(),
+            write,
+            address: self.address,
+            command,
+            data,
+        };
+
+        // L'enregistrement ne doit jamais bloquer la lecture des capteurs
+        if let Err(e) = writeln!(self.output, "{}", record.to_line()) {
+            eprintln!("[I2C] Erreur d'enregistrement: {}", e);
+        }
+
+        if self.last_flush.elapsed() > Duration::from_secs(1) {
+            let _ = self.output.flush();
+            self.last_flush = Instant::now();
+        }
+    }
+}
+
+impl<B: I2CBus> I2CBus for I2CRecorder<B> {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        self.bus.set_slave_address(address)?;
+        self.address = address;
+        Ok(())
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        let result = self.bus.block_read(command, buffer);
+        let data = match &result {
+            Ok(()) => Ok(buffer.to_vec()),
+            Err(e) => Err(e.to_string()),
+        };
+        self.record(false, command, data);
+        result
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        let result = self.bus.block_write(command, buffer);
+        let data = match &result {
+            Ok(()) => Ok(buffer.to_vec()),
+            Err(e) => Err(e.to_string()),
+        };
+        self.record(true, command, data);
+        result
+    }
+
+    fn reset(&mut self) -> anyhow::Result<()> {
+        self.bus.reset()
+    }
+}
+
+impl<B> Drop for I2CRecorder<B> {
+    fn drop(&mut self) {
+        let _ = self.output.flush();
+    }
+}
+
+/// Clé d'une file de transactions : esclave, registre, écriture
+type ReplayKey = (u16, u8, bool);
+
+/// Rejoue un enregistrement comme un bus I2C
+/// Les lectures retournent, dans l'ordre, les données enregistrées pour le même esclave et le même registre.
+/// Chaque esclave et chaque registre a sa propre file : l'ordre entre capteurs différents n'a pas d'importance.
+/// Les écritures doivent correspondre aux octets enregistrés (et échouent si elles avaient échoué lors de l'enregistrement).
+pub struct I2CReplay {
+    records: Vec<I2CRecord>,
+    queues: HashMap<ReplayKey, VecDeque<I2CRecord>>,
+    address: u16,
+}
+
+impl I2CReplay {
+    /// Charge un enregistrement depuis un fichier
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file = File::open(path.as_ref())?;
+        Self::parse(BufReader::new(file))
+    }
+
+    /// Charge un enregistrement depuis n'importe quelle source (ex: un fichier embarqué dans un test)
+    pub fn parse(input: impl BufRead) -> anyhow::Result<Self> {
+        let mut records = Vec::new();
+        for line in input.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            records.push(I2CRecord::from_line(line)?);
+        }
+
+        let mut queues: HashMap<ReplayKey, VecDeque<I2CRecord>> = HashMap::new();
+        for record in &records {
+            queues
+                .entry((record.address, record.command, record.write))
+                .or_default()
+                .push_back(record.clone());
+        }
+
+        Ok(Self { records, queues, address: 0 })
+    }
+
+    /// Transactions chargées
+    pub fn records(&self) -> &[I2CRecord] {
+        &self.records
+    }
+
+    /// Vrai si toutes les lectures ont été rejouées
+    pub fn is_finished(&self) -> bool {
+        self.queues.iter().all(|((_, _, write), queue)| *write || queue.is_empty())
+    }
+
+    /// Prochaine transaction enregistrée pour l'esclave actuel et ce registre
+    fn next(&mut self, write: bool, command: u8) -> Option<I2CRecord> {
+        self.queues.get_mut(&(self.address, command, write))?.pop_front()
+    }
+}
+
+impl I2CBus for I2CReplay {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        self.address = address;
+        Ok(())
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        let record = self.next(false, command).ok_or(anyhow!(
+            "[I2C] Fin de l'enregistrement (lecture {:#04x} / {:#04x})",
+            self.address,
+            command
+        ))?;
+
+        let data = record.data.map_err(|e| anyhow!(e))?;
+        if data.len() != buffer.len() {
+            return Err(anyhow!(
+                "[I2C] Taille de lecture différente de l'enregistrement ({} / {} octets)",
+                buffer.len(),
+                data.len()
+            ));
+        }
+
+        buffer.copy_from_slice(&data);
+        Ok(())
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        // Les écritures au-delà de l'enregistrement sont acceptées
+        let Some(record) = self.next(true, command) else {
+            return Ok(());
+        };
+
+        let data = record.data.map_err(|e| anyhow!(e))?;
+        if data != buffer {
+            return Err(anyhow!(
+                "[I2C] Ecriture différente de l'enregistrement ({:#04x} / {:#04x} à {} µs: {:02x?} au lieu de {:02x?})",
+                self.address,
+                command,
+                record.time.as_micros(),
+                buffer,
+                data
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+    use crate::i2c::I2CBit;
+
+    #[test]
+    fn recorded_bus_replayed_per_device() {
+        let mut bus = SimBus::new();
+        for (address, value) in [(0x10, 0x1234u16), (0x20, 0xABCD)] {
+            let mut device = SimDevice::new();
+            device.set_registers(0x05, &value.to_be_bytes());
+            bus.attach(address, device);
+        }
+
+        let path = std::env::temp_dir().join(format!("rctelemetrie-replay-{}.i2c", std::process::id()));
+        let mut recorder = I2CRecorder::new(bus, &path).unwrap();
+        let mut expected = Vec::new();
+        for address in [0x10, 0x20, 0x10, 0x20] {
+            recorder.set_slave_address(address).unwrap();
+            expected.push((address, recorder.lecture_dword(0x05).unwrap()));
+            recorder.ecriture_dword(0x05, address + 1).unwrap();
+        }
+        drop(recorder);
+
+        // Les lectures d'un esclave ne sont pas consommées par celles de l'autre, quel que soit l'ordre
+        let mut replay = I2CReplay::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay.records().len(), 8);
+
+        expected.sort_by_key(|(address, _)| *address);
+        assert_eq!(expected, vec![(0x10, 0x1234), (0x10, 0x0011), (0x20, 0xABCD), (0x20, 0x0021)]);
+        for (address, value) in expected {
+            replay.set_slave_address(address).unwrap();
+            assert_eq!(replay.lecture_dword(0x05).unwrap(), value);
+            replay.ecriture_dword(0x05, address + 1).unwrap();
+        }
+
+        assert!(replay.is_finished());
+        assert!(replay.lecture_dword(0x05).is_err());
+
+        // Une écriture différente de celle enregistrée est refusée
+        let mut replay = I2CReplay::parse("250 W 10 05 0011".as_bytes()).unwrap();
+        replay.set_slave_address(0x10).unwrap();
+        assert!(replay.ecriture_dword(0x05, 0x12).is_err());
+    }
+
+    #[cfg(feature = "real-sensors")]
+    #[test]
+    fn capture_replayed_through_drivers() {
+        use nalgebra::Vector3;
+
+        use crate::config::Config;
+        use crate::sensors::imu::calibration::{ImuCalibration, ImuHardwareOffsets};
+        use crate::sensors::imu::driver::{ImuDriver, MagInput};
+        use crate::sensors::imu::imu::IMU;
+        use crate::sensors::imu::ImuChip;
+        use crate::sensors::mag::hmc8553l::HMC8553L;
+
+        // MPU6050 (calibration enregistrée) puis HMC5883L, et deux échantillons dans la FIFO
+        let mut replay = I2CReplay::parse(include_str!("fixtures/imu_hmc.i2c").as_bytes()).unwrap();
+        let mut config = Config::new();
+        config.imu_calibration = Some(ImuCalibration {
+            gyro: Vector3::zeros(),
+            accel: Vector3::zeros(),
+            hardware: Some(ImuHardwareOffsets { gyro: Vector3::new(12, -7, 3), accel: Vector3::new(-1500, 820, 1210) }),
+            gyro_temp: None,
+        });
+
+        // Ordre différent de l'enregistrement : le magnétomètre est initialisé en premier
+        let mut mag = HMC8553L::new(&mut replay, config.clone()).unwrap();
+        let mut imu = IMU::new(&mut replay, &config, ImuChip::Mpu6050).unwrap();
+        assert_eq!(mag.get_mag_axes_raw(&mut replay).unwrap(), Some(Vector3::new(120, -35, -410)));
+
+        assert_eq!(imu.update(&mut replay, MagInput::Field(None)).unwrap(), 2);
+        assert_eq!(imu.get_raw(), (Vector3::new(-205, 0, 16384), Vector3::new(131, 0, -262)));
+        assert_eq!(imu.get_timestamp(), Duration::from_millis(10));
+        assert!((imu.get_temp() - 33.03).abs() < 1e-3);
+        assert!(replay.is_finished());
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::anyhow;
+
+use crate::i2c::I2CBus;
+
+/// Réponse programmée d'un périphérique simulé
+#[derive(Clone, Debug)]
+pub enum SimResponse {
+    /// Octets retournés à la place du contenu des registres
+    Data(Vec<u8>),
+    /// Le périphérique ne répond pas (NACK)
+    Nack,
+}
+
+/// Transaction enregistrée sur le bus simulé
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimTransaction {
+    Read { address: u16, command: u8, len: usize },
+    Write { address: u16, command: u8, data: Vec<u8> },
+}
+
+/// Périphérique simulé : une table de registres et des réponses programmées
+pub struct SimDevice {
+    register_size: usize,
+    registers: Vec<u8>,
+    read_script: HashMap<u8, VecDeque<SimResponse>>,
+    write_script: HashMap<u8, VecDeque<SimResponse>>,
+}
+
+impl SimDevice {
+    /// Périphérique avec des registres de 8 bits (auto-incrément sur les lectures en rafale)
+    pub fn new() -> Self {
+        Self::with_register_size(1)
+    }
+
+    /// Périphérique avec des registres de `register_size` octets (ex: 2 pour l'ADS1115)
+    pub fn with_register_size(register_size: usize) -> Self {
+        Self {
+            register_size,
+            registers: vec![0; 256 * register_size],
+            read_script: HashMap::new(),
+            write_script: HashMap::new(),
+        }
+    }
+
+    /// Défini le contenu d'un ou plusieurs registres à partir du registre donné
+    pub fn set_registers(&mut self, command: u8, data: &[u8]) {
+        let start = command as usize * self.register_size;
+        self.registers[start..start + data.len()].copy_from_slice(data);
+    }
+
+    /// Récupére le contenu des registres à partir du registre donné
+    pub fn registers(&self, command: u8, len: usize) -> &[u8] {
+        let start = command as usize * self.register_size;
+        &self.registers[start..start + len]
+    }
+
+    /// Ajoute une réponse programmée pour la prochaine lecture du registre
+    pub fn script_read(&mut self, command: u8, response: SimResponse) {
+        self.read_script.entry(command).or_default().push_back(response);
+    }
+
+    /// Ajoute une réponse programmée pour la prochaine écriture du registre (`Data` est ignoré)
+    pub fn script_write(&mut self, command: u8, response: SimResponse) {
+        self.write_script.entry(command).or_default().push_back(response);
+    }
+
+    fn read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        match self.read_script.get_mut(&command).and_then(|q| q.pop_front()) {
+            Some(SimResponse::Nack) => return Err(anyhow!("[SIM] NACK en lecture ({:#04x})", command)),
+            Some(SimResponse::Data(data)) => {
+                for (i, b) in buffer.iter_mut().enumerate() {
+                    *b = data.get(i).copied().unwrap_or(0);
+                }
+                return Ok(());
+            }
+            None => {}
+        }
+
+        let start = command as usize * self.register_size;
+        if start + buffer.len() > self.registers.len() {
+            return Err(anyhow!("[SIM] Lecture hors registres ({:#04x})", command));
+        }
+
+        buffer.copy_from_slice(&self.registers[start..start + buffer.len()]);
+        Ok(())
+    }
+
+    fn write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        if let Some(SimResponse::Nack) = self.write_script.get_mut(&command).and_then(|q| q.pop_front()) {
+            return Err(anyhow!("[SIM] NACK en écriture ({:#04x})", command));
+        }
+
+        let start = command as usize * self.register_size;
+        if start + buffer.len() > self.registers.len() {
+            return Err(anyhow!("[SIM] Ecriture hors registres ({:#04x})", command));
+        }
+
+        self.registers[start..start + buffer.len()].copy_from_slice(buffer);
+        Ok(())
+    }
+}
+
+impl Default for SimDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bus I2C simulé en mémoire, un périphérique par adresse
+#[derive(Default)]
+pub struct SimBus {
+    address: Option<u16>,
+    devices: HashMap<u16, SimDevice>,
+    transactions: Vec<SimTransaction>,
+}
+
+impl SimBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Branche un périphérique à l'adresse donnée
+    pub fn attach(&mut self, address: u16, device: SimDevice) {
+        self.devices.insert(address, device);
+    }
+
+    /// Accès au périphérique branché à l'adresse donnée
+    pub fn device(&mut self, address: u16) -> Option<&mut SimDevice> {
+        self.devices.get_mut(&address)
+    }
+
+    /// Historique des transactions effectuées sur le bus
+    pub fn transactions(&self) -> &[SimTransaction] {
+        &self.transactions
+    }
+
+    /// Vide l'historique des transactions
+    pub fn clear_transactions(&mut self) {
+        self.transactions.clear();
+    }
+
+    fn current(&mut self) -> anyhow::Result<(u16, &mut SimDevice)> {
+        let address = self.address.ok_or(anyhow!("[SIM] Aucun esclave sélectionné"))?;
+        let device = self
+            .devices
+            .get_mut(&address)
+            .ok_or(anyhow!("[SIM] Aucun périphérique à l'adresse {:#04x}", address))?;
+        Ok((address, device))
+    }
+}
+
+impl I2CBus for SimBus {
+    fn set_slave_address(&mut self, address: u16) -> anyhow::Result<()> {
+        self.address = Some(address);
+        Ok(())
+    }
+
+    fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        let (address, device) = self.current()?;
+        device.read(command, buffer)?;
+        self.transactions.push(SimTransaction::Read { address, command, len: buffer.len() });
+        Ok(())
+    }
+
+    fn block_write(&mut self, command: u8, buffer: &[u8]) -> anyhow::Result<()> {
+        let (address, device) = self.current()?;
+        device.write(command, buffer)?;
+        self.transactions.push(SimTransaction::Write { address, command, data: buffer.to_vec() });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_write_and_transactions() {
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.script_write(0x10, SimResponse::Nack);
+        bus.attach(0x42, device);
+        bus.set_slave_address(0x42).unwrap();
+
+        // La première écriture est refusée et n'est pas enregistrée
+        assert!(bus.block_write(0x10, &[0x01]).is_err());
+        bus.block_write(0x10, &[0x02]).unwrap();
+        let mut buffer = [0; 1];
+        bus.block_read(0x10, &mut buffer).unwrap();
+        assert_eq!(buffer, [0x02]);
+
+        assert_eq!(
+            bus.transactions(),
+            &[
+                SimTransaction::Write { address: 0x42, command: 0x10, data: vec![0x02] },
+                SimTransaction::Read { address: 0x42, command: 0x10, len: 1 },
+            ]
+        );
+        bus.clear_transactions();
+        assert!(bus.transactions().is_empty());
+    }
+}
@@ -1,18 +1,18 @@
+#![allow(clippy::upper_case_acronyms, clippy::module_inception)]
+
 mod cli;
 mod actuators;
 mod database;
 mod sensors;
 mod config;
 
-#[cfg(feature = "real-sensors")]
 mod i2c;
 
 use std::{
-    sync::Arc,
+    sync::{atomic::Ordering, Arc},
     time::Duration,
 };
 
-use actuators::esc;
 use clap::Parser;
 use database::Database;
 use futures::StreamExt;
@@ -38,7 +38,7 @@ async fn main() {
     let args = crate::cli::Cli::parse();
 
     // Vérifie si le UUID est valide
-    if let Err(_) = Uuid::parse_str(&args.uuid) {
+    if Uuid::parse_str(&args.uuid).is_err() {
         eprintln!("[MAIN] UUID invalide. Veuillez spécifier un UUID valide.");
         return;
     }
@@ -63,14 +63,16 @@ async fn main() {
     let config = db.get_config().await.expect("[DB] Erreur lors de la récupération de la configuration.");
 
     // Capteur
-    let sensors_task = {
+    let (sensors_task, mag_calibration_mode) = {
         let token = token.child_token();
         let config = config.clone();
 
-        let mut reader = sensors::reader::Reader::new(token.clone(), config).expect("[CAPTEURS] Impossible de gérer les capteurs.");
+        let i2c_bus = i2c::open_bus(config.i2c_bus, args.i2c_record.as_deref(), args.i2c_replay.as_deref()).expect("[I2C] Erreur de bus");
+        let mut reader = sensors::reader::Reader::new(token.clone(), config, i2c_bus).expect("[CAPTEURS] Impossible de gérer les capteurs.");
+        let mag_calibration_mode = reader.mag_calibration_mode();
         let db = db.clone();
     
-        tokio::spawn(async move {
+        let task = tokio::spawn(async move {
             while !token.is_cancelled() {
                 if let Some(data) = reader.next().await {
                     if let Ok(data) = data {
@@ -80,12 +82,28 @@ async fn main() {
                         let _ = db.send_sensors(data).await;
                     }
 
+                    if let Some(calibration) = reader.take_imu_calibration() {
+                        match db.save_imu_calibration(calibration).await {
+                            Ok(()) => println!("[DB] Calibration de l'IMU enregistrée."),
+                            Err(e) => eprintln!("[DB] Erreur lors de l'enregistrement de la calibration: {}", e),
+                        }
+                    }
+
+                    if let Some(calibration) = reader.take_mag_calibration() {
+                        match db.save_mag_calibration(calibration).await {
+                            Ok(()) => println!("[DB] Calibration du magnétomètre enregistrée."),
+                            Err(e) => eprintln!("[DB] Erreur lors de l'enregistrement de la calibration: {}", e),
+                        }
+                    }
+
                     sleep(Duration::from_millis(1000 / 30)).await;
                 }
             }
 
             println!("[CAPTEURS] Fin de la tâche de mise à jour de la BDD.");
-        })
+        });
+
+        (task, mag_calibration_mode)
     };
 
     // Modem 4G
@@ -276,7 +294,7 @@ async fn main() {
     };
 
     // Switch (Activation fonction unique)
-    let _ = {
+    let _switch_task = {
         let parent = token.clone();
         let token = token.child_token();
         let db = db.clone();
@@ -303,6 +321,7 @@ async fn main() {
                             while !token.is_cancelled() {
                                 if let Some(Ok(data)) = stream.next().await {
                                     if data.data.esc { esc.start() } else { esc.stop() };
+                                    mag_calibration_mode.store(data.data.mag_calibration, Ordering::Relaxed);
                                     if data.data.reload {
                                         println!("[SWITCH] Redémarrage du logiciel de télémétrie ...");
                                         parent.cancel();
@@ -0,0 +1,272 @@
+use nalgebra::{Matrix3x4, Quaternion, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Ecart maximal (en g) entre la norme de l'accélération et la gravité pour corriger l'orientation.
+/// Au delà, la voiture accélère ou tourne : seul le gyroscope est utilisé.
+const ACCEL_REJECTION: f32 = 0.15;
+
+/// Correction maximale (en rad/s, par axe) accumulée par l'intégrale du filtre de Mahony
+/// Limite la dérive (windup) pendant une longue erreur, ex: une accélération prise pour la gravité.
+const MAHONY_INTEGRAL_LIMIT: f32 = 0.1;
+
+/// Filtre utilisé pour fusionner gyroscope, accéléromètre et magnétomètre
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum AhrsFilter {
+    /// Descente de gradient (Madgwick), gain `madgwick_beta`
+    Madgwick,
+    /// Correcteur PI, gains `mahony_kp` et `mahony_ki`
+    Mahony,
+}
+
+/// Estimation de l'orientation (AHRS) sous forme de quaternion
+/// Repère terrestre : X vers le nord magnétique, Z vers le haut.
+pub(crate) struct Ahrs {
+    filter: AhrsFilter,
+    beta: f32,
+    kp: f32,
+    ki: f32,
+    mag_decl: f32,
+    quaternion: UnitQuaternion<f32>,
+    integral: Vector3<f32>,
+    initialized: bool,
+}
+
+impl Ahrs {
+    pub(crate) fn new(config: &Config) -> Self {
+        let mut ahrs = Self {
+            filter: config.ahrs_filter,
+            beta: 0.0,
+            kp: 0.0,
+            ki: 0.0,
+            mag_decl: 0.0,
+            quaternion: UnitQuaternion::identity(),
+            integral: Vector3::zeros(),
+            initialized: false,
+        };
+        ahrs.set_config(config);
+        ahrs
+    }
+
+    /// Mets à jour les gains du filtre
+    pub(crate) fn set_config(&mut self, config: &Config) {
+        if self.filter != config.ahrs_filter {
+            self.integral = Vector3::zeros();
+        }
+
+        self.filter = config.ahrs_filter;
+        self.beta = config.madgwick_beta;
+        self.kp = config.mahony_kp;
+        self.ki = config.mahony_ki;
+        self.mag_decl = config.mag_decl;
+    }
+
+    /// Mets à jour la déclinaison magnétique (en degrés) ajoutée au cap
+    pub(crate) fn set_mag_decl(&mut self, mag_decl: f32) {
+        self.mag_decl = mag_decl;
+    }
+
+    /// Repart de l'orientation donnée par la prochaine mesure
+    pub(crate) fn reset(&mut self) {
+        self.quaternion = UnitQuaternion::identity();
+        self.integral = Vector3::zeros();
+        self.initialized = false;
+    }
+
+    /// Nouvelle mesure : gyroscope en rad/s, accélération en g, champ magnétique (corrigé, unité quelconque)
+    pub(crate) fn update(&mut self, gyro: Vector3<f32>, accel: Vector3<f32>, mag: Option<Vector3<f32>>, dt: f32) {
+        // Accélération trop différente de la gravité : pas de correction
+        let gravity = accel_is_gravity(accel);
+
+        // Les mesures nulles ne donnent aucune direction
+        let accel = accel.try_normalize(f32::EPSILON);
+        let mag = mag.and_then(|m| m.try_normalize(f32::EPSILON));
+
+        if !self.initialized {
+            if let Some(accel) = accel {
+                self.quaternion = Self::initial_orientation(accel, mag);
+                self.initialized = true;
+            }
+            return;
+        }
+
+        let accel = accel.filter(|_| gravity);
+
+        match self.filter {
+            AhrsFilter::Madgwick => self.update_madgwick(gyro, accel, mag, dt),
+            AhrsFilter::Mahony => self.update_mahony(gyro, accel, mag, dt),
+        }
+    }
+
+    /// Orientation du capteur
+    pub(crate) fn quaternion(&self) -> UnitQuaternion<f32> {
+        self.quaternion
+    }
+
+    /// Roulis, tangage (en degrés) et cap (en degrés de 0 à 360, déclinaison comprise)
+    pub(crate) fn euler(&self) -> Vector3<f32> {
+        let (roll, pitch, yaw) = self.quaternion.euler_angles();
+
+        // Le lacet est compté dans le sens trigonométrique, le cap dans le sens horaire
+        let heading = (-yaw.to_degrees() + self.mag_decl).rem_euclid(360.0);
+        Vector3::new(roll.to_degrees(), pitch.to_degrees(), heading)
+    }
+
+    /// Orientation directement obtenue par l'accéléromètre (et le magnétomètre)
+    fn initial_orientation(accel: Vector3<f32>, mag: Option<Vector3<f32>>) -> UnitQuaternion<f32> {
+        let roll = accel.y.atan2(accel.z);
+        let pitch = (-accel.x).atan2((accel.y * accel.y + accel.z * accel.z).sqrt());
+        let level = UnitQuaternion::from_euler_angles(roll, pitch, 0.0);
+
+        let yaw = match mag {
+            Some(mag) => {
+                let horizontal = level.transform_vector(&mag);
+                -horizontal.y.atan2(horizontal.x)
+            }
+            None => 0.0,
+        };
+
+        UnitQuaternion::from_euler_angles(roll, pitch, yaw)
+    }
+
+    /// Gradient de la fonction objectif de Madgwick : écart entre les directions de référence (gravité, champ
+    /// terrestre) ramenées dans le repère du capteur et les directions mesurées, dérivé par rapport au quaternion
+    fn gradient(&self, accel: Vector3<f32>, mag: Option<Vector3<f32>>) -> Quaternion<f32> {
+        let q = self.quaternion.into_inner();
+        let (q0, q1, q2, q3) = (q.w, q.i, q.j, q.k);
+
+        // Gravité (0, 0, 1)
+        let f = Vector3::new(
+            2.0 * (q1 * q3 - q0 * q2) - accel.x,
+            2.0 * (q0 * q1 + q2 * q3) - accel.y,
+            2.0 * (0.5 - q1 * q1 - q2 * q2) - accel.z,
+        );
+        let j = Matrix3x4::new(
+            -2.0 * q2, 2.0 * q3, -2.0 * q0, 2.0 * q1,
+            2.0 * q1, 2.0 * q0, 2.0 * q3, 2.0 * q2,
+            0.0, -4.0 * q1, -4.0 * q2, 0.0,
+        );
+        let mut gradient = j.transpose() * f;
+
+        // Champ terrestre (bx, 0, bz) : inclinaison de la mesure ramenée dans le repère terrestre
+        if let Some(mag) = mag {
+            let h = self.quaternion.transform_vector(&mag);
+            let (bx, bz) = ((h.x * h.x + h.y * h.y).sqrt(), h.z);
+            let f = Vector3::new(
+                2.0 * bx * (0.5 - q2 * q2 - q3 * q3) + 2.0 * bz * (q1 * q3 - q0 * q2) - mag.x,
+                2.0 * bx * (q1 * q2 - q0 * q3) + 2.0 * bz * (q0 * q1 + q2 * q3) - mag.y,
+                2.0 * bx * (q0 * q2 + q1 * q3) + 2.0 * bz * (0.5 - q1 * q1 - q2 * q2) - mag.z,
+            );
+            let j = Matrix3x4::new(
+                -2.0 * bz * q2, 2.0 * bz * q3, -4.0 * bx * q2 - 2.0 * bz * q0, -4.0 * bx * q3 + 2.0 * bz * q1,
+                -2.0 * bx * q3 + 2.0 * bz * q1, 2.0 * bx * q2 + 2.0 * bz * q0, 2.0 * bx * q1 + 2.0 * bz * q3, -2.0 * bx * q0 + 2.0 * bz * q2,
+                2.0 * bx * q2, 2.0 * bx * q3 - 4.0 * bz * q1, 2.0 * bx * q0 - 4.0 * bz * q2, 2.0 * bx * q1,
+            );
+            gradient += j.transpose() * f;
+        }
+
+        Quaternion::new(gradient[0], gradient[1], gradient[2], gradient[3])
+    }
+
+    /// Erreur entre les directions mesurées et celles estimées à partir de l'orientation actuelle
+    /// (produit vectoriel mesure x estimation, dans le repère du capteur)
+    fn error(&self, accel: Option<Vector3<f32>>, mag: Option<Vector3<f32>>) -> Vector3<f32> {
+        let mut error = Vector3::zeros();
+
+        if let Some(accel) = accel {
+            let gravity = self.quaternion.inverse_transform_vector(&Vector3::z());
+            error += accel.cross(&gravity);
+
+            // Le magnétomètre ne corrige que le cap, son inclinaison est celle de l'estimation
+            if let Some(mag) = mag {
+                let h = self.quaternion.transform_vector(&mag);
+                let reference = Vector3::new((h.x * h.x + h.y * h.y).sqrt(), 0.0, h.z);
+                error += mag.cross(&self.quaternion.inverse_transform_vector(&reference));
+            }
+        }
+
+        error
+    }
+
+    fn update_madgwick(&mut self, gyro: Vector3<f32>, accel: Option<Vector3<f32>>, mag: Option<Vector3<f32>>, dt: f32) {
+        let q = self.quaternion.into_inner();
+        let mut q_dot = q * Quaternion::from_imag(gyro) * 0.5;
+
+        // Descente de gradient : un pas de `beta` dans la direction qui réduit l'écart (sans accélération, gyroscope seul)
+        if let Some(accel) = accel {
+            let step = self.gradient(accel, mag);
+            if step.norm() > f32::EPSILON {
+                q_dot -= step.normalize() * self.beta;
+            }
+        }
+
+        self.quaternion = UnitQuaternion::from_quaternion(q + q_dot * dt);
+    }
+
+    fn update_mahony(&mut self, gyro: Vector3<f32>, accel: Option<Vector3<f32>>, mag: Option<Vector3<f32>>, dt: f32) {
+        let error = self.error(accel, mag);
+
+        if self.ki > 0.0 {
+            self.integral += error * (self.ki * dt);
+            self.integral = self.integral.map(|v| v.clamp(-MAHONY_INTEGRAL_LIMIT, MAHONY_INTEGRAL_LIMIT));
+        }
+
+        let gyro = gyro + error * self.kp + self.integral;
+        self.quaternion *= UnitQuaternion::from_scaled_axis(gyro * dt);
+    }
+}
+
+/// Vrai si l'accélération (en g) ne mesure (presque) que la gravité
+fn accel_is_gravity(accel: Vector3<f32>) -> bool {
+    (accel.norm() - 1.0).abs() <= ACCEL_REJECTION
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converges_to_level_and_magnetic_heading() {
+        for filter in [AhrsFilter::Madgwick, AhrsFilter::Mahony] {
+            let mut config = Config::new();
+            config.ahrs_filter = filter;
+            config.mag_decl = 0.0;
+            let mut ahrs = Ahrs::new(&config);
+
+            // Démarre à plat face au nord, puis le capteur est immobile face à l'est (cap 90°)
+            ahrs.update(Vector3::zeros(), Vector3::z(), Some(Vector3::new(0.4, 0.0, -0.3)), 0.01);
+            for _ in 0..5000 {
+                ahrs.update(Vector3::zeros(), Vector3::z(), Some(Vector3::new(0.0, 0.4, -0.3)), 0.01);
+            }
+
+            let euler = ahrs.euler();
+            assert!(euler.x.abs() < 1.0 && euler.y.abs() < 1.0, "{:?}: {:?}", filter, euler);
+            assert!((euler.z - 90.0).abs() < 1.0, "{:?}: {:?}", filter, euler);
+
+            // Capteur penché de 20° en roulis
+            let tilt = UnitQuaternion::from_euler_angles(20.0_f32.to_radians(), 0.0, 0.0);
+            for _ in 0..5000 {
+                ahrs.update(Vector3::zeros(), tilt.inverse_transform_vector(&Vector3::z()), None, 0.01);
+            }
+            assert!((ahrs.euler().x - 20.0).abs() < 1.0, "{:?}: {:?}", filter, ahrs.euler());
+        }
+    }
+
+    #[test]
+    fn mahony_integral_bounded() {
+        let mut config = Config::new();
+        config.ahrs_filter = AhrsFilter::Mahony;
+        config.mahony_ki = 5.0;
+        let mut ahrs = Ahrs::new(&config);
+
+        // Biais du gyroscope de 1 rad/s en roulis, corrigé en permanence par l'accéléromètre
+        ahrs.update(Vector3::zeros(), Vector3::z(), None, 0.01);
+        for _ in 0..5000 {
+            ahrs.update(Vector3::new(1.0, 0.0, 0.0), Vector3::z(), None, 0.01);
+        }
+
+        assert!(ahrs.integral.amax() <= MAHONY_INTEGRAL_LIMIT, "{:?}", ahrs.integral);
+        assert!(ahrs.integral.x < -0.09);
+    }
+}
@@ -1,8 +1,7 @@
 #![allow(unused)]
 
-use crate::i2c::I2CBit;
+use crate::i2c::{I2CBit, I2CBus};
 use nalgebra::Vector3;
-use rppal::i2c::I2c;
 use std::fmt;
 use std::thread::sleep;
 use std::time::Duration;
@@ -11,14 +10,18 @@ use std::{error::Error, task::Poll};
 use tokio_stream::Stream;
 
 use crate::sensors::analog::registry;
+use crate::sensors::analog::registry::{Ads1115DataRate, Ads1115Mux, Ads1115Pga};
 
 pub(crate) struct Analog {}
 
 // Voir documentation : https://www.ti.com/lit/ds/symlink/ads1118.pdf
 
 impl Analog {
+    /// Adresse du module sur le bus I2C
+    pub(crate) const ADDR: u16 = registry::ANALOG_ADDR;
+
     /// Constructeur
-    pub(crate) fn new(i2c: &mut I2c) -> anyhow::Result<Self> {        
+    pub(crate) fn new<B: I2CBus>(i2c: &mut B) -> anyhow::Result<Self> {        
         // Créer l'objet et commence l'initialisation
         let mut analog = Analog {};
 
@@ -28,129 +31,114 @@ impl Analog {
         Ok(analog)
     }
 
-    fn set_slave(&self, i2c: &mut I2c) -> anyhow::Result<()> {
+    fn set_slave<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
         i2c.set_slave_address(registry::ANALOG_ADDR)?;
         Ok(())
     }
 
     // Permet l'initialisation du module avec les valeurs demandées
-    fn init(&mut self, i2c: &mut I2c) -> anyhow::Result<()> {
+    fn init<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
         println!("[ANALOG] Initialisation ...");
         self.reset(i2c)?;
-        self.set_datarate(i2c, registry::ADS1115_CONFIG_DR_128_VAL);
-        self.set_mode(i2c, true);
+        self.set_datarate(i2c, Ads1115DataRate::Sps128)?;
+        self.set_mode(i2c, true)?;
+
+        // Les valeurs ci-dessus sont celles de démarrage : les seuils servent de témoin de configuration
+        self.set_lo_thresh(i2c, registry::ADS1115_READY_LO_THRESH)?;
+        self.set_hi_thresh(i2c, registry::ADS1115_READY_HI_THRESH)?;
         Ok(())
     }
 
+    /// Vérifie que la configuration persistante (seuils, data rate, mode) est toujours celle de `init`
+    /// Relance l'initialisation si ce n'est plus le cas. Retourne vrai si c'est le cas.
+    pub(crate) fn check_and_recover<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<bool> {
+        self.set_slave(i2c)?;
+
+        let lo_thresh = i2c.lecture_reg(registry::ADS1115_LO_THRESH)?;
+        let hi_thresh = i2c.lecture_reg(registry::ADS1115_HI_THRESH)?;
+        let datarate = i2c.lecture_field(registry::ADS1115_CONFIG_DR)?;
+        let single_shot = i2c.lecture_field(registry::ADS1115_CONFIG_MODE)?;
+        if lo_thresh == registry::ADS1115_READY_LO_THRESH
+            && hi_thresh == registry::ADS1115_READY_HI_THRESH
+            && datarate == Ads1115DataRate::Sps128
+            && single_shot
+        {
+            return Ok(false);
+        }
+
+        println!("[ANALOG] Configuration perdue, réinitialisation du module ...");
+        self.init(i2c)?;
+        Ok(true)
+    }
+
     /// Réinitialise le module avec les valeurs par défaut
-    fn reset(&self, i2c: &mut I2c) -> anyhow::Result<()> {
-        i2c.ecriture_dword(registry::ADS1115_CONFIG, 0x8583)?;
+    fn reset<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        i2c.ecriture_reg(registry::ADS1115_CONFIG, 0x8583)?;
         self.set_lo_thresh(i2c, 0x8000)?;
         self.set_hi_thresh(i2c, 0x7FFF)?;
         Ok(())
     }
 
     /// Défini le seuil bas
-    fn set_lo_thresh(&self, i2c: &mut I2c, seuil: u16) -> anyhow::Result<()> {
-        i2c.ecriture_dword(registry::ADS1115_LO_THRESH, seuil)
+    fn set_lo_thresh<B: I2CBus>(&self, i2c: &mut B, seuil: u16) -> anyhow::Result<()> {
+        i2c.ecriture_reg(registry::ADS1115_LO_THRESH, seuil)
     }
 
     /// Défini le seuil haut
-    fn set_hi_thresh(&self, i2c: &mut I2c, seuil: u16) -> anyhow::Result<()> {
-        i2c.ecriture_dword(registry::ADS1115_HI_THRESH, seuil)
+    fn set_hi_thresh<B: I2CBus>(&self, i2c: &mut B, seuil: u16) -> anyhow::Result<()> {
+        i2c.ecriture_reg(registry::ADS1115_HI_THRESH, seuil)
     }
 
     /// Défini les inputs
-    fn set_input(&self, i2c: &mut I2c, input: u16) -> anyhow::Result<()> {
-        i2c.ecriture_bits16(
-            registry::ADS1115_CONFIG,
-            registry::ADS1115_CONFIG_MUX_BIT,
-            registry::ADS1115_CONFIG_MUX_LEN,
-            input,
-        )
+    fn set_input<B: I2CBus>(&self, i2c: &mut B, input: Ads1115Mux) -> anyhow::Result<()> {
+        i2c.ecriture_field(registry::ADS1115_CONFIG_MUX, input)
     }
 
     /// Active le mode Single-Shot ou le mode conversion continue (True => Single Shot)
-    fn set_mode(&self, i2c: &mut I2c, state: bool) -> anyhow::Result<()> {
-        i2c.ecriture_bit16(
-            registry::ADS1115_CONFIG,
-            registry::ADS1115_CONFIG_MODE_BIT,
-            state,
-        )
+    fn set_mode<B: I2CBus>(&self, i2c: &mut B, state: bool) -> anyhow::Result<()> {
+        i2c.ecriture_field(registry::ADS1115_CONFIG_MODE, state)
     }
 
     /// Défini le data rate
-    fn set_datarate(&self, i2c: &mut I2c, dr: u16) -> anyhow::Result<()> {
-        i2c.ecriture_bits16(
-            registry::ADS1115_CONFIG,
-            registry::ADS1115_CONFIG_DR_BIT,
-            registry::ADS1115_CONFIG_DR_LEN,
-            dr,
-        )
+    fn set_datarate<B: I2CBus>(&self, i2c: &mut B, dr: Ads1115DataRate) -> anyhow::Result<()> {
+        i2c.ecriture_field(registry::ADS1115_CONFIG_DR, dr)
     }
 
     /// Défini le gain
-    fn set_gain(&self, i2c: &mut I2c, gain: u16) -> anyhow::Result<f32> {
-        i2c.ecriture_bits16(
-            registry::ADS1115_CONFIG,
-            registry::ADS1115_CONFIG_PGA_BIT,
-            registry::ADS1115_CONFIG_PGA_LEN,
-            gain,
-        )?;
-        match gain {
-            registry::ADS1115_CONFIG_PGA_FSR_6_144_VAL => {
-                return Ok((6.144 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            registry::ADS1115_CONFIG_PGA_FSR_4_096_VAL => {
-                return Ok((4.096 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            registry::ADS1115_CONFIG_PGA_FSR_2_048_VAL => {
-                return Ok((2.048 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            registry::ADS1115_CONFIG_PGA_FSR_1_024_VAL => {
-                return Ok((1.024 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            registry::ADS1115_CONFIG_PGA_FSR_0_512_VAL => {
-                return Ok((0.512 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            registry::ADS1115_CONFIG_PGA_FSR_0_256_1_VAL => {
-                return Ok((0.256 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            registry::ADS1115_CONFIG_PGA_FSR_0_256_2_VAL => {
-                return Ok((0.256 * 2.0) / 2.0_f32.powf(16.0));
-            }
-            default => {
-                println!("[ANALOG] Gain inconnu, défini à 1 par défaut.");
-                return Ok(1.0);
-            }
-        }
+    fn set_gain<B: I2CBus>(&self, i2c: &mut B, gain: Ads1115Pga) -> anyhow::Result<f32> {
+        i2c.ecriture_field(registry::ADS1115_CONFIG_PGA, gain)?;
+
+        let fsr = match gain {
+            Ads1115Pga::Fsr6v144 => 6.144,
+            Ads1115Pga::Fsr4v096 => 4.096,
+            Ads1115Pga::Fsr2v048 => 2.048,
+            Ads1115Pga::Fsr1v024 => 1.024,
+            Ads1115Pga::Fsr0v512 => 0.512,
+            Ads1115Pga::Fsr0v256 | Ads1115Pga::Fsr0v256Alt1 | Ads1115Pga::Fsr0v256Alt2 => 0.256,
+        };
+        Ok((fsr * 2.0) / 2.0_f32.powf(16.0))
     }
 
     /// Vérifie si une conversion est en cours
-    fn is_conversion_progress(&self, i2c: &mut I2c) -> anyhow::Result<bool> {
-        let in_progress =
-            i2c.lecture_bit16(registry::ADS1115_CONFIG, registry::ADS1115_CONFIG_OS_BIT)?;
-        Ok(!in_progress)
+    fn is_conversion_progress<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool> {
+        let idle = i2c.lecture_field(registry::ADS1115_CONFIG_OS)?;
+        Ok(!idle)
     }
 
     /// Démarre une conversion (En Single Mode)
-    fn start_conversion(&self, i2c: &mut I2c) -> anyhow::Result<()> {
-        i2c.ecriture_bit16(
-            registry::ADS1115_CONFIG,
-            registry::ADS1115_CONFIG_OS_BIT,
-            true,
-        )
+    fn start_conversion<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        i2c.ecriture_field(registry::ADS1115_CONFIG_OS, true)
     }
 
     /// Lecture des données de tension (RAW)
-    fn get_voltage_raw(&self, i2c: &mut I2c) -> anyhow::Result<u16> {
-        i2c.lecture_dword(registry::ADS1115_CONVERSION)
+    fn get_voltage_raw<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<u16> {
+        i2c.lecture_reg(registry::ADS1115_CONVERSION)
     }
 
     /// Lecture des données de tension
-    fn get_voltage(&self, i2c: &mut I2c, input: u16, gain: u16) -> anyhow::Result<f32> {
+    fn get_voltage<B: I2CBus>(&self, i2c: &mut B, input: Ads1115Mux, gain: Ads1115Pga) -> anyhow::Result<f32> {
         // Défini les paramètres à utiliser
-        self.set_input(i2c, input);
+        self.set_input(i2c, input)?;
         let gain_adc = self.set_gain(i2c, gain)?;
 
         // Active un Sigle Shot
@@ -160,21 +148,89 @@ impl Analog {
         while self.is_conversion_progress(i2c)? {}
 
         let mut raw = self.get_voltage_raw(i2c)?;
-        if raw > 65500 || raw < 100 {
+        if !(100..=65500).contains(&raw) {
             raw = 0;
         }
 
         // Retourne la valeur obtenue
-        Ok((((raw as f32) * gain_adc) * registry::ANALOG_BATT_GAIN))
+        Ok(((raw as f32) * gain_adc) * registry::ANALOG_BATT_GAIN)
     }
 
     /// Récupére la valeur de la batterie
-    pub(crate) fn get_battery(&mut self, i2c: &mut I2c) -> anyhow::Result<f32> {
+    pub(crate) fn get_battery<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<f32> {
         self.set_slave(i2c)?;
         self.get_voltage(
             i2c,
-            registry::ADS1115_CONFIG_MUX_AIN0_AIN1_VAL,
-            registry::ADS1115_CONFIG_PGA_FSR_4_096_VAL,
+            Ads1115Mux::Ain0Ain1,
+            Ads1115Pga::Fsr4v096,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::manager::{I2CManager, RetryPolicy};
+    use crate::i2c::sim::{SimBus, SimDevice, SimResponse};
+
+    fn analog_bus(conversion: u16) -> SimBus {
+        let mut device = SimDevice::with_register_size(2);
+        device.set_registers(registry::ADS1115_CONVERSION.address(), &conversion.to_be_bytes());
+        let mut bus = SimBus::new();
+        bus.attach(registry::ANALOG_ADDR, device);
+        bus
+    }
+
+    #[test]
+    fn battery_from_conversion() {
+        let mut bus = analog_bus(0x4000);
+        let mut analog = Analog::new(&mut bus).unwrap();
+
+        // 16384 LSB à ±4.096 V, pont diviseur de 2.5
+        let battery = analog.get_battery(&mut bus).unwrap();
+        assert!((battery - 5.12).abs() < 1e-4, "{}", battery);
+
+        // Mesure faite entre AIN0 et AIN1, gain ±4.096 V, en Single-Shot
+        assert_eq!(bus.lecture_field(registry::ADS1115_CONFIG_MUX).unwrap(), Ads1115Mux::Ain0Ain1);
+        assert_eq!(bus.lecture_field(registry::ADS1115_CONFIG_PGA).unwrap(), Ads1115Pga::Fsr4v096);
+        assert!(bus.lecture_field(registry::ADS1115_CONFIG_MODE).unwrap());
+    }
+
+    #[test]
+    fn battery_out_of_range() {
+        let mut bus = analog_bus(50);
+        let mut analog = Analog::new(&mut bus).unwrap();
+        assert_eq!(analog.get_battery(&mut bus).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn reset_detected_and_reinitialised() {
+        let mut bus = analog_bus(0x4000);
+        let mut analog = Analog::new(&mut bus).unwrap();
+        assert!(!analog.check_and_recover(&mut bus).unwrap());
+
+        // Perte d'alimentation : registres aux valeurs de démarrage
+        let device = bus.device(registry::ANALOG_ADDR).unwrap();
+        device.set_registers(registry::ADS1115_CONFIG.address(), &0x8583u16.to_be_bytes());
+        device.set_registers(registry::ADS1115_LO_THRESH.address(), &0x8000u16.to_be_bytes());
+        device.set_registers(registry::ADS1115_HI_THRESH.address(), &0x7FFFu16.to_be_bytes());
+
+        assert!(analog.check_and_recover(&mut bus).unwrap());
+        assert_eq!(bus.lecture_reg(registry::ADS1115_LO_THRESH).unwrap(), registry::ADS1115_READY_LO_THRESH);
+        assert_eq!(bus.lecture_reg(registry::ADS1115_HI_THRESH).unwrap(), registry::ADS1115_READY_HI_THRESH);
+        assert!(!analog.check_and_recover(&mut bus).unwrap());
+    }
+
+    #[test]
+    fn nack_retried() {
+        // Un NACK isolé sur la lecture de la conversion est absorbé par la nouvelle tentative
+        let mut bus = analog_bus(0x4000);
+        bus.device(registry::ANALOG_ADDR).unwrap().script_read(registry::ADS1115_CONVERSION.address(), SimResponse::Nack);
+
+        let manager = I2CManager::with_retry(bus, RetryPolicy { retries: 2, delay: Duration::ZERO });
+        let mut i2c = manager.device(registry::ANALOG_ADDR);
+        let mut analog = Analog::new(&mut i2c).unwrap();
+        let battery = analog.get_battery(&mut i2c).unwrap();
+        assert!((battery - 5.12).abs() < 1e-4, "{}", battery);
+    }
+}
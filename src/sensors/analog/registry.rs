@@ -1,47 +1,67 @@
 #![allow(unused)]
 
+use crate::i2c::field::{field_enum, Field, Register, RO, RW};
+
 pub const ANALOG_BATT_GAIN: f32 = 2.5;
 
 pub const ANALOG_ADDR: u16 = 0x48;
 
-pub const ADS1115_CONVERSION: u8 = 0x0;
-pub const ADS1115_CONFIG: u8 = 0x1;
-pub const ADS1115_LO_THRESH: u8 = 0x2;
-pub const ADS1115_HI_THRESH: u8 = 0x3;
-
-pub const ADS1115_CONFIG_OS_BIT: u8 = 15;
-
-pub const ADS1115_CONFIG_MUX_BIT: u8 = 12;
-pub const ADS1115_CONFIG_MUX_LEN: u8 = 3;
-pub const ADS1115_CONFIG_MUX_AIN0_AIN1_VAL: u16 = 0b000;
-pub const ADS1115_CONFIG_MUX_AIN0_AIN3_VAL: u16 = 0b001;
-pub const ADS1115_CONFIG_MUX_AIN1_AIN3_VAL: u16 = 0b010;
-pub const ADS1115_CONFIG_MUX_AIN2_AIN3_VAL: u16 = 0b011;
-pub const ADS1115_CONFIG_MUX_AIN0_GND_VAL: u16 = 0b100;
-pub const ADS1115_CONFIG_MUX_AIN1_GND_VAL: u16 = 0b101;
-pub const ADS1115_CONFIG_MUX_AIN2_GND_VAL: u16 = 0b110;
-pub const ADS1115_CONFIG_MUX_AIN3_GND_VAL: u16 = 0b111;
-
-pub const ADS1115_CONFIG_PGA_BIT: u8 = 9;
-pub const ADS1115_CONFIG_PGA_LEN: u8 = 3;
-pub const ADS1115_CONFIG_PGA_FSR_6_144_VAL: u16 = 0b000;
-pub const ADS1115_CONFIG_PGA_FSR_4_096_VAL: u16 = 0b001;
-pub const ADS1115_CONFIG_PGA_FSR_2_048_VAL: u16 = 0b010;
-pub const ADS1115_CONFIG_PGA_FSR_1_024_VAL: u16 = 0b011;
-pub const ADS1115_CONFIG_PGA_FSR_0_512_VAL: u16 = 0b100;
-pub const ADS1115_CONFIG_PGA_FSR_0_256_VAL: u16 = 0b101;
-pub const ADS1115_CONFIG_PGA_FSR_0_256_1_VAL: u16 = 0b110;
-pub const ADS1115_CONFIG_PGA_FSR_0_256_2_VAL: u16 = 0b111;
-
-pub const ADS1115_CONFIG_MODE_BIT: u8 = 8;
-
-pub const ADS1115_CONFIG_DR_BIT: u8 = 5;
-pub const ADS1115_CONFIG_DR_LEN: u8 = 3;
-pub const ADS1115_CONFIG_DR_8_VAL: u16 = 0b000;
-pub const ADS1115_CONFIG_DR_16_VAL: u16 = 0b001;
-pub const ADS1115_CONFIG_DR_32_VAL: u16 = 0b010;
-pub const ADS1115_CONFIG_DR_64_VAL: u16 = 0b011;
-pub const ADS1115_CONFIG_DR_128_VAL: u16 = 0b100;
-pub const ADS1115_CONFIG_DR_250_VAL: u16 = 0b101;
-pub const ADS1115_CONFIG_DR_475_VAL: u16 = 0b110;
-pub const ADS1115_CONFIG_DR_860_VAL: u16 = 0b111;
+pub const ADS1115_CONVERSION: Register<RO> = Register::r16(0x0);
+pub const ADS1115_CONFIG: Register<RW> = Register::r16(0x1);
+pub const ADS1115_LO_THRESH: Register<RW> = Register::r16(0x2);
+pub const ADS1115_HI_THRESH: Register<RW> = Register::r16(0x3);
+
+/// Seuils du mode "conversion prête" de la broche ALERT/RDY (bit de poids fort à 1 pour HI, à 0 pour LO)
+/// Différents des valeurs de démarrage (0x7FFF / 0x8000), ils signalent une réinitialisation du module
+pub const ADS1115_READY_LO_THRESH: u16 = 0x0000;
+pub const ADS1115_READY_HI_THRESH: u16 = 0x8000;
+
+/// Lecture : faux si une conversion est en cours. Ecriture : démarre une conversion (Single-Shot)
+pub const ADS1115_CONFIG_OS: Field<RW, bool> = Field::bit(ADS1115_CONFIG, 15);
+pub const ADS1115_CONFIG_MUX: Field<RW, Ads1115Mux> = Field::new(ADS1115_CONFIG, 12, 3);
+pub const ADS1115_CONFIG_PGA: Field<RW, Ads1115Pga> = Field::new(ADS1115_CONFIG, 9, 3);
+/// Vrai : mode Single-Shot, faux : conversion continue
+pub const ADS1115_CONFIG_MODE: Field<RW, bool> = Field::bit(ADS1115_CONFIG, 8);
+pub const ADS1115_CONFIG_DR: Field<RW, Ads1115DataRate> = Field::new(ADS1115_CONFIG, 5, 3);
+
+field_enum! {
+    /// Entrées de la mesure
+    pub enum Ads1115Mux {
+        Ain0Ain1 = 0b000,
+        Ain0Ain3 = 0b001,
+        Ain1Ain3 = 0b010,
+        Ain2Ain3 = 0b011,
+        Ain0Gnd = 0b100,
+        Ain1Gnd = 0b101,
+        Ain2Gnd = 0b110,
+        Ain3Gnd = 0b111,
+    }
+}
+
+field_enum! {
+    /// Pleine échelle de l'amplificateur (en V)
+    pub enum Ads1115Pga {
+        Fsr6v144 = 0b000,
+        Fsr4v096 = 0b001,
+        Fsr2v048 = 0b010,
+        Fsr1v024 = 0b011,
+        Fsr0v512 = 0b100,
+        Fsr0v256 = 0b101,
+        Fsr0v256Alt1 = 0b110,
+        Fsr0v256Alt2 = 0b111,
+    }
+}
+
+field_enum! {
+    /// Nombre d'échantillons par seconde
+    pub enum Ads1115DataRate {
+        Sps8 = 0b000,
+        Sps16 = 0b001,
+        Sps32 = 0b010,
+        Sps64 = 0b011,
+        Sps128 = 0b100,
+        Sps250 = 0b101,
+        Sps475 = 0b110,
+        Sps860 = 0b111,
+    }
+}
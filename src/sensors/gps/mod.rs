@@ -40,7 +40,7 @@ impl GPS {
         // Traitement des messages.
         let mut trames = Vec::new();
         while self.buffer.contains(&b'\n') {
-            let trame = self.buffer.iter().position(|&x| x == '\n' as u8).map(|idx| {
+            let trame = self.buffer.iter().position(|&x| x == b'\n').map(|idx| {
                 let (left, right) = self.buffer.split_at(idx + 1);
                 let trame = left.to_vec();
                 self.buffer = right.to_vec();
@@ -49,7 +49,7 @@ impl GPS {
 
             match trame {
                 Some(v) => {
-                    let trame = String::from_utf8(v).unwrap_or(String::new());
+                    let trame = String::from_utf8(v).unwrap_or_default();
                     if let Ok(sentence) = self.parser.parse_sentence(trame.as_str()) {
                         trames.push(sentence);
                     }
@@ -60,7 +60,7 @@ impl GPS {
             }
         }
 
-        if trames.len() == 0 {
+        if trames.is_empty() {
             return Ok(Option::None)
         }
 
@@ -0,0 +1,268 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use nalgebra::{Matrix3, Vector3};
+
+use crate::config::Config;
+use crate::i2c::field::{Field, RO};
+use crate::i2c::{I2CBit, I2CBus};
+use crate::sensors::imu::calibration::FusionCalibrationStatus;
+use crate::sensors::imu::driver::{ImuChip, ImuDriver, MagInput};
+use crate::sensors::imu::registry;
+use crate::sensors::imu::registry::BnoMode;
+
+/// Passage en mode configuration (19 ms d'après le datasheet)
+const CONFIG_MODE_DELAY: Duration = Duration::from_millis(25);
+
+/// Passage du mode configuration à un mode de fusion (7 ms d'après le datasheet)
+const FUSION_MODE_DELAY: Duration = Duration::from_millis(10);
+
+/// Pilote du BNO055 : la fusion (mode NDOF, magnétomètre intégré) est réalisée par le module
+/// Le magnétomètre externe et l'AHRS ne sont pas utilisés.
+pub(crate) struct BNO055 {
+    addr: u16,
+    axis_map: (u16, u16),
+    mag_decl: f32,
+    angles: Vector3<f32>,
+    quaternion: (f32, f32, f32, f32),
+    accel: Vector3<f32>,
+    gyro: Vector3<f32>,
+    raw_accel: Vector3<i16>,
+    raw_gyro: Vector3<i16>,
+    temp: f32,
+    calibration: Option<FusionCalibrationStatus>,
+    start: Instant,
+    timestamp: Duration,
+    last_sample: Option<Instant>,
+}
+
+impl BNO055 {
+    /// Constructeur
+    pub(crate) fn new<B: I2CBus>(i2c: &mut B, config: &Config, addr: u16) -> anyhow::Result<Self> {
+        // Orientation appliquée par le module lui-même, avant la fusion
+        let axis_map = axis_remap(&config.imu_orientation.matrix())
+            .ok_or(anyhow!("[IMU] Orientation {:?} non supportée par le BNO055 (quarts de tour uniquement)", config.imu_orientation))?;
+
+        let mut bno = Self {
+            addr,
+            axis_map,
+            mag_decl: config.mag_decl,
+            angles: Vector3::zeros(),
+            quaternion: (1.0, 0.0, 0.0, 0.0),
+            accel: Vector3::zeros(),
+            gyro: Vector3::zeros(),
+            raw_accel: Vector3::zeros(),
+            raw_gyro: Vector3::zeros(),
+            temp: 0.0,
+            calibration: None,
+            start: Instant::now(),
+            timestamp: Duration::ZERO,
+            last_sample: None,
+        };
+
+        i2c.set_slave_address(addr)?;
+        bno.whoami(i2c)?;
+        bno.init_module(i2c)?;
+
+        let st_result = i2c.lecture_reg(registry::BNO055_ST_RESULT)?;
+        println!("[IMU] Auto-test BNO055 (ST_RESULT): {:#06b}", st_result);
+
+        Ok(bno)
+    }
+
+    /// Qui suis-je ?
+    fn whoami<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        let who = i2c.lecture_reg(registry::BNO055_CHIP_ID)? as u8;
+        if who != registry::BNO055_ID {
+            return Err(anyhow!("[IMU] Capteur non reconnu (CHIP_ID: {:#04x})", who));
+        }
+        Ok(())
+    }
+
+    /// Configure les unités et l'orientation en mode configuration, puis lance la fusion
+    fn init_module<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
+        println!("[IMU] Initialisation ...");
+        i2c.ecriture_field(registry::BNO055_OPR_MODE_MODE, BnoMode::Config)?;
+        sleep(CONFIG_MODE_DELAY);
+
+        i2c.ecriture_reg(registry::BNO055_PAGE_ID, 0)?;
+        i2c.ecriture_reg(registry::BNO055_PWR_MODE, registry::BNO055_POWER_NORMAL)?;
+        i2c.ecriture_reg(registry::BNO055_UNIT_SEL, registry::BNO055_UNITS)?;
+        i2c.ecriture_reg(registry::BNO055_AXIS_MAP_CONFIG, self.axis_map.0)?;
+        i2c.ecriture_reg(registry::BNO055_AXIS_MAP_SIGN, self.axis_map.1)?;
+
+        i2c.ecriture_field(registry::BNO055_OPR_MODE_MODE, BnoMode::Ndof)?;
+        sleep(FUSION_MODE_DELAY);
+        Ok(())
+    }
+}
+
+/// Registres AXIS_MAP_CONFIG et AXIS_MAP_SIGN équivalents à la matrice de passage (voiture = matrice * capteur)
+/// `None` si la matrice n'est pas une permutation des axes (au signe près).
+fn axis_remap(matrix: &Matrix3<f32>) -> Option<(u16, u16)> {
+    let mut config = 0;
+    let mut sign = 0;
+
+    for row in 0..3 {
+        let axes: Vec<usize> = (0..3).filter(|&col| matrix[(row, col)] != 0.0).collect();
+        let [col] = axes[..] else {
+            return None;
+        };
+
+        let value = matrix[(row, col)];
+        if value.abs() != 1.0 {
+            return None;
+        }
+
+        // Axe X sur les bits 1:0 (signe sur le bit 2), Y sur 3:2 (bit 1), Z sur 5:4 (bit 0)
+        config |= (col as u16) << (2 * row);
+        if value < 0.0 {
+            sign |= 1 << (2 - row);
+        }
+    }
+
+    // Chaque axe du capteur doit être utilisé une seule fois
+    let used = (0..3).map(|row| (config >> (2 * row)) & 0x03).fold(0, |used, col| used | (1 << col));
+    (used == 0b111).then_some((config, sign))
+}
+
+impl ImuDriver for BNO055 {
+    fn chip(&self) -> ImuChip {
+        ImuChip::Bno055
+    }
+
+    /// Lis le résultat de la fusion s'il a été rafraîchi depuis la dernière lecture (100 Hz)
+    fn update(&mut self, i2c: &mut dyn I2CBus, _mag: MagInput) -> anyhow::Result<usize> {
+        let period = Duration::from_millis(registry::BNO055_FUSION_PERIOD_MS);
+        if self.last_sample.is_some_and(|last| last.elapsed() < period) {
+            return Ok(0);
+        }
+
+        i2c.set_slave_address(self.addr)?;
+        let [w, x, y, z] = i2c.lecture_i16_le::<4>(registry::BNO055_RA_QUA_DATA_W_LSB)?;
+        let [heading, roll, pitch] = i2c.lecture_i16_le::<3>(registry::BNO055_RA_EUL_HEADING_LSB)?;
+        let [ax, ay, az] = i2c.lecture_i16_le::<3>(registry::BNO055_RA_ACC_DATA_X_LSB)?;
+        let [gx, gy, gz] = i2c.lecture_i16_le::<3>(registry::BNO055_RA_GYR_DATA_X_LSB)?;
+        let temp = i2c.lecture_reg(registry::BNO055_TEMP)? as u8 as i8;
+        let calib_stat = i2c.lecture_reg(registry::BNO055_CALIB_STAT)?;
+
+        let q = |v: i16| v as f32 / registry::BNO055_QUATERNION_LSB;
+        self.quaternion = (q(w), q(x), q(y), q(z));
+
+        // Le cap du module est compté dans le sens horaire, sans déclinaison
+        let euler = |v: i16| v as f32 / registry::BNO055_EULER_LSB;
+        let heading = (euler(heading) + self.mag_decl).rem_euclid(360.0);
+        self.angles = Vector3::new(euler(pitch), euler(roll), heading);
+
+        self.raw_accel = Vector3::new(ax, ay, az);
+        self.raw_gyro = Vector3::new(gx, gy, gz);
+        self.accel = self.raw_accel.map(|v| v as f32 / 1000.0);
+        self.gyro = self.raw_gyro.map(|v| v as f32 / registry::BNO055_GYRO_LSB);
+        self.temp = temp as f32;
+
+        let level = |field: Field<RO, u8>| field.decode(calib_stat).unwrap_or_default();
+        self.calibration = Some(FusionCalibrationStatus {
+            system: level(registry::BNO055_CALIB_STAT_SYS),
+            gyro: level(registry::BNO055_CALIB_STAT_GYR),
+            accel: level(registry::BNO055_CALIB_STAT_ACC),
+            mag: level(registry::BNO055_CALIB_STAT_MAG),
+        });
+
+        let now = Instant::now();
+        self.timestamp = now - self.start;
+        self.last_sample = Some(now);
+        Ok(1)
+    }
+
+    fn check_and_recover(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<bool> {
+        i2c.set_slave_address(self.addr)?;
+        self.whoami(&mut i2c)?;
+
+        if i2c.lecture_field(registry::BNO055_OPR_MODE_MODE)? == BnoMode::Ndof {
+            return Ok(false);
+        }
+
+        println!("[IMU] Configuration perdue, réinitialisation du module ...");
+        self.init_module(&mut i2c)?;
+        Ok(true)
+    }
+
+    fn set_mag_decl(&mut self, mag_decl: f32) {
+        self.mag_decl = mag_decl;
+    }
+
+    fn get_angles(&self) -> Vector3<f32> {
+        self.angles
+    }
+
+    fn get_quaternion(&self) -> (f32, f32, f32, f32) {
+        self.quaternion
+    }
+
+    fn get_acceleration(&self) -> Vector3<f32> {
+        self.accel
+    }
+
+    fn get_angular_rate(&self) -> Vector3<f32> {
+        self.gyro
+    }
+
+    fn get_raw(&self) -> (Vector3<i16>, Vector3<i16>) {
+        (self.raw_accel, self.raw_gyro)
+    }
+
+    fn get_temp(&self) -> f32 {
+        self.temp
+    }
+
+    fn get_timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    fn get_sample_period(&self) -> Duration {
+        Duration::from_millis(registry::BNO055_FUSION_PERIOD_MS)
+    }
+
+    fn get_fusion_calibration(&self) -> Option<FusionCalibrationStatus> {
+        self.calibration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+    use crate::sensors::orientation::Orientation;
+
+    #[test]
+    fn orientation_remapped_by_module() {
+        // Valeurs par défaut du datasheet
+        assert_eq!(axis_remap(&Orientation::Normal.matrix()), Some((0x24, 0x00)));
+
+        // Quart de tour : X de la voiture = -Y du capteur, Y de la voiture = X du capteur
+        assert_eq!(axis_remap(&Orientation::Yaw90.matrix()), Some((0x21, 0x04)));
+        assert_eq!(axis_remap(&Orientation::UpsideDown.matrix()), Some((0x24, 0x03)));
+        assert_eq!(axis_remap(&Matrix3::repeat(0.5)), None);
+
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::BNO055_CHIP_ID.address(), &[registry::BNO055_ID]);
+        bus.attach(registry::BNO055_ADDR, device);
+        let mut config = Config::new();
+        config.imu_orientation = Orientation::Yaw90;
+        let mut bno = BNO055::new(&mut bus, &config, registry::BNO055_ADDR).unwrap();
+
+        let device = bus.device(registry::BNO055_ADDR).unwrap();
+        assert_eq!(device.registers(registry::BNO055_AXIS_MAP_CONFIG.address(), 2), &[0x21, 0x04]);
+        assert_eq!(device.registers(registry::BNO055_OPR_MODE.address(), 1), &[BnoMode::Ndof as u8]);
+        assert!(!bno.check_and_recover(&mut bus).unwrap());
+
+        // Calibration de la fusion : système 3, gyroscope 2, accéléromètre 1, magnétomètre 0
+        let device = bus.device(registry::BNO055_ADDR).unwrap();
+        device.set_registers(registry::BNO055_CALIB_STAT.address(), &[0b11_10_01_00]);
+        assert_eq!(bno.update(&mut bus, MagInput::Field(None)).unwrap(), 1);
+        let calibration = FusionCalibrationStatus { system: 3, gyro: 2, accel: 1, mag: 0 };
+        assert_eq!(bno.get_fusion_calibration(), Some(calibration));
+    }
+}
@@ -0,0 +1,392 @@
+use std::time::{Duration, Instant};
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+/// Ecart-type maximal du gyroscope (en °/s) pour considérer la voiture immobile
+const GYRO_STILL_STD: f32 = 1.0;
+
+/// Ecart-type maximal de l'accéléromètre (en g) pour considérer la voiture immobile
+const ACCEL_STILL_STD: f32 = 0.03;
+
+/// Ecart maximal (en g) entre la norme de l'accélération moyenne et la gravité
+const GRAVITY_TOLERANCE: f32 = 0.1;
+
+/// Durée d'observation avant de décider si la voiture est immobile
+const STILL_WINDOW: Duration = Duration::from_secs(2);
+
+/// Délai minimal entre deux mises à jour de la calibration en fonctionnement
+const REFRESH_PERIOD: Duration = Duration::from_secs(60);
+
+/// Sensibilité des registres de décalage du gyroscope (LSB par °/s, échelle ±1000 °/s)
+const GYRO_OFFSET_LSB: f32 = 32.8;
+
+/// Sensibilité des registres de décalage de l'accéléromètre (LSB par g, échelle ±16 g)
+const ACCEL_OFFSET_LSB: f32 = 2048.0;
+
+/// Plage de température du modèle de biais du gyroscope (en °C), découpée en tranches de `GYRO_TEMP_STEP`
+const GYRO_TEMP_MIN: f32 = -20.0;
+const GYRO_TEMP_STEP: f32 = 5.0;
+const GYRO_TEMP_BINS: usize = 20;
+
+/// Poids d'une nouvelle mesure dans une tranche déjà apprise
+const GYRO_TEMP_LEARN_RATE: f32 = 0.5;
+
+/// Ecart minimal (en °C) entre les températures apprises pour ajuster une droite, biais constant en dessous
+const GYRO_TEMP_MIN_SPAN: f32 = 10.0;
+
+/// Prolongement de la droite (en °C) au-delà des températures apprises, biais constant ensuite
+const GYRO_TEMP_EXTRAPOLATION: f32 = 5.0;
+
+/// Où sont appliqués les décalages de l'IMU
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CalibrationMode {
+    /// Soustraits par le pilote après chaque lecture
+    Software,
+    /// Ecrits dans les registres de décalage du module (registres, FIFO, ... déjà corrigés)
+    Hardware,
+}
+
+/// Etat de la calibration automatique d'une IMU à fusion embarquée (BNO055), de 0 (non calibré) à 3 (calibré)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FusionCalibrationStatus {
+    pub system: u8,
+    pub gyro: u8,
+    pub accel: u8,
+    pub mag: u8,
+}
+
+/// Décalages de l'IMU, enregistrés dans la configuration de la voiture
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ImuCalibration {
+    /// Décalage du gyroscope (en °/s)
+    pub gyro: Vector3<f32>,
+    /// Décalage de l'accéléromètre (en g), gravité retirée
+    pub accel: Vector3<f32>,
+    /// Registres de décalage du module, en mode `Hardware`
+    pub hardware: Option<ImuHardwareOffsets>,
+    /// Biais du gyroscope appris en fonction de la température
+    #[serde(default)]
+    pub gyro_temp: Option<GyroTempModel>,
+}
+
+/// Compensation du biais du gyroscope en fonction de la température
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum GyroTempCompensation {
+    /// Biais fixe, celui de la dernière calibration
+    Off,
+    /// Droite ajustée (moindres carrés) sur les biais appris
+    Linear,
+    /// Interpolation entre les biais appris, constant au-delà
+    Table,
+}
+
+/// Biais moyen appris à une température
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct GyroTempPoint {
+    /// Température moyenne (en °C)
+    pub temp: f32,
+    /// Biais du capteur (en °/s), hors registres de décalage
+    pub bias: Vector3<f32>,
+}
+
+/// Biais du gyroscope en fonction de la température, appris pendant les arrêts de la voiture
+/// Une mesure par tranche de température, de `GYRO_TEMP_MIN` à `GYRO_TEMP_MIN + GYRO_TEMP_BINS * GYRO_TEMP_STEP`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub(crate) struct GyroTempModel {
+    bins: [Option<GyroTempPoint>; GYRO_TEMP_BINS],
+}
+
+impl GyroTempModel {
+    /// Ajoute le biais mesuré pendant un arrêt, moyenné avec celui déjà appris dans la même tranche
+    pub(crate) fn learn(&mut self, temp: f32, bias: Vector3<f32>) {
+        let bin = ((temp - GYRO_TEMP_MIN) / GYRO_TEMP_STEP).floor().clamp(0.0, (GYRO_TEMP_BINS - 1) as f32) as usize;
+        let point = match self.bins[bin] {
+            Some(old) => GyroTempPoint {
+                temp: old.temp + (temp - old.temp) * GYRO_TEMP_LEARN_RATE,
+                bias: old.bias + (bias - old.bias) * GYRO_TEMP_LEARN_RATE,
+            },
+            None => GyroTempPoint { temp, bias },
+        };
+        self.bins[bin] = Some(point);
+    }
+
+    /// Biais estimé à cette température, `None` sans compensation ou sans aucune mesure
+    pub(crate) fn bias(&self, temp: f32, compensation: GyroTempCompensation) -> Option<Vector3<f32>> {
+        let points: Vec<GyroTempPoint> = self.bins.iter().flatten().copied().collect();
+        let (first, last) = (points.first()?, points.last()?);
+
+        match compensation {
+            GyroTempCompensation::Off => None,
+            GyroTempCompensation::Table => {
+                if temp <= first.temp {
+                    return Some(first.bias);
+                }
+
+                // Tranches dans l'ordre des températures : interpolation entre les deux mesures encadrantes
+                let bias = points.windows(2).find(|pair| temp <= pair[1].temp).map(|pair| {
+                    let ratio = (temp - pair[0].temp) / (pair[1].temp - pair[0].temp).max(f32::EPSILON);
+                    pair[0].bias + (pair[1].bias - pair[0].bias) * ratio
+                });
+                Some(bias.unwrap_or(last.bias))
+            }
+            GyroTempCompensation::Linear => {
+                let n = points.len() as f32;
+                let mean_temp = points.iter().map(|p| p.temp).sum::<f32>() / n;
+                let mean_bias = points.iter().map(|p| p.bias).sum::<Vector3<f32>>() / n;
+                let variance: f32 = points.iter().map(|p| (p.temp - mean_temp).powi(2)).sum();
+
+                // Températures trop proches : la pente ne serait que du bruit, biais constant
+                if last.temp - first.temp < GYRO_TEMP_MIN_SPAN || variance < f32::EPSILON {
+                    return Some(mean_bias);
+                }
+
+                let temp = temp.clamp(first.temp - GYRO_TEMP_EXTRAPOLATION, last.temp + GYRO_TEMP_EXTRAPOLATION);
+                let covariance: Vector3<f32> = points.iter().map(|p| (p.bias - mean_bias) * (p.temp - mean_temp)).sum();
+                Some(mean_bias + covariance / variance * (temp - mean_temp))
+            }
+        }
+    }
+}
+
+/// Contenu des registres de décalage du module (XG_OFFS_USR* et XA_OFFS_*)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ImuHardwareOffsets {
+    pub gyro: Vector3<i16>,
+    pub accel: Vector3<i16>,
+}
+
+impl ImuHardwareOffsets {
+    /// Décalage ajouté au gyroscope (en °/s) par les registres
+    pub(crate) fn gyro_bias(&self) -> Vector3<f32> {
+        self.gyro.map(|register| register as f32 / GYRO_OFFSET_LSB)
+    }
+
+    /// Registres corrigés du décalage restant mesuré avec les registres actuels
+    /// Le bit 0 des registres de l'accéléromètre est réservé et conservé.
+    pub(crate) fn corrected(&self, residual: &ImuCalibration) -> Self {
+        let offset = |register: i16, value: f32, lsb: f32| {
+            (register as f32 - (value * lsb).round()).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        };
+
+        Self {
+            gyro: self.gyro.zip_map(&residual.gyro, |register, value| offset(register, value, GYRO_OFFSET_LSB)),
+            accel: self.accel.zip_map(&residual.accel, |register, value| {
+                (offset(register, value, ACCEL_OFFSET_LSB) & !1) | (register & 1)
+            }),
+        }
+    }
+}
+
+/// Moyenne et variance des mesures sur une fenêtre, pour détecter l'immobilité
+pub(crate) struct StillnessDetector {
+    window: usize,
+    count: usize,
+    gyro_sum: Vector3<f64>,
+    gyro_sq_sum: Vector3<f64>,
+    accel_sum: Vector3<f64>,
+    accel_sq_sum: Vector3<f64>,
+    last_refresh: Option<Instant>,
+}
+
+impl StillnessDetector {
+    /// Détecteur sur `window` échantillons
+    pub(crate) fn new(window: usize) -> Self {
+        Self {
+            window: window.max(2),
+            count: 0,
+            gyro_sum: Vector3::zeros(),
+            gyro_sq_sum: Vector3::zeros(),
+            accel_sum: Vector3::zeros(),
+            accel_sq_sum: Vector3::zeros(),
+            last_refresh: None,
+        }
+    }
+
+    /// Détecteur sur `STILL_WINDOW` à la période d'échantillonnage donnée
+    pub(crate) fn with_period(sample_period: Duration) -> Self {
+        let window = STILL_WINDOW.as_secs_f64() / sample_period.as_secs_f64().max(f64::EPSILON);
+        Self::new(window as usize)
+    }
+
+    /// Oublie les échantillons de la fenêtre en cours
+    pub(crate) fn clear(&mut self) {
+        self.count = 0;
+        self.gyro_sum = Vector3::zeros();
+        self.gyro_sq_sum = Vector3::zeros();
+        self.accel_sum = Vector3::zeros();
+        self.accel_sq_sum = Vector3::zeros();
+    }
+
+    /// Ajoute un échantillon non calibré (gyroscope en °/s, accélération en g)
+    /// Retourne la calibration à la fin d'une fenêtre immobile, la voiture devant être à plat :
+    /// à n'utiliser que pour une calibration explicite (démarrage), jamais en fonctionnement.
+    pub(crate) fn push(&mut self, gyro: Vector3<f32>, accel: Vector3<f32>) -> Option<ImuCalibration> {
+        let (gyro_mean, accel_mean) = self.accumulate(gyro, accel)?;
+
+        // La gravité est supposée alignée sur l'axe le plus vertical
+        let axis = accel_mean.iamax();
+        let mut accel = accel_mean;
+        accel[axis] -= accel_mean[axis].signum();
+
+        Some(ImuCalibration {
+            gyro: gyro_mean.cast::<f32>(),
+            accel: accel.cast::<f32>(),
+            hardware: None,
+            gyro_temp: None,
+        })
+    }
+
+    /// Biais du gyroscope (en °/s) à la fin d'une fenêtre immobile, au plus une fois toutes les `REFRESH_PERIOD`
+    /// L'accéléromètre n'est pas recalibré : garée sur une pente, la voiture n'est pas à plat.
+    pub(crate) fn push_refresh(&mut self, gyro: Vector3<f32>, accel: Vector3<f32>) -> Option<Vector3<f32>> {
+        if self.last_refresh.is_some_and(|last| last.elapsed() < REFRESH_PERIOD) {
+            return None;
+        }
+
+        let (gyro_mean, _) = self.accumulate(gyro, accel)?;
+        self.last_refresh = Some(Instant::now());
+        Some(gyro_mean.cast::<f32>())
+    }
+
+    /// Ajoute un échantillon, retourne les moyennes (gyroscope, accéléromètre) à la fin d'une fenêtre immobile
+    fn accumulate(&mut self, gyro: Vector3<f32>, accel: Vector3<f32>) -> Option<(Vector3<f64>, Vector3<f64>)> {
+        let (gyro, accel) = (gyro.cast::<f64>(), accel.cast::<f64>());
+        self.gyro_sum += gyro;
+        self.gyro_sq_sum += gyro.component_mul(&gyro);
+        self.accel_sum += accel;
+        self.accel_sq_sum += accel.component_mul(&accel);
+        self.count += 1;
+
+        if self.count < self.window {
+            return None;
+        }
+
+        let means = self.still_means();
+        self.clear();
+        means
+    }
+
+    /// Moyennes de la fenêtre en cours, `None` si la voiture a bougé
+    fn still_means(&self) -> Option<(Vector3<f64>, Vector3<f64>)> {
+        let n = self.count as f64;
+        let gyro_mean = self.gyro_sum / n;
+        let accel_mean = self.accel_sum / n;
+        let gyro_var = self.gyro_sq_sum / n - gyro_mean.component_mul(&gyro_mean);
+        let accel_var = self.accel_sq_sum / n - accel_mean.component_mul(&accel_mean);
+
+        let gyro_still = gyro_var.max().max(0.0).sqrt() <= GYRO_STILL_STD as f64;
+        let accel_still = accel_var.max().max(0.0).sqrt() <= ACCEL_STILL_STD as f64;
+        let gravity = (accel_mean.norm() - 1.0).abs() <= GRAVITY_TOLERANCE as f64;
+        if !(gyro_still && accel_still && gravity) {
+            return None;
+        }
+
+        Some((gyro_mean, accel_mean))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibration_only_when_still() {
+        let mut detector = StillnessDetector::new(100);
+
+        // Immobile : décalages moyens, gravité retirée
+        let mut calibration = None;
+        for n in 0..100 {
+            let noise = if n % 2 == 0 { 0.1 } else { -0.1 };
+            calibration = detector.push(Vector3::new(0.5 + noise, -0.2, 0.0), Vector3::new(0.02, 0.0, 1.01 + noise / 10.0));
+        }
+        let calibration = calibration.unwrap();
+        assert!((calibration.gyro - Vector3::new(0.5, -0.2, 0.0)).norm() < 1e-4);
+        assert!((calibration.accel - Vector3::new(0.02, 0.0, 0.01)).norm() < 1e-4);
+
+        // Secousse pendant la fenêtre : pas de calibration
+        let mut calibration = None;
+        for n in 0..100 {
+            let gyro = if n == 50 { Vector3::new(40.0, 0.0, 0.0) } else { Vector3::zeros() };
+            calibration = detector.push(gyro, Vector3::z());
+        }
+        assert!(calibration.is_none());
+    }
+
+    #[test]
+    fn refresh_keeps_slope_out_of_accel_bias() {
+        let mut detector = StillnessDetector::new(100);
+
+        // Garée sur une pente de 10° : seul le biais du gyroscope est rafraîchi
+        let slope = 10.0_f32.to_radians();
+        let mut gyro = None;
+        for _ in 0..100 {
+            gyro = detector.push_refresh(Vector3::new(0.3, 0.0, -0.1), Vector3::new(slope.sin(), 0.0, slope.cos()));
+        }
+        assert!((gyro.unwrap() - Vector3::new(0.3, 0.0, -0.1)).norm() < 1e-4);
+
+        // Au plus un rafraîchissement par `REFRESH_PERIOD`
+        for _ in 0..100 {
+            assert!(detector.push_refresh(Vector3::zeros(), Vector3::z()).is_none());
+        }
+    }
+
+    #[test]
+    fn hardware_offsets_cancel_residual() {
+        let offsets = ImuHardwareOffsets { gyro: Vector3::new(10, 0, -5), accel: Vector3::new(-1201, 600, 1025) };
+        let residual = ImuCalibration {
+            gyro: Vector3::new(1.0, -0.5, 0.0),
+            accel: Vector3::new(0.01, 0.0, -0.02),
+            hardware: None,
+            gyro_temp: None,
+        };
+
+        let corrected = offsets.corrected(&residual);
+        assert_eq!(corrected.gyro, Vector3::new(-23, 16, -5));
+        // -1201 - 20 = -1221 (bit 0 conservé), 600 inchangé, 1025 + 41 = 1066 -> 1067 (bit 0 conservé)
+        assert_eq!(corrected.accel, Vector3::new(-1221, 600, 1067));
+    }
+
+    #[test]
+    fn gyro_bias_follows_temperature() {
+        let mut model = GyroTempModel::default();
+        assert_eq!(model.bias(25.0, GyroTempCompensation::Linear), None);
+
+        // Une seule mesure : biais constant
+        model.learn(20.0, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(model.bias(50.0, GyroTempCompensation::Linear), Some(Vector3::new(1.0, 0.0, 0.0)));
+
+        // +0,1 °/s par °C sur l'axe Z
+        model.learn(30.0, Vector3::new(1.0, 0.0, 1.0));
+        model.learn(40.0, Vector3::new(1.0, 0.0, 2.0));
+        let linear = model.bias(45.0, GyroTempCompensation::Linear).unwrap();
+        assert!((linear - Vector3::new(1.0, 0.0, 2.5)).norm() < 1e-4);
+
+        let table = model.bias(35.0, GyroTempCompensation::Table).unwrap();
+        assert!((table - Vector3::new(1.0, 0.0, 1.5)).norm() < 1e-4);
+        assert_eq!(model.bias(45.0, GyroTempCompensation::Table), Some(Vector3::new(1.0, 0.0, 2.0)));
+        assert_eq!(model.bias(35.0, GyroTempCompensation::Off), None);
+
+        // Nouvelle mesure dans une tranche déjà apprise : moyenne
+        model.learn(41.0, Vector3::new(1.0, 0.0, 3.0));
+        assert_eq!(model.bias(40.5, GyroTempCompensation::Table), Some(Vector3::new(1.0, 0.0, 2.5)));
+    }
+
+    #[test]
+    fn gyro_bias_slope_needs_temperature_span() {
+        // Deux tranches voisines, 2 °C d'écart : pas de pente, moyenne des biais
+        let mut model = GyroTempModel::default();
+        model.learn(24.0, Vector3::new(0.0, 0.0, 0.0));
+        model.learn(26.0, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(model.bias(60.0, GyroTempCompensation::Linear), Some(Vector3::new(0.0, 0.0, 0.5)));
+
+        // +0,1 °/s par °C de 20 à 40 °C : droite prolongée de 5 °C seulement
+        let mut model = GyroTempModel::default();
+        model.learn(20.0, Vector3::new(0.0, 0.0, 0.0));
+        model.learn(40.0, Vector3::new(0.0, 0.0, 2.0));
+        let hot = model.bias(80.0, GyroTempCompensation::Linear).unwrap();
+        let cold = model.bias(-20.0, GyroTempCompensation::Linear).unwrap();
+        assert!((hot - Vector3::new(0.0, 0.0, 2.5)).norm() < 1e-4);
+        assert!((cold - Vector3::new(0.0, 0.0, -0.5)).norm() < 1e-4);
+    }
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use rppal::gpio::{Gpio, InputPin, Trigger};
+
+/// Broche INT de l'IMU : une impulsion à chaque nouvel échantillon
+pub(crate) struct DataReady {
+    int_pin: InputPin,
+}
+
+impl DataReady {
+    pub(crate) fn new(pin: u8) -> anyhow::Result<Self> {
+        let gpio = Gpio::new()?;
+        let mut int_pin = gpio.get(pin)?.into_input_pulldown();
+        int_pin.set_interrupt(Trigger::RisingEdge)?;
+
+        println!("[IMU] Interruption sur la broche {}.", pin);
+        Ok(DataReady { int_pin })
+    }
+
+    /// Attend le prochain échantillon. Retourne faux si rien n'est arrivé avant `timeout`.
+    pub(crate) fn wait(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        Ok(self.int_pin.poll_interrupt(false, Some(timeout))?.is_some())
+    }
+}
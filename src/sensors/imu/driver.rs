@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use nalgebra::Vector3;
+
+use crate::config::Config;
+use crate::i2c::manager::{I2CDevice, I2CManager};
+use crate::i2c::{I2CBit, I2CBus};
+use crate::sensors::imu::bno055::BNO055;
+use crate::sensors::imu::calibration::{FusionCalibrationStatus, ImuCalibration};
+use crate::sensors::imu::events::ImuEvent;
+use crate::sensors::imu::icm20948::ICM20948;
+use crate::sensors::imu::imu::IMU;
+use crate::sensors::imu::registry;
+use crate::sensors::imu::self_test::SelfTestReport;
+pub(crate) use crate::sensors::imu::settings::ImuChip;
+
+/// Magnétomètre branché sur le bus I2C auxiliaire, lu par le module lui-même (SLV0)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct AuxMagnetometer {
+    /// Adresse du magnétomètre sur le bus auxiliaire
+    pub addr: u8,
+    /// Premier registre des mesures (3 valeurs de 16 bits, octet de poids fort en premier)
+    pub reg: u8,
+}
+
+/// Champ magnétique fourni à la fusion
+pub(crate) enum MagInput<'a> {
+    /// Mesure calibrée lue sur le bus principal, la même pour tous les échantillons
+    Field(Option<Vector3<f32>>),
+    /// Mesures lues sur le bus auxiliaire (registres bruts, dans l'ordre de lecture), calibrées par l'appelant
+    /// (`None` pour une mesure saturée)
+    Aux(&'a dyn Fn([i16; 3]) -> Option<Vector3<f32>>),
+}
+
+/// Pilote d'une IMU : mesures, orientation et surveillance du module
+pub(crate) trait ImuDriver {
+    /// Puce pilotée
+    fn chip(&self) -> ImuChip;
+
+    /// Lis les nouveaux échantillons et mets à jour les valeurs de l'IMU
+    /// `mag` : champ magnétique corrigé, dans le repère de la voiture (sans mesure, le cap n'est pas corrigé)
+    /// Retourne le nombre d'échantillons traités.
+    fn update(&mut self, i2c: &mut dyn I2CBus, mag: MagInput) -> anyhow::Result<usize>;
+
+    /// Vérifie que le module répond toujours et n'a pas été réinitialisé (perte d'alimentation, ...)
+    /// Relance l'initialisation si la configuration relue ne correspond plus. Retourne vrai si c'est le cas.
+    fn check_and_recover(&mut self, i2c: &mut dyn I2CBus) -> anyhow::Result<bool>;
+
+    /// Mets à jour la déclinaison magnétique (en degrés) ajoutée au cap
+    fn set_mag_decl(&mut self, mag_decl: f32);
+
+    /// Tangage, roulis et cap (en degrés)
+    fn get_angles(&self) -> Vector3<f32>;
+
+    /// Orientation sous forme de quaternion (w, x, y, z)
+    fn get_quaternion(&self) -> (f32, f32, f32, f32);
+
+    /// Accélération (en g, gravité comprise) du dernier échantillon
+    fn get_acceleration(&self) -> Vector3<f32>;
+
+    /// Vitesse angulaire calibrée (en °/s) du dernier échantillon
+    fn get_angular_rate(&self) -> Vector3<f32>;
+
+    /// Accélération et vitesse angulaire brutes (LSB) du dernier échantillon
+    fn get_raw(&self) -> (Vector3<i16>, Vector3<i16>);
+
+    /// Température (en °C) du dernier échantillon
+    fn get_temp(&self) -> f32;
+
+    /// Instant du dernier échantillon
+    fn get_timestamp(&self) -> Duration;
+
+    /// Période entre deux échantillons
+    fn get_sample_period(&self) -> Duration;
+
+    /// Résultat de l'auto-test du démarrage
+    fn get_self_test(&self) -> Option<SelfTestReport> {
+        None
+    }
+
+    /// Etat de la calibration de la fusion réalisée par la puce
+    fn get_fusion_calibration(&self) -> Option<FusionCalibrationStatus> {
+        None
+    }
+
+    /// Calibration obtenue depuis le dernier appel, à enregistrer dans la configuration
+    fn take_new_calibration(&mut self) -> Option<ImuCalibration> {
+        None
+    }
+
+    /// Evénements détectés depuis le dernier appel
+    fn take_events(&mut self) -> Vec<ImuEvent> {
+        Vec::new()
+    }
+
+    /// Vrai si le module ne détecte plus aucun mouvement
+    fn is_parked(&self) -> bool {
+        false
+    }
+
+    /// Fait lire le magnétomètre par le module (bus auxiliaire) au lieu de l'hôte (bus principal)
+    fn set_aux_magnetometer(&mut self, _i2c: &mut dyn I2CBus, _aux_mag: Option<AuxMagnetometer>) -> anyhow::Result<()> {
+        Err(anyhow!("[IMU] Lecture du magnétomètre auxiliaire non supportée ({:?})", self.chip()))
+    }
+
+    /// Accède directement au magnétomètre auxiliaire (mode "Bypass") le temps de `f`
+    fn with_bypass(&mut self, _i2c: &mut dyn I2CBus, f: &mut dyn FnMut() -> anyhow::Result<bool>) -> anyhow::Result<bool> {
+        f()
+    }
+
+    /// Registres du magnétomètre auxiliaire (dans l'ordre de lecture) du dernier échantillon
+    fn get_aux_mag(&self) -> Option<[i16; 3]> {
+        None
+    }
+}
+
+/// Lecture d'un registre d'identification, `None` si personne ne répond
+fn probe<B: I2CBus>(i2c: &mut B, register: u8) -> Option<u8> {
+    i2c.lecture_words::<1>(register).ok().map(|[id]| id)
+}
+
+/// Cherche l'IMU présente sur le bus (WHO_AM_I / CHIP_ID), retourne la puce et son adresse
+pub(crate) fn detect_chip<B: I2CBus>(i2c_bus: &I2CManager<B>) -> Option<(ImuChip, u16)> {
+    for addr in [registry::IMU_ADDR, registry::ICM20948_ADDR_ALT] {
+        let mut device = i2c_bus.device(addr);
+        let chip = match probe(&mut device, registry::MPU6050_RA_WHO_AM_I) {
+            Some(registry::MPU6050_ID) => Some(ImuChip::Mpu6050),
+            Some(registry::MPU6500_ID) => Some(ImuChip::Mpu6500),
+            Some(registry::MPU9250_ID | registry::MPU9255_ID) => Some(ImuChip::Mpu9250),
+            Some(_) if probe(&mut device, registry::ICM20948_WHO_AM_I.address()) == Some(registry::ICM20948_ID) => {
+                Some(ImuChip::Icm20948)
+            }
+            _ => None,
+        };
+
+        // Les MPU sont toujours à l'adresse par défaut (AD0 à la masse)
+        if let Some(chip) = chip.filter(|&chip| addr == registry::IMU_ADDR || chip == ImuChip::Icm20948) {
+            return Some((chip, addr));
+        }
+    }
+
+    [registry::BNO055_ADDR, registry::BNO055_ADDR_ALT]
+        .into_iter()
+        .find(|&addr| probe(&mut i2c_bus.device(addr), registry::BNO055_CHIP_ID.address()) == Some(registry::BNO055_ID))
+        .map(|addr| (ImuChip::Bno055, addr))
+}
+
+/// Adresse de la puce choisie dans la configuration : la première qui répond (broche d'adresse à la masse ou non)
+fn chip_address<B: I2CBus>(i2c_bus: &I2CManager<B>, chip: ImuChip) -> u16 {
+    let (addresses, register) = match chip {
+        ImuChip::Icm20948 => ([registry::ICM20948_ADDR, registry::ICM20948_ADDR_ALT], registry::ICM20948_WHO_AM_I.address()),
+        ImuChip::Bno055 => ([registry::BNO055_ADDR, registry::BNO055_ADDR_ALT], registry::BNO055_CHIP_ID.address()),
+        // Le pilote des MPU n'utilise que l'adresse par défaut (AD0 à la masse)
+        _ => return registry::IMU_ADDR,
+    };
+
+    addresses
+        .into_iter()
+        .find(|&addr| probe(&mut i2c_bus.device(addr), register).is_some())
+        .unwrap_or(addresses[0])
+}
+
+/// Créer le pilote de l'IMU choisie dans la configuration (`imu_chip`), ou détectée sur le bus
+pub(crate) fn detect<B: I2CBus>(i2c_bus: &I2CManager<B>, config: &Config) -> anyhow::Result<(Box<dyn ImuDriver>, I2CDevice<B>)> {
+    let (chip, addr) = match config.imu_chip {
+        Some(chip) => (chip, chip_address(i2c_bus, chip)),
+        None => detect_chip(i2c_bus).ok_or(anyhow!("[IMU] Aucune IMU détectée"))?,
+    };
+
+    println!("[IMU] Puce: {:?} ({:#04x})", chip, addr);
+    let mut i2c = i2c_bus.device(addr);
+    let driver: Box<dyn ImuDriver> = match chip {
+        ImuChip::Mpu6050 | ImuChip::Mpu6500 | ImuChip::Mpu9250 => Box::new(IMU::new(&mut i2c, config, chip)?),
+        ImuChip::Icm20948 => Box::new(ICM20948::new(&mut i2c, config, addr)?),
+        ImuChip::Bno055 => Box::new(BNO055::new(&mut i2c, config, addr)?),
+    };
+
+    Ok((driver, i2c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+
+    #[test]
+    fn chip_detected_by_identification_register() {
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::MPU6050_RA_WHO_AM_I, &[registry::MPU9250_ID]);
+        bus.attach(registry::IMU_ADDR, device);
+        assert_eq!(detect_chip(&I2CManager::new(bus)), Some((ImuChip::Mpu9250, registry::IMU_ADDR)));
+
+        // ICM-20948 à l'adresse alternative : WHO_AM_I en 0x00
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::ICM20948_WHO_AM_I.address(), &[registry::ICM20948_ID]);
+        bus.attach(registry::ICM20948_ADDR_ALT, device);
+        assert_eq!(detect_chip(&I2CManager::new(bus)), Some((ImuChip::Icm20948, registry::ICM20948_ADDR_ALT)));
+
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::BNO055_CHIP_ID.address(), &[registry::BNO055_ID]);
+        bus.attach(registry::BNO055_ADDR, device);
+        assert_eq!(detect_chip(&I2CManager::new(bus)), Some((ImuChip::Bno055, registry::BNO055_ADDR)));
+
+        assert_eq!(detect_chip(&I2CManager::new(SimBus::new())), None);
+
+        // Puce choisie dans la configuration, à l'adresse qui répond
+        let mut bus = SimBus::new();
+        bus.attach(registry::BNO055_ADDR_ALT, SimDevice::new());
+        let bus = I2CManager::new(bus);
+        assert_eq!(chip_address(&bus, ImuChip::Bno055), registry::BNO055_ADDR_ALT);
+        assert_eq!(chip_address(&bus, ImuChip::Icm20948), registry::ICM20948_ADDR);
+    }
+}
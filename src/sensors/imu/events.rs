@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Sensibilité des seuils de détection (g par LSB)
+const THRESHOLD_LSB: f32 = 0.002;
+
+/// Sensibilité de la durée d'immobilité (ms par LSB)
+const ZERO_MOTION_DURATION_LSB: u16 = 64;
+
+/// Evénement détecté par le module
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) enum ImuEventKind {
+    /// Accélération au-delà du seuil de mouvement (choc)
+    Impact,
+    /// Chute libre (roues décollées)
+    Jump,
+    /// Plus aucun mouvement
+    Parked,
+    /// De nouveau en mouvement après `Parked`
+    Moving,
+}
+
+/// Evénement horodaté par l'horloge d'échantillonnage de l'IMU (en secondes)
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct ImuEvent {
+    pub kind: ImuEventKind,
+    pub timestamp: f64,
+}
+
+/// Réglage des détecteurs du module, en valeurs de registres
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct EventDetectors {
+    pub motion_threshold: u8,
+    pub motion_duration: u8,
+    pub free_fall_threshold: u8,
+    pub free_fall_duration: u8,
+    pub zero_motion_threshold: u8,
+    pub zero_motion_duration: u8,
+}
+
+impl EventDetectors {
+    /// Détecteurs configurés, `None` s'ils sont désactivés
+    pub(crate) fn new(config: &Config) -> Option<Self> {
+        if !config.imu_events {
+            return None;
+        }
+
+        Some(Self {
+            motion_threshold: threshold(config.imu_motion_threshold_g),
+            motion_duration: config.imu_motion_duration_ms,
+            free_fall_threshold: threshold(config.imu_free_fall_threshold_g),
+            free_fall_duration: config.imu_free_fall_duration_ms,
+            zero_motion_threshold: threshold(config.imu_zero_motion_threshold_g),
+            zero_motion_duration: (config.imu_zero_motion_duration_ms / ZERO_MOTION_DURATION_LSB).clamp(1, 255) as u8,
+        })
+    }
+}
+
+/// Seuil en g vers la valeur du registre
+fn threshold(g: f32) -> u8 {
+    (g / THRESHOLD_LSB).round().clamp(1.0, 255.0) as u8
+}
@@ -0,0 +1,481 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use nalgebra::{Matrix3, Vector3};
+
+use crate::config::Config;
+use crate::i2c::{I2CBit, I2CBus};
+use crate::sensors::ahrs::Ahrs;
+use crate::sensors::imu::calibration::{GyroTempCompensation, GyroTempModel, ImuCalibration, StillnessDetector};
+use crate::sensors::imu::driver::{ImuChip, ImuDriver, MagInput};
+use crate::sensors::imu::registry;
+use crate::sensors::imu::registry::{AccelRange, DlpfBandwidth, GyroRange, IcmAccelDlpf, IcmGyroDlpf};
+
+/// Attente de la fin de la réinitialisation du module
+const RESET_DELAY: Duration = Duration::from_millis(100);
+
+/// Nombre de mesures de la calibration au démarrage
+const CALIBRATION_SAMPLES: usize = 500;
+
+/// Pilote de l'ICM-20948 (échantillons lus par lots dans la FIFO)
+/// Le magnétomètre intégré (AK09916) n'est pas utilisé.
+pub(crate) struct ICM20948 {
+    addr: u16,
+    gyro_cal: Vector3<f32>,
+    accel_cal: Vector3<f32>,
+    gyro_temp: GyroTempModel,
+    gyro_temp_comp: GyroTempCompensation,
+    still: StillnessDetector,
+    new_calibration: Option<ImuCalibration>,
+    gyro_scale: f32,
+    accel_scale: f32,
+    gyro_range: GyroRange,
+    accel_range: AccelRange,
+    dlpf: DlpfBandwidth,
+    divider: u16,
+    ahrs: Ahrs,
+    orientation: Matrix3<f32>,
+    accel: Vector3<f32>,
+    gyro: Vector3<f32>,
+    raw_accel: Vector3<i16>,
+    raw_gyro: Vector3<i16>,
+    temp: f32,
+    sample_period: Duration,
+    timestamp: Duration,
+    last_drain: Option<Instant>,
+}
+
+impl ICM20948 {
+    /// Constructeur
+    pub(crate) fn new<B: I2CBus>(i2c: &mut B, config: &Config, addr: u16) -> anyhow::Result<Self> {
+        let divider = Self::sample_rate_divider(config.imu_rate_hz);
+        let sample_period = Duration::from_secs_f32((divider as f32 + 1.0) / registry::ICM20948_BASE_RATE);
+
+        let mut icm = Self {
+            addr,
+            gyro_cal: Vector3::zeros(),
+            accel_cal: Vector3::zeros(),
+            gyro_temp: GyroTempModel::default(),
+            gyro_temp_comp: config.imu_gyro_temp_comp,
+            still: StillnessDetector::with_period(sample_period),
+            new_calibration: None,
+            gyro_scale: Self::gyro_scale(config.imu_gyro_range),
+            accel_scale: Self::accel_scale(config.imu_accel_range),
+            gyro_range: config.imu_gyro_range,
+            accel_range: config.imu_accel_range,
+            dlpf: config.imu_dlpf,
+            divider,
+            ahrs: Ahrs::new(config),
+            orientation: config.imu_orientation.matrix(),
+            accel: Vector3::zeros(),
+            gyro: Vector3::zeros(),
+            raw_accel: Vector3::zeros(),
+            raw_gyro: Vector3::zeros(),
+            temp: 0.0,
+            sample_period,
+            timestamp: Duration::ZERO,
+            last_drain: None,
+        };
+
+        i2c.set_slave_address(addr)?;
+        icm.whoami(i2c)?;
+        i2c.ecriture_field(registry::ICM20948_PWR1_DEVICE_RESET, true)?;
+        sleep(RESET_DELAY);
+        icm.init_module(i2c)?;
+
+        // Seule la calibration logicielle est possible (pas de registres de décalage gérés)
+        match config.imu_calibration.filter(|c| c.hardware.is_none()) {
+            Some(calibration) => {
+                println!("[IMU] Calibration enregistrée.");
+                icm.set_calibration(calibration);
+            }
+            None => icm.calibration_imu(i2c)?,
+        }
+
+        // La FIFO s'est remplie pendant la calibration
+        icm.reset_fifo(i2c)?;
+
+        Ok(icm)
+    }
+
+    /// Calibration au démarrage, uniquement si la voiture reste immobile et à plat pendant les mesures
+    fn calibration_imu<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
+        println!("[IMU] Calibration ...");
+
+        let mut detector = StillnessDetector::new(CALIBRATION_SAMPLES);
+        let mut calibration = None;
+        for _ in 0..CALIBRATION_SAMPLES {
+            let (accel, gyro) = self.read_sample(i2c)?;
+            calibration = detector.push(gyro, accel);
+            sleep(self.sample_period);
+        }
+
+        match calibration {
+            Some(mut calibration) => {
+                self.gyro_temp.learn(self.temp, calibration.gyro);
+                calibration.gyro_temp = Some(self.gyro_temp);
+                self.set_calibration(calibration);
+                self.new_calibration = Some(calibration);
+            }
+            None => println!("[IMU] Voiture en mouvement, calibration ignorée."),
+        }
+
+        Ok(())
+    }
+
+    /// Lis un échantillon : accélération (en g) et vitesse angulaire (en °/s) non calibrées, température
+    fn read_sample<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<(Vector3<f32>, Vector3<f32>)> {
+        // ACCEL_XOUT_H .. GYRO_ZOUT_L puis TEMP_OUT_H : 7 valeurs consécutives de 16 bits
+        let data = i2c.lecture_i16_be::<7>(registry::ICM20948_RA_ACCEL_XOUT_H)?;
+        Ok(self.decode_sample(data))
+    }
+
+    /// Décode un échantillon (même ordre dans les registres et dans la FIFO)
+    fn decode_sample(&mut self, data: [i16; 7]) -> (Vector3<f32>, Vector3<f32>) {
+        self.raw_accel = Vector3::new(data[0], data[1], data[2]);
+        self.raw_gyro = Vector3::new(data[3], data[4], data[5]);
+        self.temp = (data[6] as f32 / 333.87) + 21.0;
+
+        let accel = self.raw_accel.map(|v| v as f32) / self.accel_scale;
+        let gyro = self.raw_gyro.map(|v| v as f32) / self.gyro_scale;
+        (accel, gyro)
+    }
+
+    /// Vide la FIFO et repart d'un échantillon aligné (banque 0)
+    fn reset_fifo<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
+        i2c.ecriture_reg(registry::ICM20948_FIFO_RST, registry::ICM20948_FIFO_RESET_ALL)?;
+        i2c.ecriture_reg(registry::ICM20948_FIFO_RST, 0)?;
+        self.last_drain = None;
+        Ok(())
+    }
+
+    /// Récupére tous les échantillons présents dans la FIFO (dans l'ordre d'acquisition, banque 0)
+    /// Retourne `None` si la FIFO a débordé : les échantillons sont perdus, elle doit être vidée.
+    fn read_fifo<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<Option<Vec<[i16; 7]>>> {
+        let overflow = i2c.lecture_field(registry::ICM20948_INTSTATUS2_FIFO_OVERFLOW)? != 0;
+        let count = i2c.lecture_reg(registry::ICM20948_FIFO_COUNT)? as usize;
+        if overflow || count >= registry::ICM20948_FIFO_SIZE {
+            return Ok(None);
+        }
+
+        // Un échantillon par transaction, jamais retentée : les octets lus sont consommés
+        let mut samples = Vec::with_capacity(count / registry::ICM20948_FIFO_SAMPLE_SIZE);
+        for _ in 0..count / registry::ICM20948_FIFO_SAMPLE_SIZE {
+            let mut buffer = [0u8; registry::ICM20948_FIFO_SAMPLE_SIZE];
+            i2c.fifo_read(registry::ICM20948_RA_FIFO_R_W, &mut buffer)?;
+
+            let mut words = [0i16; 7];
+            for (word, bytes) in words.iter_mut().zip(buffer.chunks_exact(2)) {
+                *word = i16::from_be_bytes([bytes[0], bytes[1]]);
+            }
+            samples.push(words);
+        }
+
+        Ok(Some(samples))
+    }
+
+    /// Sensibilité du gyroscope (LSB par °/s)
+    fn gyro_scale(range: GyroRange) -> f32 {
+        match range {
+            GyroRange::Fs250  => 131.0,
+            GyroRange::Fs500  => 65.5,
+            GyroRange::Fs1000 => 32.8,
+            GyroRange::Fs2000 => 16.4,
+        }
+    }
+
+    /// Sensibilité de l'accéléromètre (LSB par g)
+    fn accel_scale(range: AccelRange) -> f32 {
+        match range {
+            AccelRange::Fs2  => 16384.0,
+            AccelRange::Fs4  => 8192.0,
+            AccelRange::Fs8  => 4096.0,
+            AccelRange::Fs16 => 2048.0,
+        }
+    }
+
+    /// Diviseur (GYRO_SMPLRT_DIV et ACCEL_SMPLRT_DIV) de la fréquence interne de 1125 Hz
+    fn sample_rate_divider(rate: u16) -> u16 {
+        let divider = (registry::ICM20948_BASE_RATE / rate.max(1) as f32).round() as u16;
+        divider.clamp(1, 256) - 1
+    }
+
+    /// Qui suis-je ? (le WHO_AM_I est en banque 0)
+    fn whoami<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        self.select_bank(i2c, 0)?;
+        let who = i2c.lecture_reg(registry::ICM20948_WHO_AM_I)? as u8;
+        if who != registry::ICM20948_ID {
+            return Err(anyhow!("[IMU] Capteur non reconnu (WHO_AM_I: {:#04x})", who));
+        }
+        Ok(())
+    }
+
+    /// Sélectionne la banque de registres
+    fn select_bank<B: I2CBus>(&self, i2c: &mut B, bank: u8) -> anyhow::Result<()> {
+        i2c.ecriture_field(registry::ICM20948_BANK_SEL, bank)
+    }
+
+    /// Réveille le module, applique les échelles, le filtre passe-bas et la fréquence d'échantillonnage
+    fn init_module<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
+        println!("[IMU] Initialisation ...");
+        self.select_bank(i2c, 0)?;
+        i2c.ecriture_field(registry::ICM20948_PWR1_CLKSEL, registry::ICM20948_CLOCK_AUTO)?;
+        i2c.ecriture_field(registry::ICM20948_PWR1_SLEEP, false)?;
+
+        // Le magnétomètre intégré reste accessible directement depuis le bus principal
+        i2c.ecriture_field(registry::ICM20948_INTCFG_BYPASS_EN, true)?;
+        i2c.ecriture_field(registry::ICM20948_INTEN1_RAW_DATA_0_RDY, true)?;
+
+        self.select_bank(i2c, 2)?;
+        i2c.ecriture_reg(registry::ICM20948_GYRO_SMPLRT_DIV, self.divider)?;
+        i2c.ecriture_reg(registry::ICM20948_ACCEL_SMPLRT_DIV, self.divider)?;
+        i2c.ecriture_field(registry::ICM20948_GCONFIG1_FS_SEL, self.gyro_range)?;
+        let (gyro_dlpf, accel_dlpf) = icm_dlpf(self.dlpf);
+        i2c.ecriture_field(registry::ICM20948_GCONFIG1_DLPFCFG, gyro_dlpf)?;
+        i2c.ecriture_field(registry::ICM20948_GCONFIG1_FCHOICE, true)?;
+        i2c.ecriture_field(registry::ICM20948_ACONFIG_FS_SEL, self.accel_range)?;
+        i2c.ecriture_field(registry::ICM20948_ACONFIG_DLPFCFG, accel_dlpf)?;
+        i2c.ecriture_field(registry::ICM20948_ACONFIG_FCHOICE, true)?;
+
+        // Accélération, vitesse angulaire et température placées dans la FIFO à chaque échantillon
+        self.select_bank(i2c, 0)?;
+        i2c.ecriture_reg(registry::ICM20948_FIFO_EN_2, registry::ICM20948_FIFO_SOURCES)?;
+        i2c.ecriture_field(registry::ICM20948_USERCTRL_FIFO_EN, true)?;
+        self.reset_fifo(i2c)
+    }
+
+    /// Relis la configuration appliquée par `init_module`
+    fn is_configured<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool> {
+        self.select_bank(i2c, 0)?;
+        let awake = !i2c.lecture_field(registry::ICM20948_PWR1_SLEEP)?;
+        let fifo = i2c.lecture_field(registry::ICM20948_USERCTRL_FIFO_EN)?
+            && i2c.lecture_reg(registry::ICM20948_FIFO_EN_2)? == registry::ICM20948_FIFO_SOURCES;
+
+        self.select_bank(i2c, 2)?;
+        let configured = i2c.lecture_reg(registry::ICM20948_GYRO_SMPLRT_DIV)? == self.divider
+            && i2c.lecture_field(registry::ICM20948_GCONFIG1_FS_SEL)? == self.gyro_range
+            && i2c.lecture_field(registry::ICM20948_GCONFIG1_DLPFCFG)? == icm_dlpf(self.dlpf).0
+            && i2c.lecture_field(registry::ICM20948_ACONFIG_FS_SEL)? == self.accel_range;
+
+        self.select_bank(i2c, 0)?;
+        Ok(awake && fifo && configured)
+    }
+
+    /// Applique une calibration (décalages en °/s et en g)
+    fn set_calibration(&mut self, calibration: ImuCalibration) {
+        self.gyro_cal = calibration.gyro;
+        self.accel_cal = calibration.accel;
+        if let Some(gyro_temp) = calibration.gyro_temp {
+            self.gyro_temp = gyro_temp;
+        }
+
+        println!("[IMU] Calibration GYRO: (X: {} Y: {} Z: {})", self.gyro_cal.x, self.gyro_cal.y, self.gyro_cal.z);
+        println!("[IMU] Calibration ACCEL: (X: {} Y: {} Z: {})", self.accel_cal.x, self.accel_cal.y, self.accel_cal.z);
+    }
+}
+
+/// Filtres de l'ICM-20948 dont la coupure est la plus proche de la bande passante demandée (tables des MPU)
+fn icm_dlpf(dlpf: DlpfBandwidth) -> (IcmGyroDlpf, IcmAccelDlpf) {
+    match dlpf {
+        DlpfBandwidth::Bw256 | DlpfBandwidth::Bw188 => (IcmGyroDlpf::Hz197, IcmAccelDlpf::Hz246),
+        DlpfBandwidth::Bw98 => (IcmGyroDlpf::Hz120, IcmAccelDlpf::Hz111),
+        DlpfBandwidth::Bw42 => (IcmGyroDlpf::Hz51, IcmAccelDlpf::Hz50),
+        DlpfBandwidth::Bw20 => (IcmGyroDlpf::Hz24, IcmAccelDlpf::Hz24),
+        DlpfBandwidth::Bw10 => (IcmGyroDlpf::Hz12, IcmAccelDlpf::Hz12),
+        DlpfBandwidth::Bw5 => (IcmGyroDlpf::Hz6, IcmAccelDlpf::Hz6),
+    }
+}
+
+impl ImuDriver for ICM20948 {
+    fn chip(&self) -> ImuChip {
+        ImuChip::Icm20948
+    }
+
+    /// Lis tous les échantillons en attente dans la FIFO, chacun espacé d'exactement une période
+    fn update(&mut self, mut i2c: &mut dyn I2CBus, mag: MagInput) -> anyhow::Result<usize> {
+        i2c.set_slave_address(self.addr)?;
+
+        let samples = match self.read_fifo(&mut i2c) {
+            Ok(Some(samples)) => samples,
+            result => {
+                // Echantillons perdus : l'horloge avance du temps écoulé depuis la dernière lecture
+                if let Some(last_drain) = self.last_drain {
+                    self.timestamp += last_drain.elapsed();
+                }
+                self.still.clear();
+
+                // FIFO pleine ou lecture interrompue au milieu d'un échantillon : elle est vidée pour repartir alignée
+                let reset = self.reset_fifo(&mut i2c);
+                return match result {
+                    Err(e) => {
+                        if let Err(reset) = reset {
+                            println!("[IMU] FIFO non réinitialisée: {}", reset);
+                        }
+                        Err(e)
+                    }
+                    Ok(_) => {
+                        println!("[IMU] FIFO pleine, échantillons perdus.");
+                        reset.map(|_| 0)
+                    }
+                };
+            }
+        };
+
+        if !samples.is_empty() {
+            self.last_drain = Some(Instant::now());
+        }
+
+        // Une seule mesure du magnétomètre par appel (pas de bus auxiliaire)
+        let field = match mag {
+            MagInput::Field(field) => field,
+            MagInput::Aux(_) => None,
+        };
+
+        let dt = self.sample_period.as_secs_f32();
+        let mut gyro_bias = None;
+        for data in &samples {
+            let (accel, gyro) = self.decode_sample(*data);
+
+            // Voiture immobile : nouveau biais du gyroscope, appliqué après ce lot d'échantillons
+            if let Some(bias) = self.still.push_refresh(gyro, accel) {
+                gyro_bias = Some(bias);
+            }
+
+            // Biais du gyroscope selon la température, sinon celui de la dernière calibration
+            let gyro_offset = self.gyro_temp.bias(self.temp, self.gyro_temp_comp).unwrap_or(self.gyro_cal);
+            self.accel = self.orientation * (accel - self.accel_cal);
+            self.gyro = self.orientation * (gyro - gyro_offset);
+
+            self.ahrs.update(self.gyro.map(|v| v.to_radians()), self.accel, field, dt);
+            self.timestamp += self.sample_period;
+        }
+
+        // Le décalage de l'accéléromètre est conservé
+        if let Some(gyro_bias) = gyro_bias {
+            println!("[IMU] Voiture immobile, nouvelle calibration du gyroscope.");
+            self.gyro_temp.learn(self.temp, gyro_bias);
+            let calibration = ImuCalibration {
+                gyro: gyro_bias,
+                accel: self.accel_cal,
+                hardware: None,
+                gyro_temp: Some(self.gyro_temp),
+            };
+            self.set_calibration(calibration);
+            self.new_calibration = Some(calibration);
+        }
+
+        Ok(samples.len())
+    }
+
+    fn check_and_recover(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<bool> {
+        i2c.set_slave_address(self.addr)?;
+        self.whoami(&mut i2c)?;
+
+        if self.is_configured(&mut i2c)? {
+            return Ok(false);
+        }
+
+        println!("[IMU] Configuration perdue, réinitialisation du module ...");
+        self.init_module(&mut i2c)?;
+        self.ahrs.reset();
+        Ok(true)
+    }
+
+    fn take_new_calibration(&mut self) -> Option<ImuCalibration> {
+        self.new_calibration.take()
+    }
+
+    fn set_mag_decl(&mut self, mag_decl: f32) {
+        self.ahrs.set_mag_decl(mag_decl);
+    }
+
+    fn get_angles(&self) -> Vector3<f32> {
+        let euler = self.ahrs.euler();
+        Vector3::new(euler.y, euler.x, euler.z)
+    }
+
+    fn get_quaternion(&self) -> (f32, f32, f32, f32) {
+        let q = self.ahrs.quaternion();
+        (q.w, q.i, q.j, q.k)
+    }
+
+    fn get_acceleration(&self) -> Vector3<f32> {
+        self.accel
+    }
+
+    fn get_angular_rate(&self) -> Vector3<f32> {
+        self.gyro
+    }
+
+    fn get_raw(&self) -> (Vector3<i16>, Vector3<i16>) {
+        (self.raw_accel, self.raw_gyro)
+    }
+
+    fn get_temp(&self) -> f32 {
+        self.temp
+    }
+
+    fn get_timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    fn get_sample_period(&self) -> Duration {
+        self.sample_period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice, SimResponse};
+
+    #[test]
+    fn configured_in_bank_2_and_sample_decoded() {
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::ICM20948_WHO_AM_I.address(), &[registry::ICM20948_ID]);
+        bus.attach(registry::ICM20948_ADDR, device);
+        let mut config = Config::new();
+        config.imu_dlpf = DlpfBandwidth::Bw188;
+        let mut icm = ICM20948::new(&mut bus, &config, registry::ICM20948_ADDR).unwrap();
+
+        // Banque 0 de nouveau sélectionnée, 200 Hz : 1125 Hz / 6 (la banque 2 partage les adresses de la banque 0)
+        let device = bus.device(registry::ICM20948_ADDR).unwrap();
+        assert_eq!(device.registers(registry::ICM20948_REG_BANK_SEL.address(), 1), &[0]);
+        assert_eq!(device.registers(registry::ICM20948_ACCEL_SMPLRT_DIV.address(), 2), &[0, 5]);
+
+        // Filtres de l'ICM les plus proches de 188 Hz : 197 Hz (gyroscope) et 246 Hz (accéléromètre), FCHOICE à 1
+        assert_eq!(device.registers(registry::ICM20948_GYRO_CONFIG_1.address(), 1), &[(IcmGyroDlpf::Hz197 as u8) << 3 | 1]);
+        assert_eq!(device.registers(registry::ICM20948_ACCEL_CONFIG.address(), 1), &[(IcmAccelDlpf::Hz246 as u8) << 3 | 1]);
+        assert!(icm.is_configured(&mut bus).unwrap());
+
+        // Accélération, vitesse angulaire et température dans la FIFO
+        let device = bus.device(registry::ICM20948_ADDR).unwrap();
+        assert_eq!(device.registers(registry::ICM20948_FIFO_EN_2.address(), 1), &[registry::ICM20948_FIFO_SOURCES as u8]);
+        assert_eq!(device.registers(registry::ICM20948_USER_CTRL.address(), 1), &[1 << 6]);
+
+        // FIFO vide : rien n'est lu
+        assert_eq!(icm.update(&mut bus, MagInput::Field(None)).unwrap(), 0);
+
+        // Deux échantillons en attente, espacés d'une période (1125 Hz / 6)
+        let device = bus.device(registry::ICM20948_ADDR).unwrap();
+        let count = (2 * registry::ICM20948_FIFO_SAMPLE_SIZE) as u16;
+        device.set_registers(registry::ICM20948_FIFO_COUNT.address(), &count.to_be_bytes());
+        for gyro_x in [0i16, 131] {
+            let data: Vec<u8> = [0i16, -8192, 16384, gyro_x, -262, 0, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+            device.script_read(registry::ICM20948_RA_FIFO_R_W, SimResponse::Data(data));
+        }
+
+        assert_eq!(icm.update(&mut bus, MagInput::Field(None)).unwrap(), 2);
+        assert_eq!(icm.get_timestamp(), icm.get_sample_period() * 2);
+        assert_eq!(icm.get_acceleration(), Vector3::new(0.0, -0.5, 1.0));
+        assert_eq!(icm.get_angular_rate(), Vector3::new(1.0, -2.0, 0.0));
+        assert!((icm.get_temp() - 21.0).abs() < 1e-3);
+
+        // Débordement : la FIFO est vidée, aucun échantillon n'est traité
+        let device = bus.device(registry::ICM20948_ADDR).unwrap();
+        device.set_registers(registry::ICM20948_INT_STATUS_2.address(), &[0x01]);
+        assert_eq!(icm.update(&mut bus, MagInput::Field(None)).unwrap(), 0);
+    }
+}
@@ -3,46 +3,154 @@
 use std::{error::Error, task::Poll};
 use std::fmt;
 use tokio_stream::Stream;
-use rppal::i2c::I2c;
-use crate::i2c::I2CBit;
+use crate::i2c::{I2CBit, I2CBus};
 use std::time::Duration;
 use std::thread::sleep;
 use std::time::Instant;
-use nalgebra::Vector3;
+use nalgebra::{Matrix3, Vector3};
+use anyhow::anyhow;
+use crate::config::Config;
+use crate::sensors::ahrs::Ahrs;
+use crate::sensors::imu::calibration::{CalibrationMode, GyroTempCompensation, GyroTempModel, ImuCalibration, ImuHardwareOffsets, StillnessDetector};
+use crate::sensors::imu::driver::{AuxMagnetometer, ImuChip, ImuDriver, MagInput};
+use crate::sensors::imu::events::{EventDetectors, ImuEvent, ImuEventKind};
+use crate::sensors::imu::self_test::SelfTestReport;
 use crate::sensors::imu::registry;
+use crate::sensors::imu::registry::{AccelRange, ClockSource, DlpfBandwidth, GyroRange};
 
+/// Nombre d'échantillons moyennés pour chaque mesure de l'auto-test
+const SELF_TEST_SAMPLES: u32 = 20;
+
+/// Attente de la stabilisation des mesures après l'activation (ou la désactivation) de l'auto-test
+const SELF_TEST_SETTLE: Duration = Duration::from_millis(50);
+
+/// Echantillon brut de l'IMU (même instant pour tous les axes)
+struct RawSample {
+    accel: Vector3<f32>,
+    temp: i16,
+    gyro: Vector3<f32>,
+    /// Registres du magnétomètre auxiliaire, s'il est lu par le module
+    mag: Option<[i16; 3]>,
+}
+
+impl RawSample {
+    /// Accélération, température puis vitesse angulaire (ordre des registres et de la FIFO)
+    fn from_words(data: [i16; 7]) -> Self {
+        Self {
+            accel: Vector3::new(data[0] as f32, data[1] as f32, data[2] as f32),
+            temp: data[3],
+            gyro: Vector3::new(data[4] as f32, data[5] as f32, data[6] as f32),
+            mag: None,
+        }
+    }
+}
+
+/// Pilote des MPU6050, MPU6500 et MPU9250 (registres communs)
 pub(crate) struct IMU {
+    chip: ImuChip,
     gyro_cal: Vector3<f32>,
     accel_cal: Vector3<f32>,
+    gyro_temp: GyroTempModel,
+    gyro_temp_comp: GyroTempCompensation,
+    still: StillnessDetector,
+    new_calibration: Option<ImuCalibration>,
+    calibration_mode: CalibrationMode,
+    hardware_offsets: Option<ImuHardwareOffsets>,
+    self_test: Option<SelfTestReport>,
+    detectors: Option<EventDetectors>,
+    events: Vec<ImuEvent>,
+    parked: bool,
     gyro_scale: f32,
     accel_scale: f32,
-    angles: Vector3<f32>,
+    gyro_range: GyroRange,
+    accel_range: AccelRange,
+    dlpf: DlpfBandwidth,
+    who_am_i: u8,
+    ahrs: Ahrs,
+    orientation: Matrix3<f32>,
+    aux_mag: Option<AuxMagnetometer>,
+    mag: Option<[i16; 3]>,
+    accel: Vector3<f32>,
+    gyro: Vector3<f32>,
+    raw_accel: Vector3<i16>,
+    raw_gyro: Vector3<i16>,
     temp: f32,
-    speed: f64,
-    last_measurment: Option<Instant>,
+    output_rate: u16,
+    sample_period: Duration,
+    timestamp: Duration,
+    last_drain: Option<Instant>,
 }
 
 impl IMU {
+    /// Adresse du module sur le bus I2C
+    pub(crate) const ADDR: u16 = registry::IMU_ADDR;
+
     /// Constructeur
-    pub(crate) fn new(i2c: &mut I2c) -> anyhow::Result<Self> {
+    pub(crate) fn new<B: I2CBus>(i2c: &mut B, config: &Config, chip: ImuChip) -> anyhow::Result<Self> {
 
         // Créer l'objet et commence l'initialisation
         let mut imu = Self {
+            chip,
             gyro_cal: Vector3::new(0.0, 0.0, 0.0),
             accel_cal: Vector3::new(0.0, 0.0, 0.0),
+            gyro_temp: GyroTempModel::default(),
+            gyro_temp_comp: config.imu_gyro_temp_comp,
+            still: StillnessDetector::new(0),
+            new_calibration: None,
+            calibration_mode: config.imu_calibration_mode,
+            hardware_offsets: None,
+            self_test: None,
+            detectors: EventDetectors::new(config).filter(|_| chip == ImuChip::Mpu6050),
+            events: Vec::new(),
+            parked: false,
             gyro_scale: 131.0,
             accel_scale: 16384.0,
-            angles: Vector3::new(0.0, 0.0, 0.0),
+            gyro_range: config.imu_gyro_range,
+            accel_range: config.imu_accel_range,
+            dlpf: config.imu_dlpf,
+            who_am_i: 0,
+            ahrs: Ahrs::new(config),
+            orientation: config.imu_orientation.matrix(),
+            aux_mag: None,
+            mag: None,
+            accel: Vector3::zeros(),
+            gyro: Vector3::zeros(),
+            raw_accel: Vector3::zeros(),
+            raw_gyro: Vector3::zeros(),
             temp: 0.0,
-            speed: 0.0,
-            last_measurment: Option::None,
+            output_rate: config.imu_rate_hz,
+            sample_period: Duration::ZERO,
+            timestamp: Duration::ZERO,
+            last_drain: None,
         };
 
         // Prépare le module
         imu.set_slave(i2c)?;
         imu.reset(i2c)?;
+        imu.who_am_i = imu.whoami(i2c)?;
         imu.init_module(i2c)?;
-        imu.calibration_imu(i2c)?;
+        imu.still = StillnessDetector::with_period(imu.sample_period);
+
+        // Auto-test avant la calibration (les mesures sont faussées pendant le test)
+        let report = imu.self_test(i2c)?;
+        report.log();
+        imu.self_test = Some(report);
+
+        // Réutilise la calibration enregistrée (du même mode), sinon calibre si la voiture est immobile
+        let hardware = imu.calibration_mode == CalibrationMode::Hardware;
+        match config.imu_calibration.filter(|c| c.hardware.is_some() == hardware) {
+            Some(calibration) => {
+                println!("[IMU] Calibration enregistrée.");
+                if let Some(offsets) = calibration.hardware {
+                    imu.set_hardware_offsets(i2c, offsets)?;
+                }
+                imu.set_calibration(calibration);
+            }
+            None => imu.calibration_imu(i2c)?,
+        }
+
+        // La FIFO s'est remplie pendant la calibration
+        imu.reset_fifo(i2c)?;
 
         // Vérification
         imu.debug_get_info(i2c)?;
@@ -50,24 +158,65 @@ impl IMU {
         Ok(imu)
     }
 
-    pub(crate) fn recalibrate(&mut self, i2c: &mut I2c) {
-        self.calibration_imu(i2c);
+    pub(crate) fn recalibrate<B: I2CBus>(&mut self, i2c: &mut B, config: &Config) -> anyhow::Result<()> {
+        self.ahrs.set_config(config);
+        self.orientation = config.imu_orientation.matrix();
+        self.calibration_imu(i2c)?;
+        self.reset_fifo(i2c)
     }
 
-    pub(crate) fn set_speed(&mut self, speed: f64) {
-        self.speed = speed;
+    fn set_slave<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        i2c.set_slave_address(registry::IMU_ADDR)?;
+        Ok(())
     }
 
-    fn set_slave(&self, i2c: &mut I2c) -> anyhow::Result<()> {
-        i2c.set_slave_address(registry::IMU_ADDR);
-        Ok(())
+    /// Vérifie que le module répond toujours et n'a pas été réinitialisé (perte d'alimentation, ...)
+    /// Relance l'initialisation si la configuration relue ne correspond plus. Retourne vrai si c'est le cas.
+    pub(crate) fn check_and_recover<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<bool> {
+        self.set_slave(i2c)?;
+
+        let who = self.whoami(i2c)?;
+        if who != self.who_am_i {
+            return Err(anyhow!("[IMU] Capteur non reconnu (WHO_AM_I: {:#04x})", who));
+        }
+
+        if self.is_configured(i2c)? {
+            return Ok(false);
+        }
+
+        println!("[IMU] Configuration perdue, réinitialisation du module ...");
+        self.init_module(i2c)?;
+
+        // Les échantillons perdus ne sont pas intégrés
+        self.ahrs.reset();
+        Ok(true)
+    }
+
+    /// Relis la configuration appliquée par `init_module`
+    fn is_configured<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool> {
+        let offsets = match self.hardware_offsets {
+            Some(offsets) => self.get_hardware_offsets(i2c)? == offsets,
+            None => true,
+        };
+
+        Ok(!self.is_sleep_mode(i2c)?
+            && self.get_clock_source(i2c)? == ClockSource::PllXGyro
+            && self.get_fullscale_gyro_range(i2c)? == self.gyro_range
+            && self.get_fullscale_accel_range(i2c)? == self.accel_range
+            && self.get_dlpf_bandwidth(i2c)? == self.dlpf
+            && self.get_sample_rate_divider(i2c)? == Self::sample_rate_divider(Self::gyro_rate(self.dlpf), self.output_rate)
+            && i2c.lecture_field(registry::MPU6050_USERCTRL_FIFO_EN)?
+            && i2c.lecture_field(registry::MPU6050_USERCTRL_I2C_MST_EN)? == self.aux_mag.is_some()
+            && offsets)
     }
 
-    fn debug_get_info(&self, i2c: &mut I2c) -> anyhow::Result<()>  {
+    fn debug_get_info<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()>  {
         let clock = self.get_clock_source(i2c)?;
         let sleep = self.is_sleep_mode(i2c)?;
-        let gyro_scale_range: u8 = self.get_fullscale_gyro_range(i2c)?;
+        let gyro_scale_range = self.get_fullscale_gyro_range(i2c)?;
         let accel_scale_range = self.get_fullscale_accel_range(i2c)?;
+        let dlpf = self.get_dlpf_bandwidth(i2c)?;
+        let sample_rate = Self::gyro_rate(dlpf) as f32 / (self.get_sample_rate_divider(i2c)? as f32 + 1.0);
         let temp_enable = self.is_temp_sensor_enable(i2c)?;
         let who = self.whoami(i2c)?;
         let i2cbypass = self.get_i2c_bypass_enable(i2c)?;
@@ -76,31 +225,43 @@ impl IMU {
         println!("[IMU] Temp. Enable: {}", temp_enable);
         println!("[IMU] I2C Bypass Enable: {}", i2cbypass);
         println!("[IMU] Sleep: {}", sleep);
-        println!("[IMU] Clock source: {:#04x}", clock);
-        println!("[IMU] Gyro scale range: {:#04x}", gyro_scale_range);
-        println!("[IMU] Accel scale range: {:#04x}", accel_scale_range);
+        println!("[IMU] Clock source: {:?}", clock);
+        println!("[IMU] Gyro scale range: {:?}", gyro_scale_range);
+        println!("[IMU] Accel scale range: {:?}", accel_scale_range);
+        println!("[IMU] DLPF bandwidth: {:?}", dlpf);
+        println!("[IMU] Sample rate: {:.1} Hz", sample_rate);
         Ok(())
     }
 
     /// Qui suis-je ?
-    fn whoami(&self, i2c: &mut I2c) -> anyhow::Result<u8>  {
-        i2c.lecture_word(registry::MPU6050_RA_WHO_AM_I)
+    fn whoami<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<u8>  {
+        Ok(i2c.lecture_reg(registry::MPU6050_WHO_AM_I)? as u8)
     }
 
     /// Initialise rapidement le module avec des valeurs pré-défini
-    fn init_module(&mut self, i2c: &mut I2c) -> anyhow::Result<()>  {
+    fn init_module<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()>  {
         println!("[IMU] Initialisation ...");
-        self.set_clock_source(i2c, registry::MPU6050_CLOCK_PLL_XGYRO)?;
-        self.set_i2c_bypass_enable(i2c, true)?;
+        self.set_clock_source(i2c, ClockSource::PllXGyro)?;
         self.set_temp_sensor_enable(i2c, true)?;
         self.set_sleep_mode(i2c, false)?;
-        self.set_fullscale_accel_range(i2c, registry::MPU6050_ACCEL_FS_2)?;
-        self.set_fullscale_gyro_range(i2c, registry::MPU6050_GYRO_FS_250)?;
+        self.set_fullscale_accel_range(i2c, self.accel_range)?;
+        self.set_fullscale_gyro_range(i2c, self.gyro_range)?;
+        self.set_dlpf_bandwidth(i2c, self.dlpf)?;
+        self.set_sample_rate(i2c, self.output_rate)?;
+        self.set_data_ready_interrupt(i2c)?;
+        self.set_event_detectors(i2c)?;
+        self.set_aux_master(i2c)?;
+        self.set_fifo_enable(i2c)?;
+
+        // Les registres de décalage sont perdus à chaque réinitialisation du module
+        if let Some(offsets) = self.hardware_offsets {
+            self.set_hardware_offsets(i2c, offsets)?;
+        }
         Ok(())
     }
 
     /// Réinitialise le capteur via le trigger de tous les resets
-    fn reset(&self, i2c: &mut I2c) -> anyhow::Result<()>  {
+    fn reset<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()>  {
         i2c.ecriture_word(registry::MPU6050_RA_USER_CTRL, 0x07)?;
         i2c.ecriture_word(registry::MPU6050_RA_SIGNAL_PATH_RESET, 0x07)?;
         i2c.ecriture_word(registry::MPU6050_RA_PWR_MGMT_1, 0x80)?;
@@ -108,246 +269,941 @@ impl IMU {
     }
 
     /// Vérifie si le module est en veille
-    fn is_sleep_mode(&self, i2c: &mut I2c) -> anyhow::Result<bool>  {
-        i2c.lecture_bit8(registry::MPU6050_RA_PWR_MGMT_1, registry::MPU6050_PWR1_SLEEP_BIT)
+    fn is_sleep_mode<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool>  {
+        i2c.lecture_field(registry::MPU6050_PWR1_SLEEP)
     }
 
     /// Défini le mode veille du module
-    fn set_sleep_mode(&self, i2c: &mut I2c,  enable: bool) -> anyhow::Result<()>  {
-        i2c.ecriture_bit8(registry::MPU6050_RA_PWR_MGMT_1, registry::MPU6050_PWR1_SLEEP_BIT, enable)
+    fn set_sleep_mode<B: I2CBus>(&self, i2c: &mut B,  enable: bool) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_PWR1_SLEEP, enable)
     }
 
     /// Vérifie si le capteur de temperature est bien activé
-    fn is_temp_sensor_enable(&self, i2c: &mut I2c) -> anyhow::Result<bool>  {
-        let is_temp = i2c.lecture_bit8(registry::MPU6050_RA_PWR_MGMT_1, registry::MPU6050_PWR1_TEMP_DIS_BIT)?;
+    fn is_temp_sensor_enable<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool>  {
+        let is_temp = i2c.lecture_field(registry::MPU6050_PWR1_TEMP_DIS)?;
         Ok(!is_temp)
     }
 
     /// Défini l'activation du capteur de temperature
-    fn set_temp_sensor_enable(&self, i2c: &mut I2c,  enable: bool) -> anyhow::Result<()>  {
-        i2c.ecriture_bit8(registry::MPU6050_RA_PWR_MGMT_1, registry::MPU6050_PWR1_TEMP_DIS_BIT, !enable)
+    fn set_temp_sensor_enable<B: I2CBus>(&self, i2c: &mut B,  enable: bool) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_PWR1_TEMP_DIS, !enable)
     }
 
     /// Récupére la source de l'horloge
-    fn get_clock_source(&self, i2c: &mut I2c) -> anyhow::Result<u8>  {
-        i2c.lecture_bits8(registry::MPU6050_RA_PWR_MGMT_1, registry::MPU6050_PWR1_CLKSEL_BIT, registry::MPU6050_PWR1_CLKSEL_LENGTH)
+    fn get_clock_source<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<ClockSource>  {
+        i2c.lecture_field(registry::MPU6050_PWR1_CLKSEL)
     }
  
     /// Défini la source de l'horloge
-    fn set_clock_source(&self, i2c: &mut I2c,  source: u8) -> anyhow::Result<()>  {
-        i2c.ecriture_bits8(registry::MPU6050_RA_PWR_MGMT_1, registry::MPU6050_PWR1_CLKSEL_BIT, registry::MPU6050_PWR1_CLKSEL_LENGTH, source)
+    fn set_clock_source<B: I2CBus>(&self, i2c: &mut B,  source: ClockSource) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_PWR1_CLKSEL, source)
     }
 
     /// Récupére le scale du gyroscope
-    fn get_fullscale_gyro_range(&self, i2c: &mut I2c) -> anyhow::Result<u8>  {
-        i2c.lecture_bits8(registry::MPU6050_RA_GYRO_CONFIG, registry::MPU6050_GCONFIG_FS_SEL_BIT, registry::MPU6050_GCONFIG_FS_SEL_LENGTH)
+    fn get_fullscale_gyro_range<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<GyroRange>  {
+        i2c.lecture_field(registry::MPU6050_GCONFIG_FS_SEL)
     }
 
     /// Défini le mode "Bypass" pour l'I2C Aux.
-    fn set_i2c_bypass_enable(&self, i2c: &mut I2c,  enable: bool) -> anyhow::Result<()>  {
-        i2c.ecriture_bit8(registry::MPU6050_RA_INT_PIN_CFG, registry::MPU6050_INTCFG_I2C_BYPASS_EN_BIT, enable)
+    fn set_i2c_bypass_enable<B: I2CBus>(&self, i2c: &mut B,  enable: bool) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_INTCFG_I2C_BYPASS_EN, enable)
     }
 
     /// Récupére le mode "Bypass" pour l'I2C Aux.
-    fn get_i2c_bypass_enable(&self, i2c: &mut I2c) -> anyhow::Result<bool>  {
-        i2c.lecture_bit8(registry::MPU6050_RA_INT_PIN_CFG, registry::MPU6050_INTCFG_I2C_BYPASS_EN_BIT)
+    fn get_i2c_bypass_enable<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool>  {
+        i2c.lecture_field(registry::MPU6050_INTCFG_I2C_BYPASS_EN)
     }
     
     /// Défini le scale du gyroscope
-    fn set_fullscale_gyro_range(&mut self, i2c: &mut I2c, range: u8) -> anyhow::Result<()>  {
-        match range {
-            registry::MPU6050_GYRO_FS_250  => self.gyro_scale=131.0,
-            registry::MPU6050_GYRO_FS_500  => self.gyro_scale=65.5,
-            registry::MPU6050_GYRO_FS_1000 => self.gyro_scale=32.8,
-            registry::MPU6050_GYRO_FS_2000 => self.gyro_scale=16.4,
-            _ => println!("Gyro range invalide."),
-        }
+    fn set_fullscale_gyro_range<B: I2CBus>(&mut self, i2c: &mut B, range: GyroRange) -> anyhow::Result<()>  {
+        self.gyro_scale = match range {
+            GyroRange::Fs250  => 131.0,
+            GyroRange::Fs500  => 65.5,
+            GyroRange::Fs1000 => 32.8,
+            GyroRange::Fs2000 => 16.4,
+        };
+        self.gyro_range = range;
         
-        i2c.ecriture_bits8(registry::MPU6050_RA_GYRO_CONFIG, registry::MPU6050_GCONFIG_FS_SEL_BIT, registry::MPU6050_GCONFIG_FS_SEL_LENGTH, range)
+        i2c.ecriture_field(registry::MPU6050_GCONFIG_FS_SEL, range)
     }
 
     /// Récupére le scale de l'accélérométre
-    fn get_fullscale_accel_range(&self, i2c: &mut I2c) -> anyhow::Result<u8>  {
-        i2c.lecture_bits8(registry::MPU6050_RA_ACCEL_CONFIG, registry::MPU6050_ACONFIG_AFS_SEL_BIT, registry::MPU6050_ACONFIG_AFS_SEL_LENGTH)
+    fn get_fullscale_accel_range<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<AccelRange>  {
+        i2c.lecture_field(registry::MPU6050_ACONFIG_AFS_SEL)
     }
     
     /// Défini le scale de l'accélérométre
-    fn set_fullscale_accel_range(&mut self, i2c: &mut I2c, range: u8) -> anyhow::Result<()>  {
-        match range {
-            registry::MPU6050_ACCEL_FS_2 => self.accel_scale=16384.0,
-            registry::MPU6050_ACCEL_FS_4 => self.accel_scale=8192.0,
-            registry::MPU6050_ACCEL_FS_8 => self.accel_scale=4096.0,
-            registry::MPU6050_ACCEL_FS_16=> self.accel_scale=2048.0,
-            _ => println!("Accel range invalide."),
+    fn set_fullscale_accel_range<B: I2CBus>(&mut self, i2c: &mut B, range: AccelRange) -> anyhow::Result<()>  {
+        self.accel_scale = match range {
+            AccelRange::Fs2  => 16384.0,
+            AccelRange::Fs4  => 8192.0,
+            AccelRange::Fs8  => 4096.0,
+            AccelRange::Fs16 => 2048.0,
+        };
+        self.accel_range = range;
+
+        i2c.ecriture_field(registry::MPU6050_ACONFIG_AFS_SEL, range)
+    }
+
+    /// Récupére la bande passante du filtre passe-bas
+    fn get_dlpf_bandwidth<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<DlpfBandwidth>  {
+        i2c.lecture_field(registry::MPU6050_CFG_DLPF_CFG)
+    }
+
+    /// Défini la bande passante du filtre passe-bas (à appliquer avant la fréquence d'échantillonnage)
+    /// Sur le MPU6500, l'accéléromètre a son propre filtre (même réglage que le gyroscope)
+    fn set_dlpf_bandwidth<B: I2CBus>(&mut self, i2c: &mut B, bandwidth: DlpfBandwidth) -> anyhow::Result<()>  {
+        self.dlpf = bandwidth;
+        if self.chip != ImuChip::Mpu6050 {
+            i2c.ecriture_field(registry::MPU6500_ACONFIG2_A_DLPF_CFG, bandwidth)?;
+        }
+        i2c.ecriture_field(registry::MPU6050_CFG_DLPF_CFG, bandwidth)
+    }
+
+    /// Fréquence d'échantillonnage du gyroscope (en Hz) : 8 kHz sans filtre passe-bas, 1 kHz sinon
+    fn gyro_rate(bandwidth: DlpfBandwidth) -> u32 {
+        match bandwidth {
+            DlpfBandwidth::Bw256 => 8000,
+            _ => 1000,
+        }
+    }
+
+    /// Récupére le diviseur de la fréquence d'échantillonnage
+    fn get_sample_rate_divider<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<u8>  {
+        Ok(i2c.lecture_reg(registry::MPU6050_SMPLRT_DIV)? as u8)
+    }
+
+    /// Défini la fréquence d'échantillonnage (en Hz), la plus proche possible de celle demandée
+    fn set_sample_rate<B: I2CBus>(&mut self, i2c: &mut B, rate: u16) -> anyhow::Result<()>  {
+        let gyro_rate = Self::gyro_rate(self.dlpf);
+        let divider = Self::sample_rate_divider(gyro_rate, rate);
+        self.sample_period = Duration::from_secs_f64((divider as f64 + 1.0) / gyro_rate as f64);
+
+        i2c.ecriture_reg(registry::MPU6050_SMPLRT_DIV, divider as u16)
+    }
+
+    /// Diviseur (SMPLRT_DIV) de la fréquence du gyroscope
+    fn sample_rate_divider(gyro_rate: u32, rate: u16) -> u8 {
+        let divider = (gyro_rate as f32 / rate.max(1) as f32).round() as u32;
+        divider.clamp(1, 256) as u8 - 1
+    }
+
+    /// Active l'interruption "données prêtes" sur la broche INT (impulsion de 50 µs)
+    fn set_data_ready_interrupt<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_INTCFG_LATCH_INT_EN, false)?;
+        i2c.ecriture_field(registry::MPU6050_INTEN_DATA_RDY, true)
+    }
+
+    /// Configure les détecteurs de mouvement, de chute libre et d'immobilité (et leurs interruptions)
+    fn set_event_detectors<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()>  {
+        let Some(detectors) = self.detectors else {
+            i2c.ecriture_field(registry::MPU6050_INTEN_MOT, false)?;
+            i2c.ecriture_field(registry::MPU6050_INTEN_FF, false)?;
+            return i2c.ecriture_field(registry::MPU6050_INTEN_ZMOT, false);
+        };
+
+        i2c.ecriture_reg(registry::MPU6050_MOT_THR, detectors.motion_threshold as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_MOT_DUR, detectors.motion_duration as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_FF_THR, detectors.free_fall_threshold as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_FF_DUR, detectors.free_fall_duration as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_ZRMOT_THR, detectors.zero_motion_threshold as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_ZRMOT_DUR, detectors.zero_motion_duration as u16)?;
+
+        // Les détecteurs de mouvement travaillent sur l'accélération filtrée (passe-haut à 5 Hz)
+        i2c.ecriture_field(registry::MPU6050_ACONFIG_ACCEL_HPF, registry::MPU6050_DHPF_5)?;
+
+        i2c.ecriture_field(registry::MPU6050_INTEN_MOT, true)?;
+        i2c.ecriture_field(registry::MPU6050_INTEN_FF, true)?;
+        i2c.ecriture_field(registry::MPU6050_INTEN_ZMOT, true)
+    }
+
+    /// Traduit le statut des interruptions en événements, horodatés au dernier échantillon lu
+    fn detect_events<B: I2CBus>(&mut self, i2c: &mut B, status: u16) -> anyhow::Result<()>  {
+        let timestamp = self.timestamp.as_secs_f64();
+
+        if registry::MPU6050_INTSTATUS_MOT.decode(status) == Some(true) {
+            self.events.push(ImuEvent { kind: ImuEventKind::Impact, timestamp });
+        }
+
+        if registry::MPU6050_INTSTATUS_FF.decode(status) == Some(true) {
+            self.events.push(ImuEvent { kind: ImuEventKind::Jump, timestamp });
+        }
+
+        // L'interruption d'immobilité se déclenche à l'arrêt et à la reprise du mouvement
+        if registry::MPU6050_INTSTATUS_ZMOT.decode(status) == Some(true) {
+            let parked = i2c.lecture_field(registry::MPU6050_MOTDETECT_ZRMOT)?;
+            if parked != self.parked {
+                self.parked = parked;
+                let kind = if parked { ImuEventKind::Parked } else { ImuEventKind::Moving };
+                self.events.push(ImuEvent { kind, timestamp });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fait lire le magnétomètre par le module (bus auxiliaire) au lieu de l'hôte (bus principal)
+    /// Le magnétomètre doit déjà être configuré, en passant par le mode "Bypass".
+    pub(crate) fn set_aux_magnetometer<B: I2CBus>(&mut self, i2c: &mut B, aux_mag: Option<AuxMagnetometer>) -> anyhow::Result<()>  {
+        self.set_slave(i2c)?;
+        self.aux_mag = aux_mag;
+        self.mag = None;
+        self.set_aux_master(i2c)?;
+        self.set_fifo_enable(i2c)
+    }
+
+    /// Accède directement au magnétomètre auxiliaire (mode "Bypass") le temps de `f`
+    pub(crate) fn with_bypass<B: I2CBus, T>(&mut self, i2c: &mut B, f: impl FnOnce() -> T) -> anyhow::Result<T>  {
+        if self.aux_mag.is_none() {
+            return Ok(f());
+        }
+
+        self.set_slave(i2c)?;
+        i2c.ecriture_field(registry::MPU6050_USERCTRL_I2C_MST_EN, false)?;
+        self.set_i2c_bypass_enable(i2c, true)?;
+        let result = f();
+        self.set_slave(i2c)?;
+        self.set_aux_master(i2c)?;
+        Ok(result)
+    }
+
+    /// Maître I2C auxiliaire : SLV0 lit les mesures du magnétomètre à chaque échantillon
+    /// Sans magnétomètre auxiliaire, le bus auxiliaire est relié au bus principal (mode "Bypass").
+    fn set_aux_master<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()>  {
+        let Some(aux_mag) = self.aux_mag else {
+            i2c.ecriture_field(registry::MPU6050_USERCTRL_I2C_MST_EN, false)?;
+            return self.set_i2c_bypass_enable(i2c, true);
+        };
+
+        self.set_i2c_bypass_enable(i2c, false)?;
+
+        // 400 kHz, l'échantillon n'est disponible qu'une fois le magnétomètre lu (alignement dans le temps)
+        i2c.ecriture_field(registry::MPU6050_I2CMST_CLK, registry::MPU6050_CLOCK_DIV_400)?;
+        i2c.ecriture_field(registry::MPU6050_I2CMST_WAIT_FOR_ES, true)?;
+
+        i2c.ecriture_reg(registry::MPU6050_I2C_SLV0_ADDR, (1 << registry::MPU6050_I2C_SLV_RW_BIT | aux_mag.addr) as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_I2C_SLV0_REG, aux_mag.reg as u16)?;
+        i2c.ecriture_reg(registry::MPU6050_I2C_SLV0_CTRL, (1 << registry::MPU6050_I2C_SLV_EN_BIT | registry::MPU6050_AUX_MAG_SIZE as u8) as u16)?;
+
+        i2c.ecriture_field(registry::MPU6050_USERCTRL_I2C_MST_EN, true)
+    }
+
+    /// Taille de la FIFO en octets
+    fn fifo_size(&self) -> usize {
+        match self.chip {
+            ImuChip::Mpu6050 => registry::MPU6050_FIFO_SIZE,
+            _ => registry::MPU6500_FIFO_SIZE,
+        }
+    }
+
+    /// Taille d'un échantillon dans la FIFO (avec les mesures du magnétomètre auxiliaire)
+    fn fifo_sample_size(&self) -> usize {
+        match self.aux_mag {
+            Some(_) => registry::MPU6050_FIFO_SAMPLE_SIZE + registry::MPU6050_AUX_MAG_SIZE,
+            None => registry::MPU6050_FIFO_SAMPLE_SIZE,
         }
+    }
+
+    /// Place l'accélération, la température et la vitesse angulaire dans la FIFO à chaque échantillon
+    /// (suivies des mesures du magnétomètre auxiliaire)
+    fn set_fifo_enable<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()>  {
+        let slv0 = match self.aux_mag {
+            Some(_) => 1 << registry::MPU6050_SLV0_FIFO_EN_BIT,
+            None => 0,
+        };
+
+        i2c.ecriture_reg(registry::MPU6050_FIFO_EN, registry::MPU6050_FIFO_SOURCES | slv0)?;
+        self.reset_fifo(i2c)
+    }
 
-        i2c.ecriture_bits8(registry::MPU6050_RA_ACCEL_CONFIG, registry::MPU6050_ACONFIG_AFS_SEL_BIT, registry::MPU6050_ACONFIG_AFS_SEL_LENGTH, range)
+    /// Vide la FIFO et repart d'un échantillon aligné
+    fn reset_fifo<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_USERCTRL_FIFO_EN, false)?;
+        i2c.ecriture_field(registry::MPU6050_USERCTRL_FIFO_RESET, true)?;
+        i2c.ecriture_field(registry::MPU6050_USERCTRL_FIFO_EN, true)?;
+        self.last_drain = None;
+        Ok(())
+    }
+
+    /// Récupére tous les échantillons présents dans la FIFO (dans l'ordre d'acquisition)
+    /// Retourne `None` si la FIFO a débordé : les échantillons sont perdus, elle doit être vidée.
+    fn read_fifo<B: I2CBus>(&mut self, i2c: &mut B, status: u16) -> anyhow::Result<Option<Vec<RawSample>>>  {
+        let count = i2c.lecture_reg(registry::MPU6050_FIFO_COUNT)? as usize;
+        let overflow = registry::MPU6050_INTSTATUS_FIFO_OFLOW.decode(status) == Some(true);
+        if overflow || count >= self.fifo_size() {
+            return Ok(None);
+        }
+
+        // Un échantillon (puis le magnétomètre auxiliaire) par transaction : la FIFO se lit toujours à la même adresse
+        let sample_size = self.fifo_sample_size();
+        let mut samples = Vec::with_capacity(count / sample_size);
+        for _ in 0..count / sample_size {
+            let data = Self::fifo_words::<7, B>(i2c)?;
+            let mut sample = RawSample::from_words(data);
+            if self.aux_mag.is_some() {
+                sample.mag = Some(Self::fifo_words::<3, B>(i2c)?);
+            }
+            samples.push(sample);
+        }
+
+        Ok(Some(samples))
+    }
+
+    /// Lecture de N valeurs 16 bits signées dans la FIFO (octet de poids fort en premier), sans nouvelle tentative
+    fn fifo_words<const N: usize, B: I2CBus>(i2c: &mut B) -> anyhow::Result<[i16; N]>  {
+        let mut buffer = vec![0u8; N * 2];
+        i2c.fifo_read(registry::MPU6050_RA_FIFO_R_W, &mut buffer)?;
+
+        let mut words = [0i16; N];
+        for (word, bytes) in words.iter_mut().zip(buffer.chunks_exact(2)) {
+            *word = i16::from_be_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(words)
+    }
+
+    /// Active (ou désactive) l'auto-test sur tous les axes
+    fn set_self_test_enable<B: I2CBus>(&self, i2c: &mut B, enable: bool) -> anyhow::Result<()>  {
+        i2c.ecriture_field(registry::MPU6050_GCONFIG_XG_ST, enable)?;
+        i2c.ecriture_field(registry::MPU6050_GCONFIG_YG_ST, enable)?;
+        i2c.ecriture_field(registry::MPU6050_GCONFIG_ZG_ST, enable)?;
+        i2c.ecriture_field(registry::MPU6050_ACONFIG_XA_ST, enable)?;
+        i2c.ecriture_field(registry::MPU6050_ACONFIG_YA_ST, enable)?;
+        i2c.ecriture_field(registry::MPU6050_ACONFIG_ZA_ST, enable)
+    }
+
+    /// Auto-test du datasheet : réponse des capteurs à l'auto-test comparée aux valeurs d'usine
+    fn self_test<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<SelfTestReport>  {
+        println!("[IMU] Auto-test ...");
+
+        // Echelles imposées par le datasheet pendant le test
+        let (gyro_range, accel_range) = (self.gyro_range, self.accel_range);
+        let test_accel_range = match self.chip {
+            ImuChip::Mpu6050 => AccelRange::Fs8,
+            _ => AccelRange::Fs2,
+        };
+        self.set_fullscale_gyro_range(i2c, GyroRange::Fs250)?;
+        self.set_fullscale_accel_range(i2c, test_accel_range)?;
+
+        sleep(SELF_TEST_SETTLE);
+        let normal = self.get_sample_average(i2c, SELF_TEST_SAMPLES)?;
+
+        self.set_self_test_enable(i2c, true)?;
+        sleep(SELF_TEST_SETTLE);
+        let test = self.get_sample_average(i2c, SELF_TEST_SAMPLES);
+
+        // Toujours désactiver le test et rétablir les échelles, même en cas d'erreur de lecture
+        self.set_self_test_enable(i2c, false)?;
+        self.set_fullscale_gyro_range(i2c, gyro_range)?;
+        self.set_fullscale_accel_range(i2c, accel_range)?;
+        let test = test?;
+
+        let (gyro_response, accel_response) = (test.gyro - normal.gyro, test.accel - normal.accel);
+        if self.chip != ImuChip::Mpu6050 {
+            let [gx, gy, gz] = i2c.lecture_words::<3>(registry::MPU6500_RA_SELF_TEST_X_GYRO)?;
+            let [ax, ay, az] = i2c.lecture_words::<3>(registry::MPU6500_RA_SELF_TEST_X_ACCEL)?;
+            return Ok(SelfTestReport::mpu6500(gyro_response, accel_response, [gx, gy, gz, ax, ay, az]));
+        }
+
+        let trim = i2c.lecture_words::<4>(registry::MPU6050_RA_SELF_TEST_X)?;
+        Ok(SelfTestReport::new(gyro_response, accel_response, trim))
     }
 
     ///////////////////////////////////
-    /// GESTION DES MESURES
+    // GESTION DES MESURES
     ///////////////////////////////////
 
-    /// Calibration de l'IMU
-    fn calibration_imu(&mut self, i2c: &mut I2c) -> anyhow::Result<()>  {
+    /// Calibration de l'IMU, uniquement si la voiture reste immobile pendant les mesures
+    fn calibration_imu<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()>  {
         println!("[IMU] Calibration ...");
 
-        // Récupére ~500 mesures et fait une moyenne
-        let mut offset_gyro = Vector3::new(0.0 as f32, 0.0 as f32, 0.0 as f32);
-        let mut offset_accel = Vector3::new(0.0 as f32, 0.0 as f32, 0.0 as f32);
+        // Récupére ~500 mesures, moyenne et variance
+        let mut detector = StillnessDetector::new(500);
+        let mut calibration = None;
 
         for n in 0..500 {
-            let mesure_gyro = self.get_gyro_raw(i2c)?;
-            let mesure_accel = self.get_accel_raw(i2c)?;
-
-            offset_gyro += mesure_gyro;
-            offset_accel += mesure_accel;
+            let mesure = self.get_sample_raw(i2c)?;
+            calibration = detector.push(mesure.gyro / self.gyro_scale, mesure.accel / self.accel_scale);
+            self.temp = self.temp_from_raw(mesure.temp);
 
             sleep(Duration::from_millis(5))
         }
 
-        self.gyro_cal = offset_gyro / 500.0;
-        self.accel_cal = offset_accel / 500.0;
+        match calibration {
+            Some(calibration) => self.apply_calibration(i2c, calibration),
+            None => println!("[IMU] Voiture en mouvement, calibration ignorée."),
+        }
 
-        println!("[IMU] Calibration GYRO: (X: {} Y: {} Z: {})", self.gyro_cal.x, self.gyro_cal.y, self.gyro_cal.z);
-        println!("[IMU] Calibration ACCEL: (X: {} Y: {} Z: {})", self.accel_cal.x, self.accel_cal.y, self.accel_cal.z);
         Ok(())
     }
 
-    /// Récupére la température en °C du capteur
-    fn get_actual_temp(&self, i2c: &mut I2c) -> anyhow::Result<f32>  {
-        let temp_h = i2c.lecture_word(registry::MPU6050_RA_TEMP_OUT_H)?;
-        let temp_l = i2c.lecture_word(registry::MPU6050_RA_TEMP_OUT_L)?;
-        let temp = ((temp_h as i16) << 8) | temp_l as i16;
+    /// Applique une nouvelle calibration selon le mode choisi, puis la garde pour l'enregistrer
+    /// `residual` : décalages mesurés avec les registres de décalage actuels
+    fn apply_calibration<B: I2CBus>(&mut self, i2c: &mut B, residual: ImuCalibration) {
+        // Biais propre au capteur à la température actuelle (registres de décalage retirés)
+        self.gyro_temp.learn(self.temp, residual.gyro - self.hardware_gyro_bias());
+
+        let calibration = match self.calibration_mode {
+            CalibrationMode::Software => Ok(residual),
+            CalibrationMode::Hardware => self.calibrate_hardware_offsets(i2c, &residual),
+        };
+
+        match calibration {
+            Ok(mut calibration) => {
+                calibration.gyro_temp = Some(self.gyro_temp);
+                self.set_calibration(calibration);
+                self.new_calibration = Some(calibration);
+            }
+            Err(e) => println!("[IMU] Calibration non appliquée: {}", e),
+        }
+    }
+
+    /// Applique un nouveau biais du gyroscope (mesuré avec les registres de décalage actuels)
+    /// Le décalage de l'accéléromètre est conservé : logiciel inchangé, registres non modifiés (résidu nul).
+    fn apply_gyro_calibration<B: I2CBus>(&mut self, i2c: &mut B, gyro: Vector3<f32>) {
+        let residual = ImuCalibration {
+            gyro,
+            accel: match self.calibration_mode {
+                CalibrationMode::Software => self.accel_cal,
+                CalibrationMode::Hardware => Vector3::zeros(),
+            },
+            hardware: None,
+            gyro_temp: None,
+        };
+        self.apply_calibration(i2c, residual);
+    }
 
-        Ok((temp as f32/340.0) + 36.53)
+    /// Corrige les registres de décalage du module, la correction logicielle devient nulle
+    fn calibrate_hardware_offsets<B: I2CBus>(&mut self, i2c: &mut B, residual: &ImuCalibration) -> anyhow::Result<ImuCalibration> {
+        let offsets = self.get_hardware_offsets(i2c)?.corrected(residual);
+        self.set_hardware_offsets(i2c, offsets)?;
+
+        println!("[IMU] Décalages matériels GYRO: {:?} ACCEL: {:?}", offsets.gyro.as_slice(), offsets.accel.as_slice());
+        Ok(ImuCalibration {
+            gyro: Vector3::zeros(),
+            accel: Vector3::zeros(),
+            hardware: Some(offsets),
+            gyro_temp: None,
+        })
+    }
+
+    /// Registres de décalage de l'accéléromètre (X, Y, Z), contigus sur le MPU6050 seulement
+    fn accel_offset_registers(&self) -> [u8; 3] {
+        match self.chip {
+            ImuChip::Mpu6050 => [registry::MPU6050_RA_XA_OFFS_H, registry::MPU6050_RA_YA_OFFS_H, registry::MPU6050_RA_ZA_OFFS_H],
+            _ => [registry::MPU6500_RA_XA_OFFSET_H, registry::MPU6500_RA_YA_OFFSET_H, registry::MPU6500_RA_ZA_OFFSET_H],
+        }
     }
 
-    /// Récupére l'accélération en X (RAW)
-    fn get_accel_x(&self, i2c: &mut I2c) -> anyhow::Result<i16>  {
-        let accel_x_h = i2c.lecture_word(registry::MPU6050_RA_ACCEL_XOUT_H)?;
-        let accel_x_l = i2c.lecture_word(registry::MPU6050_RA_ACCEL_XOUT_L)?;
-        Ok(((accel_x_h as i16) << 8) | accel_x_l as i16)
+    /// Récupére le contenu des registres de décalage
+    fn get_hardware_offsets<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<ImuHardwareOffsets> {
+        let [gx, gy, gz] = i2c.lecture_i16_be::<3>(registry::MPU6050_RA_XG_OFFS_USRH)?;
+
+        let mut accel = Vector3::zeros();
+        for (value, register) in accel.iter_mut().zip(self.accel_offset_registers()) {
+            [*value] = i2c.lecture_i16_be::<1>(register)?;
+        }
+
+        Ok(ImuHardwareOffsets {
+            gyro: Vector3::new(gx, gy, gz),
+            accel,
+        })
     }
 
-    /// Récupére l'accélération en Y (RAW)
-    fn get_accel_y(&self, i2c: &mut I2c) -> anyhow::Result<i16>  {
-        let accel_y_h = i2c.lecture_word(registry::MPU6050_RA_ACCEL_YOUT_H)?;
-        let accel_y_l = i2c.lecture_word(registry::MPU6050_RA_ACCEL_YOUT_L)?;
-        Ok(((accel_y_h as i16) << 8) | accel_y_l as i16)
+    /// Ecrit les registres de décalage et vérifie leur contenu par relecture
+    fn set_hardware_offsets<B: I2CBus>(&mut self, i2c: &mut B, offsets: ImuHardwareOffsets) -> anyhow::Result<()> {
+        i2c.ecriture_i16_be(registry::MPU6050_RA_XG_OFFS_USRH, [offsets.gyro.x, offsets.gyro.y, offsets.gyro.z])?;
+        for (register, offset) in self.accel_offset_registers().into_iter().zip(offsets.accel.iter()) {
+            i2c.ecriture_i16_be(register, [*offset])?;
+        }
+
+        let readback = self.get_hardware_offsets(i2c)?;
+        if readback != offsets {
+            return Err(anyhow!("[IMU] Décalages matériels relus différents ({:?})", readback));
+        }
+
+        self.hardware_offsets = Some(offsets);
+        Ok(())
     }
 
-    /// Récupére l'accélération en Z (RAW)
-    fn get_accel_z(&self, i2c: &mut I2c) -> anyhow::Result<i16>  {
-        let accel_z_h = i2c.lecture_word(registry::MPU6050_RA_ACCEL_ZOUT_H)?;
-        let accel_z_l = i2c.lecture_word(registry::MPU6050_RA_ACCEL_ZOUT_L)?;
-        Ok(((accel_z_h as i16) << 8) | accel_z_l as i16)
+    /// Applique une calibration (décalages en °/s et en g)
+    fn set_calibration(&mut self, calibration: ImuCalibration) {
+        self.gyro_cal = calibration.gyro;
+        self.accel_cal = calibration.accel;
+        if let Some(gyro_temp) = calibration.gyro_temp {
+            self.gyro_temp = gyro_temp;
+        }
+
+        println!("[IMU] Calibration GYRO: (X: {} Y: {} Z: {})", self.gyro_cal.x, self.gyro_cal.y, self.gyro_cal.z);
+        println!("[IMU] Calibration ACCEL: (X: {} Y: {} Z: {})", self.accel_cal.x, self.accel_cal.y, self.accel_cal.z);
     }
 
-    /// Récupére la vitesse angulaire en X (RAW)
-    fn get_gyro_x(&self, i2c: &mut I2c) -> anyhow::Result<i16>  {
-        let gyro_x_h = i2c.lecture_word(registry::MPU6050_RA_GYRO_XOUT_H)?;
-        let gyro_x_l = i2c.lecture_word(registry::MPU6050_RA_GYRO_XOUT_L)?;
-        Ok(((gyro_x_h as i16) << 8) | gyro_x_l as i16)
+    /// Décalage du gyroscope déjà corrigé par les registres du module (en °/s)
+    fn hardware_gyro_bias(&self) -> Vector3<f32> {
+        self.hardware_offsets.map_or(Vector3::zeros(), |offsets| offsets.gyro_bias())
     }
 
-    /// Récupére la vitesse angulaire en Y (RAW)
-    fn get_gyro_y(&self, i2c: &mut I2c) -> anyhow::Result<i16>  {
-        let gyro_y_h = i2c.lecture_word(registry::MPU6050_RA_GYRO_YOUT_H)?;
-        let gyro_y_l = i2c.lecture_word(registry::MPU6050_RA_GYRO_YOUT_L)?;
-        Ok(((gyro_y_h as i16) << 8) | gyro_y_l as i16)
+    /// Décalage du gyroscope à soustraire à cette température (en °/s)
+    /// Sans modèle de température, celui de la dernière calibration.
+    fn gyro_offset(&self, temp: f32) -> Vector3<f32> {
+        match self.gyro_temp.bias(temp, self.gyro_temp_comp) {
+            Some(bias) => bias + self.hardware_gyro_bias(),
+            None => self.gyro_cal,
+        }
     }
 
-    /// Récupére la vitesse angulaire en Z (RAW)
-    fn get_gyro_z(&self, i2c: &mut I2c) -> anyhow::Result<i16>  {
-        let gyro_z_h = i2c.lecture_word(registry::MPU6050_RA_GYRO_ZOUT_H)?;
-        let gyro_z_l = i2c.lecture_word(registry::MPU6050_RA_GYRO_ZOUT_L)?;
-        Ok(((gyro_z_h as i16) << 8) | gyro_z_l as i16)
+    /// Converti une température RAW en °C
+    fn temp_from_raw(&self, raw: i16) -> f32 {
+        match self.chip {
+            ImuChip::Mpu6050 => (raw as f32 / 340.0) + 36.53,
+            _ => (raw as f32 / 333.87) + 21.0,
+        }
     }
 
-    /// Récupére l'accélération dans un vecteur (RAW)
-    fn get_accel_raw(&self, i2c: &mut I2c) -> anyhow::Result<Vector3<f32>>  {
-        let accel_x = self.get_accel_x(i2c)? as f32;
-        let accel_y = self.get_accel_y(i2c)? as f32;
-        let accel_z = self.get_accel_z(i2c)? as f32;
+    /// Récupére la température en °C du capteur
+    fn get_actual_temp<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<f32>  {
+        let [temp] = i2c.lecture_i16_be::<1>(registry::MPU6050_RA_TEMP_OUT_H)?;
+        Ok(self.temp_from_raw(temp))
+    }
 
-        Ok(Vector3::new(accel_x, accel_y, accel_z))
+    /// Récupére l'accélération, la température et la vitesse angulaire (RAW) en une seule lecture
+    fn get_sample_raw<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<RawSample>  {
+        // ACCEL_XOUT_H .. GYRO_ZOUT_L : 7 valeurs consécutives de 16 bits
+        let data = i2c.lecture_i16_be::<7>(registry::MPU6050_RA_ACCEL_XOUT_H)?;
+        Ok(RawSample::from_words(data))
     }
 
-    /// Récupére la vitesse angulaire dans un vecteur (RAW)
-    fn get_gyro_raw(&self, i2c: &mut I2c) -> anyhow::Result<Vector3<f32>>   {
-        let gyro_x = self.get_gyro_x(i2c)? as f32;
-        let gyro_y = self.get_gyro_y(i2c)? as f32;
-        let gyro_z: f32 = self.get_gyro_z(i2c)? as f32;
+    /// Moyenne de plusieurs échantillons bruts, espacés d'une période d'échantillonnage
+    fn get_sample_average<B: I2CBus>(&self, i2c: &mut B, count: u32) -> anyhow::Result<RawSample>  {
+        let mut accel = Vector3::zeros();
+        let mut gyro = Vector3::zeros();
+        let mut temp = 0;
+
+        for _ in 0..count {
+            let sample = self.get_sample_raw(i2c)?;
+            accel += sample.accel;
+            gyro += sample.gyro;
+            temp = sample.temp;
+
+            sleep(self.sample_period);
+        }
+
+        Ok(RawSample {
+            accel: accel / count as f32,
+            temp,
+            gyro: gyro / count as f32,
+            mag: None,
+        })
+    }
 
-        Ok(Vector3::new(gyro_x, gyro_y, gyro_z))
+    /// Récupére l'accélération dans un vecteur (RAW)
+    fn get_accel_raw<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<Vector3<f32>>  {
+        let [x, y, z] = i2c.lecture_i16_be::<3>(registry::MPU6050_RA_ACCEL_XOUT_H)?;
+        Ok(Vector3::new(x as f32, y as f32, z as f32))
+    }
+
+    /// Récupére la vitesse angulaire dans un vecteur (RAW)
+    fn get_gyro_raw<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<Vector3<f32>>   {
+        let [x, y, z] = i2c.lecture_i16_be::<3>(registry::MPU6050_RA_GYRO_XOUT_H)?;
+        Ok(Vector3::new(x as f32, y as f32, z as f32))
     }
 
     /// Récupére l'accélération dans un vecteur
-    fn get_accel(&self, i2c: &mut I2c) -> anyhow::Result<Vector3<f32>>  {
+    fn get_accel<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<Vector3<f32>>  {
         let mut accel_measurement = self.get_accel_raw(i2c)?;
-        Ok(accel_measurement / self.accel_scale)
+        Ok(accel_measurement / self.accel_scale - self.accel_cal)
     }
 
     /// Récupére la vitesse angulaire dans un vecteur
-    fn get_gyro(&self, i2c: &mut I2c) -> anyhow::Result<Vector3<f32>>  {
-        let mut gyro_measurement = self.get_gyro_raw(i2c)? - self.gyro_cal;
-        Ok(gyro_measurement / self.gyro_scale)
+    fn get_gyro<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<Vector3<f32>>  {
+        let mut gyro_measurement = self.get_gyro_raw(i2c)?;
+        Ok(gyro_measurement / self.gyro_scale - self.gyro_cal)
+    }
+
+    /// Lis tous les échantillons en attente dans la FIFO et mets à jour les valeurs de l'IMU
+    /// `mag` : champ magnétique corrigé, dans le repère de la voiture (sans mesure, le cap n'est pas corrigé)
+    /// Retourne le nombre d'échantillons traités.
+    pub(crate) fn update<B: I2CBus>(&mut self, i2c: &mut B, mag: MagInput) -> anyhow::Result<usize>  {
+        self.set_slave(i2c)?;
+
+        // Une seule lecture du statut : elle efface toutes les interruptions
+        let status = i2c.lecture_reg(registry::MPU6050_INT_STATUS)?;
+        self.detect_events(i2c, status)?;
+
+        let samples = match self.read_fifo(i2c, status) {
+            Ok(Some(samples)) => samples,
+            result => {
+                // Echantillons perdus : l'horloge avance du temps écoulé depuis la dernière lecture
+                if let Some(last_drain) = self.last_drain {
+                    self.timestamp += last_drain.elapsed();
+                }
+                self.still.clear();
+
+                // FIFO pleine ou lecture interrompue au milieu d'un échantillon : elle est vidée pour repartir alignée
+                let reset = self.reset_fifo(i2c);
+                return match result {
+                    Err(e) => {
+                        if let Err(reset) = reset {
+                            println!("[IMU] FIFO non réinitialisée: {}", reset);
+                        }
+                        Err(e)
+                    }
+                    Ok(_) => {
+                        println!("[IMU] FIFO pleine, échantillons perdus.");
+                        reset.map(|_| 0)
+                    }
+                };
+            }
+        };
+
+        if !samples.is_empty() {
+            self.last_drain = Some(Instant::now());
+        }
+
+        // Chaque échantillon est espacé d'exactement une période
+        let dt = self.sample_period.as_secs_f32();
+        let mut gyro_bias = None;
+        for sample in &samples {
+            self.raw_accel = sample.accel.map(|v| v as i16);
+            self.raw_gyro = sample.gyro.map(|v| v as i16);
+            let accel = sample.accel / self.accel_scale;
+            let gyro = sample.gyro / self.gyro_scale;
+
+            // Voiture immobile : nouveau biais du gyroscope, appliqué après ce lot d'échantillons
+            if let Some(bias) = self.still.push_refresh(gyro, accel) {
+                gyro_bias = Some(bias);
+            }
+
+            // Calibration dans le repère du capteur (biais du gyroscope selon la température), puis passage dans celui de la voiture
+            self.temp = self.temp_from_raw(sample.temp);
+            self.accel = self.orientation * (accel - self.accel_cal);
+            self.gyro = self.orientation * (gyro - self.gyro_offset(self.temp));
+
+            // Magnétomètre auxiliaire : mesure propre à chaque échantillon
+            let field = match (&mag, sample.mag) {
+                (MagInput::Field(field), _) => *field,
+                (MagInput::Aux(calibrate), Some(words)) => calibrate(words),
+                (MagInput::Aux(_), None) => None,
+            };
+            if sample.mag.is_some() {
+                self.mag = sample.mag;
+            }
+
+            // Fusion gyroscope (en rad/s), accéléromètre et magnétomètre
+            self.ahrs.update(self.gyro.map(|v| v.to_radians()), self.accel, field, dt);
+            self.timestamp += self.sample_period;
+        }
+
+        if let Some(bias) = gyro_bias {
+            println!("[IMU] Voiture immobile, nouvelle calibration du gyroscope.");
+            self.apply_gyro_calibration(i2c, bias);
+        }
+
+        Ok(samples.len())
+    }
+}
+
+impl ImuDriver for IMU {
+    fn chip(&self) -> ImuChip {
+        self.chip
+    }
+
+    fn update(&mut self, mut i2c: &mut dyn I2CBus, mag: MagInput) -> anyhow::Result<usize> {
+        IMU::update(self, &mut i2c, mag)
+    }
+
+    fn check_and_recover(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<bool> {
+        IMU::check_and_recover(self, &mut i2c)
+    }
+
+    fn set_aux_magnetometer(&mut self, mut i2c: &mut dyn I2CBus, aux_mag: Option<AuxMagnetometer>) -> anyhow::Result<()> {
+        IMU::set_aux_magnetometer(self, &mut i2c, aux_mag)
+    }
+
+    fn with_bypass(&mut self, mut i2c: &mut dyn I2CBus, f: &mut dyn FnMut() -> anyhow::Result<bool>) -> anyhow::Result<bool> {
+        IMU::with_bypass(self, &mut i2c, f)?
+    }
+
+    /// Récupére les événements détectés depuis le dernier appel
+    fn take_events(&mut self) -> Vec<ImuEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Vrai si le module ne détecte plus aucun mouvement
+    fn is_parked(&self) -> bool {
+        self.parked
+    }
+
+    /// Récupére le résultat de l'auto-test du démarrage
+    fn get_self_test(&self) -> Option<SelfTestReport> {
+        self.self_test
+    }
+
+    /// Récupére la calibration obtenue depuis le dernier appel, à enregistrer dans la configuration
+    fn take_new_calibration(&mut self) -> Option<ImuCalibration> {
+        self.new_calibration.take()
+    }
+
+    fn set_mag_decl(&mut self, mag_decl: f32) {
+        self.ahrs.set_mag_decl(mag_decl);
+    }
+
+    /// Récupére l'orientation (tangage, roulis, cap) en degrés, calculée par l'AHRS
+    fn get_angles(&self) -> Vector3<f32> {
+        let euler = self.ahrs.euler();
+
+        // Orientation du capteur déjà corrigée (`imu_orientation`)
+        Vector3::new(euler.y, euler.x, euler.z)
+    }
+
+    /// Récupére l'orientation sous forme de quaternion (w, x, y, z)
+    fn get_quaternion(&self) -> (f32, f32, f32, f32) {
+        let q = self.ahrs.quaternion();
+        (q.w, q.i, q.j, q.k)
     }
 
-    /// Récupére un angle d'euler à partir d'un filtre complémentaire, du gyroscope et de l'accélération
-    pub(crate) fn get_angles(&self) -> Vector3<f32> {
-        self.angles * -1.0 // -1.0 car j'ai monté le capteur à l'envers :)
+    /// Récupére l'accélération (en g) du dernier échantillon
+    fn get_acceleration(&self) -> Vector3<f32> {
+        self.accel
+    }
+
+    /// Récupére la vitesse angulaire calibrée (en °/s) du dernier échantillon
+    fn get_angular_rate(&self) -> Vector3<f32> {
+        self.gyro
+    }
+
+    /// Récupére l'accélération et la vitesse angulaire brutes (LSB) du dernier échantillon
+    fn get_raw(&self) -> (Vector3<i16>, Vector3<i16>) {
+        (self.raw_accel, self.raw_gyro)
+    }
+
+    /// Récupére les registres du magnétomètre auxiliaire (dans l'ordre de lecture) du dernier échantillon
+    fn get_aux_mag(&self) -> Option<[i16; 3]> {
+        self.mag
     }
 
     /// Récupére la température enregistrer depuis la dernière update
-    pub(crate) fn get_temp(&self) -> f32 {
+    fn get_temp(&self) -> f32 {
         self.temp
     }
 
-    /// Lis et mets à jour les valeurs de l'IMU
-    pub(crate) fn update(&mut self, i2c: &mut I2c) -> anyhow::Result<()>  {
-        self.set_slave(i2c)?;
+    /// Instant du dernier échantillon, compté par l'horloge d'échantillonnage de l'IMU
+    fn get_timestamp(&self) -> Duration {
+        self.timestamp
+    }
 
-        let acceleration = self.get_accel(i2c)?;
-        let gyroscope = self.get_gyro(i2c)?;
+    /// Période entre deux échantillons
+    fn get_sample_period(&self) -> Duration {
+        self.sample_period
+    }
+}
 
-        // Récupére la température
-        self.temp = self.get_actual_temp(i2c)?;
-        
-        // Défini le temps de la 1er mesure, évite un drift des le début
-        if self.last_measurment.is_none() {
-            self.last_measurment = Some(Instant::now());
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice, SimResponse};
 
-        // Récupére le temps écoulé depuis la dernière mesure en secondes
-        let elapsed_time = self.last_measurment.unwrap().elapsed();
+    #[test]
+    fn accel_gyro_and_temp_decoding() {
+        let mut bus = SimBus::new();
+        bus.attach(registry::IMU_ADDR, SimDevice::new());
+        let mut imu = IMU::new(&mut bus, &Config::new(), ImuChip::Mpu6050).unwrap();
 
-        // Remet le compteur à 0
-        self.last_measurment = Some(Instant::now());
+        // ACCEL_XOUT_H .. GYRO_ZOUT_L : accélération, température puis gyroscope (octet de poids fort en premier)
+        let data: Vec<u8> = [0i16, -8192, 16384, 340, 131, -262, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+        bus.device(registry::IMU_ADDR).unwrap().set_registers(registry::MPU6050_RA_ACCEL_XOUT_H, &data);
 
-        // Calcul d'angle en degrée via l'accéléromètre (2D)
-        let accel_pitch  = (acceleration.y.atan2(acceleration.z)).to_degrees(); // Pitch (SUR)
-        let accel_roll = (acceleration.x.atan2(acceleration.z)).to_degrees(); // Roll (SUR)
+        assert_eq!(imu.get_accel(&mut bus).unwrap(), Vector3::new(0.0, -0.5, 1.0));
+        assert_eq!(imu.get_gyro(&mut bus).unwrap(), Vector3::new(1.0, -2.0, 0.0));
+        assert!((imu.get_actual_temp(&mut bus).unwrap() - 37.53).abs() < 1e-3);
+    }
+
+    #[test]
+    fn fifo_samples_at_configured_rate() {
+        let mut bus = SimBus::new();
+        bus.attach(registry::IMU_ADDR, SimDevice::new());
+        let mut imu = IMU::new(&mut bus, &Config::new(), ImuChip::Mpu6050).unwrap();
 
-        // Calcul d'angle en degrée via le gyroscope (3D)
-        let gyroscope_pitch = self.angles.x + (gyroscope.x * elapsed_time.as_secs_f32()); // Pitch (SUR)
-        let gyroscope_roll = self.angles.y - (gyroscope.y * elapsed_time.as_secs_f32()); // Roll (SUR)
-        let gyroscope_yaw = self.angles.z + (gyroscope.z * elapsed_time.as_secs_f32());
+        // 200 Hz : 1 kHz (filtre passe-bas à 42 Hz) / 5
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_CONFIG, 1), &[DlpfBandwidth::Bw42 as u8]);
+        assert_eq!(device.registers(registry::MPU6050_RA_SMPLRT_DIV, 1), &[4]);
+        assert_eq!(imu.get_sample_period(), Duration::from_millis(5));
 
-        // Calcul dynamique du filtre via la vitesse
-        let mut gyro_mult: f32 = 1.0;
-        if (self.speed <= 10.0) {
-            gyro_mult = 0.80 + (0.18 * ( 1.0 - (self.speed as f32 / 10.0)));
+        // Deux échantillons en attente dans la FIFO
+        let count = (2 * registry::MPU6050_FIFO_SAMPLE_SIZE) as u16;
+        device.set_registers(registry::MPU6050_RA_FIFO_COUNTH, &count.to_be_bytes());
+        for temp in [0i16, 340] {
+            let data: Vec<u8> = [0i16, 0, 16384, temp, 0, 0, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+            device.script_read(registry::MPU6050_RA_FIFO_R_W, SimResponse::Data(data));
         }
 
-        let accel_mult = 1.0 - gyro_mult;
+        assert_eq!(imu.update(&mut bus, MagInput::Field(None)).unwrap(), 2);
+        assert_eq!(imu.get_timestamp(), Duration::from_millis(10));
+        assert_eq!(imu.get_acceleration(), Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(imu.get_raw().0, Vector3::new(0, 0, 16384));
+        assert!((imu.get_temp() - 37.53).abs() < 1e-3);
 
-        // Filte complémentaire
-        self.angles.x = (gyro_mult * gyroscope_pitch) + (accel_mult * accel_pitch); // Pitch
-        self.angles.y = (gyro_mult * gyroscope_roll) + (accel_mult * accel_roll); // Roll
-        self.angles.z = gyroscope_yaw; // Très imprécis, utiliser le magnétomètre
+        // Débordement : la FIFO est vidée, aucun échantillon n'est traité
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        device.set_registers(registry::MPU6050_RA_INT_STATUS, &[1 << registry::MPU6050_INTERRUPT_FIFO_OFLOW_BIT]);
+        assert_eq!(imu.update(&mut bus, MagInput::Field(None)).unwrap(), 0);
 
-        Ok(())
+        // Perte d'alimentation : valeurs de démarrage (veille, pas de filtre, FIFO inactive)
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        device.set_registers(registry::MPU6050_RA_CONFIG, &[0]);
+        device.set_registers(registry::MPU6050_RA_SMPLRT_DIV, &[0]);
+        device.set_registers(registry::MPU6050_RA_USER_CTRL, &[0]);
+        device.set_registers(registry::MPU6050_RA_PWR_MGMT_1, &[0x40]);
+        assert!(imu.check_and_recover(&mut bus).unwrap());
+
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_CONFIG, 1), &[DlpfBandwidth::Bw42 as u8]);
+        assert_eq!(device.registers(registry::MPU6050_RA_SMPLRT_DIV, 1), &[4]);
+        assert!(!imu.check_and_recover(&mut bus).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn interrupted_fifo_read_realigned() {
+        use crate::i2c::manager::{I2CManager, RetryPolicy};
+
+        // Deux échantillons en attente, la lecture du premier échoue puis celle du suivant réussirait
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        let count = (2 * registry::MPU6050_FIFO_SAMPLE_SIZE) as u16;
+        device.set_registers(registry::MPU6050_RA_FIFO_COUNTH, &count.to_be_bytes());
+        device.script_read(registry::MPU6050_RA_FIFO_R_W, SimResponse::Nack);
+        let data: Vec<u8> = [0i16, 0, 16384, 0, 0, 0, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+        device.script_read(registry::MPU6050_RA_FIFO_R_W, SimResponse::Data(data));
+        bus.attach(registry::IMU_ADDR, device);
+
+        let manager = I2CManager::with_retry(bus, RetryPolicy { retries: 2, delay: Duration::ZERO });
+        let mut i2c = manager.device(registry::IMU_ADDR);
+        let mut imu = IMU::new(&mut i2c, &Config::new(), ImuChip::Mpu6050).unwrap();
+        i2c.ecriture_field(registry::MPU6050_USERCTRL_FIFO_RESET, false).unwrap();
+
+        // La lecture de la FIFO n'est pas retentée (octets consommés), la FIFO est vidée
+        assert!(imu.update(&mut i2c, MagInput::Field(None)).is_err());
+        assert!(i2c.lecture_field(registry::MPU6050_USERCTRL_FIFO_RESET).unwrap());
+    }
+
+    #[test]
+    fn config_settings_reach_registers() {
+        let mut bus = SimBus::new();
+        bus.attach(registry::IMU_ADDR, SimDevice::new());
+        let config = Config {
+            imu_dlpf: DlpfBandwidth::Bw98,
+            imu_gyro_range: GyroRange::Fs1000,
+            imu_accel_range: AccelRange::Fs8,
+            imu_rate_hz: 100,
+            ..Config::new()
+        };
+        let mut imu = IMU::new(&mut bus, &config, ImuChip::Mpu6050).unwrap();
+
+        // 100 Hz : 1 kHz / 10
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_CONFIG, 1), &[DlpfBandwidth::Bw98 as u8]);
+        assert_eq!(device.registers(registry::MPU6050_RA_SMPLRT_DIV, 1), &[9]);
+        assert_eq!(bus.lecture_field(registry::MPU6050_GCONFIG_FS_SEL).unwrap(), GyroRange::Fs1000);
+        assert_eq!(bus.lecture_field(registry::MPU6050_ACONFIG_AFS_SEL).unwrap(), AccelRange::Fs8);
+        assert_eq!(imu.get_sample_period(), Duration::from_millis(10));
+
+        // 4096 LSB/g et 32.8 LSB/(°/s)
+        let data: Vec<u8> = [0i16, 0, 4096, 0, 328, 0, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+        bus.device(registry::IMU_ADDR).unwrap().set_registers(registry::MPU6050_RA_ACCEL_XOUT_H, &data);
+        assert_eq!(imu.get_accel(&mut bus).unwrap(), Vector3::new(0.0, 0.0, 1.0));
+        assert!((imu.get_gyro(&mut bus).unwrap().x - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hardware_calibration_written_and_reapplied() {
+        let mut bus = SimBus::new();
+        bus.attach(registry::IMU_ADDR, SimDevice::new());
+        let mut imu = IMU::new(&mut bus, &Config::new(), ImuChip::Mpu6050).unwrap();
+
+        let residual = ImuCalibration { gyro: Vector3::new(1.0, 0.0, 0.0), accel: Vector3::new(0.0, 0.0, 0.5), hardware: None, gyro_temp: None };
+        imu.apply_calibration(&mut bus, residual);
+
+        let calibration = imu.take_new_calibration().unwrap();
+        assert_eq!(calibration.gyro, Vector3::zeros());
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_XG_OFFS_USRH, 2), &(-33i16).to_be_bytes());
+        assert_eq!(device.registers(registry::MPU6050_RA_ZA_OFFS_H, 2), &(-1024i16).to_be_bytes());
+
+        // Perte d'alimentation : les registres sont réécrits à la réinitialisation
+        device.set_registers(registry::MPU6050_RA_XG_OFFS_USRH, &[0, 0]);
+        assert!(imu.check_and_recover(&mut bus).unwrap());
+        assert_eq!(imu.get_hardware_offsets(&mut bus).unwrap(), calibration.hardware.unwrap());
+
+        // Rafraîchissement à l'arrêt : seuls les registres du gyroscope changent
+        imu.apply_gyro_calibration(&mut bus, Vector3::new(0.0, 1.0, 0.0));
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_YG_OFFS_USRH, 2), &(-33i16).to_be_bytes());
+        assert_eq!(device.registers(registry::MPU6050_RA_ZA_OFFS_H, 2), &(-1024i16).to_be_bytes());
+    }
+
+    #[test]
+    fn motion_events_from_interrupt_status() {
+        let mut bus = SimBus::new();
+        bus.attach(registry::IMU_ADDR, SimDevice::new());
+        let mut imu = IMU::new(&mut bus, &Config::new(), ImuChip::Mpu6050).unwrap();
+
+        // Seuils à 2 mg par LSB, durée d'immobilité à 64 ms par LSB
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_MOT_THR, 1), &[250]);
+        assert_eq!(device.registers(registry::MPU6050_RA_FF_THR, 1), &[150]);
+        assert_eq!(device.registers(registry::MPU6050_RA_ZRMOT_THR, 1), &[10]);
+        assert_eq!(device.registers(registry::MPU6050_RA_ZRMOT_DUR, 1), &[10]);
+
+        // Choc et arrêt dans le même statut
+        let status = (1 << registry::MPU6050_INTERRUPT_MOT_BIT) | (1 << registry::MPU6050_INTERRUPT_ZMOT_BIT);
+        device.set_registers(registry::MPU6050_RA_INT_STATUS, &[status]);
+        device.set_registers(registry::MPU6050_RA_MOT_DETECT_STATUS, &[1]);
+        imu.update(&mut bus, MagInput::Field(None)).unwrap();
+
+        let kinds: Vec<ImuEventKind> = imu.take_events().iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, vec![ImuEventKind::Impact, ImuEventKind::Parked]);
+        assert!(imu.is_parked());
+
+        // Reprise du mouvement
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        device.set_registers(registry::MPU6050_RA_INT_STATUS, &[1 << registry::MPU6050_INTERRUPT_ZMOT_BIT]);
+        device.set_registers(registry::MPU6050_RA_MOT_DETECT_STATUS, &[0]);
+        imu.update(&mut bus, MagInput::Field(None)).unwrap();
+
+        let kinds: Vec<ImuEventKind> = imu.take_events().iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, vec![ImuEventKind::Moving]);
+        assert!(imu.take_events().is_empty());
+    }
+
+    #[test]
+    fn aux_magnetometer_read_through_fifo() {
+        let mut bus = SimBus::new();
+        bus.attach(registry::IMU_ADDR, SimDevice::new());
+        let mut imu = IMU::new(&mut bus, &Config::new(), ImuChip::Mpu6050).unwrap();
+
+        let aux_mag = AuxMagnetometer { addr: 0x1E, reg: 0x03 };
+        imu.set_aux_magnetometer(&mut bus, Some(aux_mag)).unwrap();
+
+        // SLV0 : lecture de 6 octets à partir de 0x03, bypass désactivé, mesures placées dans la FIFO
+        let device = bus.device(registry::IMU_ADDR).unwrap();
+        assert_eq!(device.registers(registry::MPU6050_RA_I2C_SLV0_ADDR, 3), &[0x9E, 0x03, 0x86]);
+        assert_eq!(device.registers(registry::MPU6050_RA_INT_PIN_CFG, 1)[0] & 0x02, 0);
+        assert_eq!(device.registers(registry::MPU6050_RA_USER_CTRL, 1)[0] & 0x20, 0x20);
+        assert_eq!(device.registers(registry::MPU6050_RA_FIFO_EN, 1)[0] & 0x01, 0x01);
+
+        // Deux échantillons de 20 octets, chacun avec sa mesure du magnétomètre
+        let count = (2 * (registry::MPU6050_FIFO_SAMPLE_SIZE + registry::MPU6050_AUX_MAG_SIZE)) as u16;
+        device.set_registers(registry::MPU6050_RA_FIFO_COUNTH, &count.to_be_bytes());
+        for mag in [[100i16, 300, -200], [110, 310, -210]] {
+            let data: Vec<u8> = [0i16, 0, 16384, 0, 0, 0, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+            device.script_read(registry::MPU6050_RA_FIFO_R_W, SimResponse::Data(data));
+            let data: Vec<u8> = mag.iter().flat_map(|v| v.to_be_bytes()).collect();
+            device.script_read(registry::MPU6050_RA_FIFO_R_W, SimResponse::Data(data));
+        }
+
+        let calibrate = |words: [i16; 3]| Some(Vector3::new(words[0] as f32, words[2] as f32, words[1] as f32));
+        assert_eq!(imu.update(&mut bus, MagInput::Aux(&calibrate)).unwrap(), 2);
+        assert_eq!(imu.get_aux_mag(), Some([110, 310, -210]));
+        assert!(imu.is_configured(&mut bus).unwrap());
+    }
+}
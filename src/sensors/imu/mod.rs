@@ -1,4 +1,18 @@
 #[cfg(feature = "real-sensors")]
 mod registry;
 #[cfg(feature = "real-sensors")]
-pub mod imu;
\ No newline at end of file
+pub mod imu;
+#[cfg(feature = "real-sensors")]
+pub mod data_ready;
+pub mod calibration;
+pub mod settings;
+pub mod self_test;
+pub mod events;
+#[cfg(feature = "real-sensors")]
+pub mod driver;
+#[cfg(feature = "real-sensors")]
+pub mod icm20948;
+#[cfg(feature = "real-sensors")]
+pub mod bno055;
+
+pub(crate) use settings::{AccelRange, DlpfBandwidth, GyroRange, ImuChip};
@@ -1,5 +1,8 @@
 #![allow(unused)]
 
+use crate::i2c::field::{field_enum, Field, Register, RO, RW};
+pub use crate::sensors::imu::settings::{AccelRange, DlpfBandwidth, GyroRange};
+
 pub const IMU_ADDR: u16 = 0x68;
 
 pub const MPU6050_RA_XG_OFFS_TC: u8 = 0x00;
@@ -117,6 +120,90 @@ pub const MPU6050_RA_FIFO_COUNTL: u8 = 0x73;
 pub const MPU6050_RA_FIFO_R_W: u8 = 0x74;
 pub const MPU6050_RA_WHO_AM_I: u8 = 0x75;
 
+// Registres et champs typés
+pub const MPU6050_SMPLRT_DIV: Register<RW> = Register::r8(MPU6050_RA_SMPLRT_DIV);
+pub const MPU6050_CONFIG: Register<RW> = Register::r8(MPU6050_RA_CONFIG);
+pub const MPU6050_GYRO_CONFIG: Register<RW> = Register::r8(MPU6050_RA_GYRO_CONFIG);
+pub const MPU6050_ACCEL_CONFIG: Register<RW> = Register::r8(MPU6050_RA_ACCEL_CONFIG);
+pub const MPU6050_FF_THR: Register<RW> = Register::r8(MPU6050_RA_FF_THR);
+pub const MPU6050_FF_DUR: Register<RW> = Register::r8(MPU6050_RA_FF_DUR);
+pub const MPU6050_MOT_THR: Register<RW> = Register::r8(MPU6050_RA_MOT_THR);
+pub const MPU6050_MOT_DUR: Register<RW> = Register::r8(MPU6050_RA_MOT_DUR);
+pub const MPU6050_ZRMOT_THR: Register<RW> = Register::r8(MPU6050_RA_ZRMOT_THR);
+pub const MPU6050_ZRMOT_DUR: Register<RW> = Register::r8(MPU6050_RA_ZRMOT_DUR);
+pub const MPU6050_FIFO_EN: Register<RW> = Register::r8(MPU6050_RA_FIFO_EN);
+pub const MPU6050_I2C_MST_CTRL: Register<RW> = Register::r8(MPU6050_RA_I2C_MST_CTRL);
+pub const MPU6050_I2C_SLV0_ADDR: Register<RW> = Register::r8(MPU6050_RA_I2C_SLV0_ADDR);
+pub const MPU6050_I2C_SLV0_REG: Register<RW> = Register::r8(MPU6050_RA_I2C_SLV0_REG);
+pub const MPU6050_I2C_SLV0_CTRL: Register<RW> = Register::r8(MPU6050_RA_I2C_SLV0_CTRL);
+pub const MPU6050_INT_PIN_CFG: Register<RW> = Register::r8(MPU6050_RA_INT_PIN_CFG);
+pub const MPU6050_INT_ENABLE: Register<RW> = Register::r8(MPU6050_RA_INT_ENABLE);
+pub const MPU6050_INT_STATUS: Register<RO> = Register::r8(MPU6050_RA_INT_STATUS);
+pub const MPU6050_MOT_DETECT_STATUS: Register<RO> = Register::r8(MPU6050_RA_MOT_DETECT_STATUS);
+pub const MPU6050_USER_CTRL: Register<RW> = Register::r8(MPU6050_RA_USER_CTRL);
+pub const MPU6050_PWR_MGMT_1: Register<RW> = Register::r8(MPU6050_RA_PWR_MGMT_1);
+pub const MPU6050_FIFO_COUNT: Register<RO> = Register::r16(MPU6050_RA_FIFO_COUNTH);
+pub const MPU6050_WHO_AM_I: Register<RO> = Register::r8(MPU6050_RA_WHO_AM_I);
+
+pub const MPU6050_GCONFIG_XG_ST: Field<RW, bool> = Field::bit(MPU6050_GYRO_CONFIG, 7);
+pub const MPU6050_GCONFIG_YG_ST: Field<RW, bool> = Field::bit(MPU6050_GYRO_CONFIG, 6);
+pub const MPU6050_GCONFIG_ZG_ST: Field<RW, bool> = Field::bit(MPU6050_GYRO_CONFIG, 5);
+pub const MPU6050_GCONFIG_FS_SEL: Field<RW, GyroRange> = Field::new(MPU6050_GYRO_CONFIG, 3, 2);
+pub const MPU6050_ACONFIG_XA_ST: Field<RW, bool> = Field::bit(MPU6050_ACCEL_CONFIG, 7);
+pub const MPU6050_ACONFIG_YA_ST: Field<RW, bool> = Field::bit(MPU6050_ACCEL_CONFIG, 6);
+pub const MPU6050_ACONFIG_ZA_ST: Field<RW, bool> = Field::bit(MPU6050_ACCEL_CONFIG, 5);
+pub const MPU6050_ACONFIG_AFS_SEL: Field<RW, AccelRange> = Field::new(MPU6050_ACCEL_CONFIG, 3, 2);
+pub const MPU6050_ACONFIG_ACCEL_HPF: Field<RW, u8> = Field::new(MPU6050_ACCEL_CONFIG, 0, 3);
+pub const MPU6050_CFG_DLPF_CFG: Field<RW, DlpfBandwidth> = Field::new(MPU6050_CONFIG, 0, 3);
+pub const MPU6050_I2CMST_WAIT_FOR_ES: Field<RW, bool> = Field::bit(MPU6050_I2C_MST_CTRL, 6);
+pub const MPU6050_I2CMST_CLK: Field<RW, u8> = Field::new(MPU6050_I2C_MST_CTRL, 0, 4);
+pub const MPU6050_INTCFG_LATCH_INT_EN: Field<RW, bool> = Field::bit(MPU6050_INT_PIN_CFG, 5);
+pub const MPU6050_INTCFG_I2C_BYPASS_EN: Field<RW, bool> = Field::bit(MPU6050_INT_PIN_CFG, 1);
+pub const MPU6050_INTEN_FF: Field<RW, bool> = Field::bit(MPU6050_INT_ENABLE, 7);
+pub const MPU6050_INTEN_MOT: Field<RW, bool> = Field::bit(MPU6050_INT_ENABLE, 6);
+pub const MPU6050_INTEN_ZMOT: Field<RW, bool> = Field::bit(MPU6050_INT_ENABLE, 5);
+pub const MPU6050_INTEN_DATA_RDY: Field<RW, bool> = Field::bit(MPU6050_INT_ENABLE, 0);
+pub const MPU6050_INTSTATUS_FF: Field<RO, bool> = Field::bit(MPU6050_INT_STATUS, 7);
+pub const MPU6050_INTSTATUS_MOT: Field<RO, bool> = Field::bit(MPU6050_INT_STATUS, 6);
+pub const MPU6050_INTSTATUS_ZMOT: Field<RO, bool> = Field::bit(MPU6050_INT_STATUS, 5);
+pub const MPU6050_INTSTATUS_FIFO_OFLOW: Field<RO, bool> = Field::bit(MPU6050_INT_STATUS, 4);
+pub const MPU6050_MOTDETECT_ZRMOT: Field<RO, bool> = Field::bit(MPU6050_MOT_DETECT_STATUS, 0);
+pub const MPU6050_USERCTRL_FIFO_EN: Field<RW, bool> = Field::bit(MPU6050_USER_CTRL, 6);
+pub const MPU6050_USERCTRL_I2C_MST_EN: Field<RW, bool> = Field::bit(MPU6050_USER_CTRL, 5);
+pub const MPU6050_USERCTRL_FIFO_RESET: Field<RW, bool> = Field::bit(MPU6050_USER_CTRL, 2);
+pub const MPU6050_PWR1_SLEEP: Field<RW, bool> = Field::bit(MPU6050_PWR_MGMT_1, 6);
+pub const MPU6050_PWR1_TEMP_DIS: Field<RW, bool> = Field::bit(MPU6050_PWR_MGMT_1, 3);
+pub const MPU6050_PWR1_CLKSEL: Field<RW, ClockSource> = Field::new(MPU6050_PWR_MGMT_1, 0, 3);
+
+/// Données placées dans la FIFO : accélération, température et gyroscope (même ordre que les registres)
+pub const MPU6050_FIFO_SOURCES: u16 = 1 << MPU6050_TEMP_FIFO_EN_BIT
+    | 1 << MPU6050_XG_FIFO_EN_BIT
+    | 1 << MPU6050_YG_FIFO_EN_BIT
+    | 1 << MPU6050_ZG_FIFO_EN_BIT
+    | 1 << MPU6050_ACCEL_FIFO_EN_BIT;
+
+/// Taille d'un échantillon dans la FIFO (7 valeurs de 16 bits)
+pub const MPU6050_FIFO_SAMPLE_SIZE: usize = 14;
+
+/// Taille des mesures du magnétomètre lues par SLV0 (3 valeurs de 16 bits), à la suite de l'échantillon dans la FIFO
+pub const MPU6050_AUX_MAG_SIZE: usize = 6;
+
+/// Taille de la FIFO en octets
+pub const MPU6050_FIFO_SIZE: usize = 1024;
+
+field_enum! {
+    /// Source de l'horloge du module
+    pub enum ClockSource {
+        Internal = 0x00,
+        PllXGyro = 0x01,
+        PllYGyro = 0x02,
+        PllZGyro = 0x03,
+        PllExt32k = 0x04,
+        PllExt19m = 0x05,
+        KeepReset = 0x07,
+    }
+}
+
 pub const MPU6050_SELF_TEST_XA_1_BIT: u8 = 0x07;
 pub const MPU6050_SELF_TEST_XA_1_LENGTH: u8 = 0x03;
 pub const MPU6050_SELF_TEST_XA_2_BIT: u8 = 0x05;
@@ -160,23 +247,11 @@ pub const MPU6050_DLPF_BW_42: u8 =  0x03;
 pub const MPU6050_DLPF_BW_20: u8 =  0x04;
 pub const MPU6050_DLPF_BW_10: u8 =  0x05;
 pub const MPU6050_DLPF_BW_5: u8 = 0x06;
-pub const MPU6050_GCONFIG_FS_SEL_BIT: u8 = 3;
-pub const MPU6050_GCONFIG_FS_SEL_LENGTH: u8 =  2;
-pub const MPU6050_GYRO_FS_250: u8 = 0x00;
-pub const MPU6050_GYRO_FS_500: u8 = 0x01;
-pub const MPU6050_GYRO_FS_1000: u8 =  0x02;
-pub const MPU6050_GYRO_FS_2000: u8 =  0x03;
 pub const MPU6050_ACONFIG_XA_ST_BIT: u8 = 7;
 pub const MPU6050_ACONFIG_YA_ST_BIT: u8 = 6;
 pub const MPU6050_ACONFIG_ZA_ST_BIT: u8 = 5;
-pub const MPU6050_ACONFIG_AFS_SEL_BIT: u8 = 3;
-pub const MPU6050_ACONFIG_AFS_SEL_LENGTH: u8 = 2;
 pub const MPU6050_ACONFIG_ACCEL_HPF_BIT: u8 =  2;
 pub const MPU6050_ACONFIG_ACCEL_HPF_LENGTH: u8 =  3;
-pub const MPU6050_ACCEL_FS_2: u8 =  0x00;
-pub const MPU6050_ACCEL_FS_4: u8 =  0x01;
-pub const MPU6050_ACCEL_FS_8: u8 =  0x02;
-pub const MPU6050_ACCEL_FS_16: u8 = 0x03;
 pub const MPU6050_DHPF_RESET: u8 =  0x00;
 pub const MPU6050_DHPF_5: u8 =   0x01;
 pub const MPU6050_DHPF_2P5: u8 =  0x02;
@@ -244,7 +319,6 @@ pub const MPU6050_INTCFG_LATCH_INT_EN_BIT: u8 = 5;
 pub const MPU6050_INTCFG_INT_RD_CLEAR_BIT: u8 = 4;
 pub const MPU6050_INTCFG_FSYNC_INT_LEVEL_BIT: u8 = 3;
 pub const MPU6050_INTCFG_FSYNC_INT_EN_BIT: u8 = 2;
-pub const MPU6050_INTCFG_I2C_BYPASS_EN_BIT: u8 =  1;
 pub const MPU6050_INTCFG_CLKOUT_EN_BIT: u8 =  0;
 pub const MPU6050_INTMODE_ACTIVEHIGH: u8 = 0x00;
 pub const MPU6050_INTMODE_ACTIVELOW: u8 =  0x01;
@@ -303,18 +377,7 @@ pub const MPU6050_USERCTRL_FIFO_RESET_BIT: u8 = 2;
 pub const MPU6050_USERCTRL_I2C_MST_RESET_BIT: u8 = 1;
 pub const MPU6050_USERCTRL_SIG_COND_RESET_BIT: u8 = 0;
 pub const MPU6050_PWR1_DEVICE_RESET_BIT: u8 =  7;
-pub const MPU6050_PWR1_SLEEP_BIT: u8 =  6;
 pub const MPU6050_PWR1_CYCLE_BIT: u8 =  5;
-pub const MPU6050_PWR1_TEMP_DIS_BIT: u8 =  3;
-pub const MPU6050_PWR1_CLKSEL_BIT: u8 = 0;
-pub const MPU6050_PWR1_CLKSEL_LENGTH: u8 = 3;
-pub const MPU6050_CLOCK_INTERNAL: u8 =  0x00;
-pub const MPU6050_CLOCK_PLL_XGYRO: u8 = 0x01;
-pub const MPU6050_CLOCK_PLL_YGYRO: u8 = 0x02;
-pub const MPU6050_CLOCK_PLL_ZGYRO: u8 = 0x03;
-pub const MPU6050_CLOCK_PLL_EXT32K: u8 =  0x04;
-pub const MPU6050_CLOCK_PLL_EXT19M: u8 =  0x05;
-pub const MPU6050_CLOCK_KEEP_RESET: u8 =  0x07;
 pub const MPU6050_PWR2_LP_WAKE_CTRL_BIT: u8 =  7;
 pub const MPU6050_PWR2_LP_WAKE_CTRL_LENGTH: u8 =  2;
 pub const MPU6050_PWR2_STBY_XA_BIT: u8 =  5;
@@ -335,4 +398,166 @@ pub const MPU6050_WHO_AM_I_BIT: u8 =  6;
 pub const MPU6050_WHO_AM_I_LENGTH: u8 = 6;
 pub const MPU6050_DMP_MEMORY_BANKS: u8 =  8;
 pub const MPU6050_DMP_MEMORY_BANK_SIZE: u16 =  256;
-pub const MPU6050_DMP_MEMORY_CHUNK_SIZE: u16 =  16;
\ No newline at end of file
+pub const MPU6050_DMP_MEMORY_CHUNK_SIZE: u16 =  16;
+/// Valeur de WHO_AM_I de chaque puce de la famille
+pub const MPU6050_ID: u8 = 0x68;
+pub const MPU6500_ID: u8 = 0x70;
+pub const MPU9250_ID: u8 = 0x71;
+pub const MPU9255_ID: u8 = 0x73;
+
+// MPU6500 / MPU9250 (mêmes registres que le MPU6050, sauf ceux-ci)
+pub const MPU6500_RA_SELF_TEST_X_GYRO: u8 = 0x00;
+pub const MPU6500_RA_SELF_TEST_X_ACCEL: u8 = 0x0D;
+pub const MPU6500_RA_ACCEL_CONFIG2: u8 = 0x1D;
+pub const MPU6500_RA_XA_OFFSET_H: u8 = 0x77;
+pub const MPU6500_RA_YA_OFFSET_H: u8 = 0x7A;
+pub const MPU6500_RA_ZA_OFFSET_H: u8 = 0x7D;
+
+pub const MPU6500_ACCEL_CONFIG2: Register<RW> = Register::r8(MPU6500_RA_ACCEL_CONFIG2);
+
+/// Filtre passe-bas de l'accéléromètre (460 Hz pour `Bw256`, identique au gyroscope sinon)
+pub const MPU6500_ACONFIG2_A_DLPF_CFG: Field<RW, DlpfBandwidth> = Field::new(MPU6500_ACCEL_CONFIG2, 0, 3);
+
+/// Taille de la FIFO en octets
+pub const MPU6500_FIFO_SIZE: usize = 512;
+
+// ICM-20948 (registres répartis en 4 banques, sélectionnées par REG_BANK_SEL)
+pub const ICM20948_ADDR: u16 = 0x68;
+pub const ICM20948_ADDR_ALT: u16 = 0x69;
+pub const ICM20948_ID: u8 = 0xEA;
+
+pub const ICM20948_RA_ACCEL_XOUT_H: u8 = 0x2D;
+pub const ICM20948_RA_TEMP_OUT_H: u8 = 0x39;
+pub const ICM20948_RA_FIFO_R_W: u8 = 0x72;
+
+// Banque 0
+pub const ICM20948_WHO_AM_I: Register<RO> = Register::r8(0x00);
+pub const ICM20948_USER_CTRL: Register<RW> = Register::r8(0x03);
+pub const ICM20948_PWR_MGMT_1: Register<RW> = Register::r8(0x06);
+pub const ICM20948_INT_PIN_CFG: Register<RW> = Register::r8(0x0F);
+pub const ICM20948_INT_ENABLE_1: Register<RW> = Register::r8(0x11);
+pub const ICM20948_INT_STATUS_1: Register<RO> = Register::r8(0x1A);
+pub const ICM20948_INT_STATUS_2: Register<RO> = Register::r8(0x1B);
+pub const ICM20948_FIFO_EN_2: Register<RW> = Register::r8(0x67);
+pub const ICM20948_FIFO_RST: Register<RW> = Register::r8(0x68);
+/// FIFO_COUNTH et FIFO_COUNTL
+pub const ICM20948_FIFO_COUNT: Register<RO> = Register::r16(0x70);
+pub const ICM20948_REG_BANK_SEL: Register<RW> = Register::r8(0x7F);
+
+// Banque 2
+pub const ICM20948_GYRO_SMPLRT_DIV: Register<RW> = Register::r8(0x00);
+pub const ICM20948_GYRO_CONFIG_1: Register<RW> = Register::r8(0x01);
+pub const ICM20948_ACCEL_SMPLRT_DIV: Register<RW> = Register::r16(0x10);
+pub const ICM20948_ACCEL_CONFIG: Register<RW> = Register::r8(0x14);
+
+pub const ICM20948_USERCTRL_FIFO_EN: Field<RW, bool> = Field::bit(ICM20948_USER_CTRL, 6);
+pub const ICM20948_INTSTATUS2_FIFO_OVERFLOW: Field<RO, u8> = Field::new(ICM20948_INT_STATUS_2, 0, 5);
+pub const ICM20948_PWR1_DEVICE_RESET: Field<RW, bool> = Field::bit(ICM20948_PWR_MGMT_1, 7);
+pub const ICM20948_PWR1_SLEEP: Field<RW, bool> = Field::bit(ICM20948_PWR_MGMT_1, 6);
+pub const ICM20948_PWR1_CLKSEL: Field<RW, u8> = Field::new(ICM20948_PWR_MGMT_1, 0, 3);
+pub const ICM20948_INTCFG_BYPASS_EN: Field<RW, bool> = Field::bit(ICM20948_INT_PIN_CFG, 1);
+pub const ICM20948_INTEN1_RAW_DATA_0_RDY: Field<RW, bool> = Field::bit(ICM20948_INT_ENABLE_1, 0);
+pub const ICM20948_INTSTATUS1_RAW_DATA_0_RDY: Field<RO, bool> = Field::bit(ICM20948_INT_STATUS_1, 0);
+pub const ICM20948_BANK_SEL: Field<RW, u8> = Field::new(ICM20948_REG_BANK_SEL, 4, 2);
+pub const ICM20948_GCONFIG1_DLPFCFG: Field<RW, IcmGyroDlpf> = Field::new(ICM20948_GYRO_CONFIG_1, 3, 3);
+pub const ICM20948_GCONFIG1_FS_SEL: Field<RW, GyroRange> = Field::new(ICM20948_GYRO_CONFIG_1, 1, 2);
+pub const ICM20948_GCONFIG1_FCHOICE: Field<RW, bool> = Field::bit(ICM20948_GYRO_CONFIG_1, 0);
+pub const ICM20948_ACONFIG_DLPFCFG: Field<RW, IcmAccelDlpf> = Field::new(ICM20948_ACCEL_CONFIG, 3, 3);
+pub const ICM20948_ACONFIG_FS_SEL: Field<RW, AccelRange> = Field::new(ICM20948_ACCEL_CONFIG, 1, 2);
+pub const ICM20948_ACONFIG_FCHOICE: Field<RW, bool> = Field::bit(ICM20948_ACCEL_CONFIG, 0);
+
+/// Horloge : meilleure source disponible (PLL si possible)
+pub const ICM20948_CLOCK_AUTO: u8 = 0x01;
+
+/// FIFO_EN_2 : accéléromètre, gyroscope (X, Y, Z) et température, dans l'ordre des registres
+pub const ICM20948_FIFO_SOURCES: u16 = 0x1F;
+/// FIFO_RST : toutes les FIFO (à remettre à 0 ensuite)
+pub const ICM20948_FIFO_RESET_ALL: u16 = 0x1F;
+
+/// Taille d'un échantillon dans la FIFO : accélération, vitesse angulaire et température (7 x 16 bits)
+pub const ICM20948_FIFO_SAMPLE_SIZE: usize = 14;
+
+/// Taille de la FIFO en octets
+pub const ICM20948_FIFO_SIZE: usize = 512;
+
+/// Fréquence interne du gyroscope et de l'accéléromètre avec le filtre passe-bas (en Hz)
+pub const ICM20948_BASE_RATE: f32 = 1125.0;
+
+field_enum! {
+    /// Filtre passe-bas du gyroscope de l'ICM-20948 (coupure à -3 dB en Hz, FCHOICE à 1)
+    pub enum IcmGyroDlpf {
+        Hz197 = 0,
+        Hz152 = 1,
+        Hz120 = 2,
+        Hz51 = 3,
+        Hz24 = 4,
+        Hz12 = 5,
+        Hz6 = 6,
+        Hz361 = 7,
+    }
+}
+
+field_enum! {
+    /// Filtre passe-bas de l'accéléromètre de l'ICM-20948 (coupure à -3 dB en Hz, FCHOICE à 1)
+    pub enum IcmAccelDlpf {
+        Hz246 = 1,
+        Hz111 = 2,
+        Hz50 = 3,
+        Hz24 = 4,
+        Hz12 = 5,
+        Hz6 = 6,
+        Hz473 = 7,
+    }
+}
+
+// BNO055 (page 0)
+pub const BNO055_ADDR: u16 = 0x28;
+pub const BNO055_ADDR_ALT: u16 = 0x29;
+pub const BNO055_ID: u8 = 0xA0;
+
+pub const BNO055_RA_ACC_DATA_X_LSB: u8 = 0x08;
+pub const BNO055_RA_GYR_DATA_X_LSB: u8 = 0x14;
+pub const BNO055_RA_EUL_HEADING_LSB: u8 = 0x1A;
+pub const BNO055_RA_QUA_DATA_W_LSB: u8 = 0x20;
+
+pub const BNO055_CHIP_ID: Register<RO> = Register::r8(0x00);
+pub const BNO055_PAGE_ID: Register<RW> = Register::r8(0x07);
+pub const BNO055_TEMP: Register<RO> = Register::r8(0x34);
+pub const BNO055_CALIB_STAT: Register<RO> = Register::r8(0x35);
+pub const BNO055_ST_RESULT: Register<RO> = Register::r8(0x36);
+pub const BNO055_UNIT_SEL: Register<RW> = Register::r8(0x3B);
+pub const BNO055_OPR_MODE: Register<RW> = Register::r8(0x3D);
+pub const BNO055_PWR_MODE: Register<RW> = Register::r8(0x3E);
+pub const BNO055_SYS_TRIGGER: Register<RW> = Register::r8(0x3F);
+pub const BNO055_AXIS_MAP_CONFIG: Register<RW> = Register::r8(0x41);
+pub const BNO055_AXIS_MAP_SIGN: Register<RW> = Register::r8(0x42);
+
+pub const BNO055_OPR_MODE_MODE: Field<RW, BnoMode> = Field::new(BNO055_OPR_MODE, 0, 4);
+pub const BNO055_SYS_TRIGGER_RST_SYS: Field<RW, bool> = Field::bit(BNO055_SYS_TRIGGER, 5);
+pub const BNO055_CALIB_STAT_SYS: Field<RO, u8> = Field::new(BNO055_CALIB_STAT, 6, 2);
+pub const BNO055_CALIB_STAT_GYR: Field<RO, u8> = Field::new(BNO055_CALIB_STAT, 4, 2);
+pub const BNO055_CALIB_STAT_ACC: Field<RO, u8> = Field::new(BNO055_CALIB_STAT, 2, 2);
+pub const BNO055_CALIB_STAT_MAG: Field<RO, u8> = Field::new(BNO055_CALIB_STAT, 0, 2);
+
+/// Unités : accélération en mg, vitesse angulaire en °/s, angles en degrés, température en °C
+pub const BNO055_UNITS: u16 = 0x01;
+
+/// Mode d'alimentation normal
+pub const BNO055_POWER_NORMAL: u16 = 0x00;
+
+/// Sensibilités des mesures (LSB par unité)
+pub const BNO055_GYRO_LSB: f32 = 16.0;
+pub const BNO055_EULER_LSB: f32 = 16.0;
+pub const BNO055_QUATERNION_LSB: f32 = 16384.0;
+
+/// Période de la fusion embarquée (100 Hz)
+pub const BNO055_FUSION_PERIOD_MS: u64 = 10;
+
+field_enum! {
+    /// Mode de fonctionnement du BNO055
+    pub enum BnoMode {
+        Config = 0x00,
+        Imu = 0x08,
+        Ndof = 0x0C,
+    }
+}
@@ -0,0 +1,150 @@
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+/// Ecart maximal (en %) entre la réponse à l'auto-test et la valeur d'usine
+const MAX_CHANGE: f32 = 14.0;
+
+/// Ecart maximal (en %) pour le MPU6500 et le MPU9250
+const MPU6500_MAX_CHANGE: f32 = 50.0;
+
+/// Résultat de l'auto-test d'un axe
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct SelfTestAxis {
+    /// Ecart à la valeur d'usine (en %), `None` si le module n'a pas de valeur d'usine
+    pub change: Option<f32>,
+    pub pass: bool,
+}
+
+impl SelfTestAxis {
+    fn new(response: f32, factory: f32, max_change: f32) -> Self {
+        // Sans valeur d'usine, le module ne peut pas être validé
+        if factory == 0.0 {
+            return Self { change: None, pass: false };
+        }
+
+        let change = (response - factory) / factory * 100.0;
+        Self { change: Some(change), pass: change.abs() <= max_change }
+    }
+}
+
+/// Résultat de l'auto-test du module (axes X, Y, Z)
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct SelfTestReport {
+    pub gyro: [SelfTestAxis; 3],
+    pub accel: [SelfTestAxis; 3],
+    pub pass: bool,
+}
+
+impl SelfTestReport {
+    /// Compare la réponse à l'auto-test (gyroscope à ±250 °/s et accéléromètre à ±8 g, en LSB)
+    /// aux valeurs d'usine des registres SELF_TEST_X, _Y, _Z et _A
+    pub(crate) fn new(gyro_response: Vector3<f32>, accel_response: Vector3<f32>, trim: [u8; 4]) -> Self {
+        let (gyro_factory, accel_factory) = factory_trim(trim);
+        Self::compare(gyro_response, accel_response, gyro_factory, accel_factory, MAX_CHANGE)
+    }
+
+    /// Même comparaison pour le MPU6500 et le MPU9250 (gyroscope à ±250 °/s et accéléromètre à ±2 g)
+    /// `codes` : registres SELF_TEST_X_GYRO à _Z_GYRO puis SELF_TEST_X_ACCEL à _Z_ACCEL
+    pub(crate) fn mpu6500(gyro_response: Vector3<f32>, accel_response: Vector3<f32>, codes: [u8; 6]) -> Self {
+        let factory = |code: u8| match code {
+            0 => 0.0,
+            _ => 2620.0 * 1.01_f32.powi(code as i32 - 1),
+        };
+        let gyro_factory = Vector3::new(factory(codes[0]), factory(codes[1]), factory(codes[2]));
+        let accel_factory = Vector3::new(factory(codes[3]), factory(codes[4]), factory(codes[5]));
+
+        // Le sens de la réponse n'est pas spécifié, seule son amplitude compte
+        Self::compare(gyro_response.abs(), accel_response.abs(), gyro_factory, accel_factory, MPU6500_MAX_CHANGE)
+    }
+
+    fn compare(
+        gyro_response: Vector3<f32>,
+        accel_response: Vector3<f32>,
+        gyro_factory: Vector3<f32>,
+        accel_factory: Vector3<f32>,
+        max_change: f32,
+    ) -> Self {
+        let gyro = [0, 1, 2].map(|n| SelfTestAxis::new(gyro_response[n], gyro_factory[n], max_change));
+        let accel = [0, 1, 2].map(|n| SelfTestAxis::new(accel_response[n], accel_factory[n], max_change));
+        let pass = gyro.iter().chain(accel.iter()).all(|axis| axis.pass);
+
+        Self { gyro, accel, pass }
+    }
+
+    /// Affiche le résultat de chaque axe
+    pub(crate) fn log(&self) {
+        let status = |axis: &SelfTestAxis| {
+            let result = if axis.pass { "OK" } else { "ECHEC" };
+            match axis.change {
+                Some(change) => format!("{} ({:+.1} %)", result, change),
+                None => format!("{} (pas de valeur d'usine)", result),
+            }
+        };
+
+        for (name, axis) in ["X", "Y", "Z"].iter().zip(self.gyro.iter()) {
+            println!("[IMU] Auto-test GYRO {}: {}", name, status(axis));
+        }
+        for (name, axis) in ["X", "Y", "Z"].iter().zip(self.accel.iter()) {
+            println!("[IMU] Auto-test ACCEL {}: {}", name, status(axis));
+        }
+
+        if !self.pass {
+            println!("[IMU] Auto-test en échec, le capteur est peut-être endommagé.");
+        }
+    }
+}
+
+/// Réponse attendue à l'auto-test d'après les valeurs d'usine (en LSB, gyroscope puis accéléromètre)
+/// Gyroscope : XG_TEST (5 bits) de SELF_TEST_X à _Z.
+/// Accéléromètre : 3 bits de poids fort de SELF_TEST_X à _Z, 2 bits de poids faible dans SELF_TEST_A.
+fn factory_trim(trim: [u8; 4]) -> (Vector3<f32>, Vector3<f32>) {
+    let gyro_test = Vector3::new(trim[0] & 0x1F, trim[1] & 0x1F, trim[2] & 0x1F);
+    let accel_test = Vector3::new(
+        ((trim[0] >> 5) << 2) | ((trim[3] >> 4) & 0x03),
+        ((trim[1] >> 5) << 2) | ((trim[3] >> 2) & 0x03),
+        ((trim[2] >> 5) << 2) | (trim[3] & 0x03),
+    );
+
+    let gyro = |test: u8| match test {
+        0 => 0.0,
+        _ => 25.0 * 131.0 * 1.046_f32.powi(test as i32 - 1),
+    };
+    let accel = |test: u8| match test {
+        0 => 0.0,
+        _ => 4096.0 * 0.34 * (0.92_f32 / 0.34).powf((test as f32 - 1.0) / 30.0),
+    };
+
+    // L'axe Y du gyroscope répond dans le sens opposé
+    (
+        Vector3::new(gyro(gyro_test.x), -gyro(gyro_test.y), gyro(gyro_test.z)),
+        accel_test.map(accel),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_compared_to_factory_trim() {
+        // XG_TEST = 1, YG_TEST = 1, ZG_TEST = 0 ; XA_TEST = YA_TEST = ZA_TEST = 1
+        let trim = [0b0000_0001, 0b0000_0001, 0b0000_0000, 0b0001_0101];
+        let (gyro, accel) = factory_trim(trim);
+        assert_eq!(gyro, Vector3::new(3275.0, -3275.0, 0.0));
+        assert!((accel - Vector3::repeat(1392.64)).norm() < 1e-2);
+
+        let report = SelfTestReport::new(Vector3::new(3300.0, -2500.0, 3300.0), Vector3::repeat(1400.0), trim);
+        assert!(report.gyro[0].pass);
+        assert!(!report.gyro[1].pass);
+        assert!(!report.gyro[2].pass);
+        assert!(report.accel.iter().all(|axis| axis.pass));
+        assert!(!report.pass);
+
+        // MPU6500 : code 1 = 2620 LSB, écart toléré de 50 %, réponse prise en valeur absolue
+        let report = SelfTestReport::mpu6500(Vector3::new(-2620.0, 1000.0, 2620.0), Vector3::repeat(2620.0), [1, 1, 0, 1, 1, 1]);
+        assert_eq!(report.gyro[0].change, Some(0.0));
+        assert!(!report.gyro[1].pass);
+        assert_eq!(report.gyro[2].change, None);
+        assert!(report.accel.iter().all(|axis| axis.pass));
+    }
+}
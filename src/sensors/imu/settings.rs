@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i2c::field::field_enum;
+
+/// Puces IMU supportées
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ImuChip {
+    Mpu6050,
+    Mpu6500,
+    /// MPU6500 accompagné d'un magnétomètre AK8963 (non utilisé)
+    Mpu9250,
+    Icm20948,
+    /// Fusion des capteurs réalisée par la puce
+    Bno055,
+}
+
+field_enum! {
+    /// Bande passante du filtre passe-bas numérique (en Hz, accéléromètre / gyroscope)
+    /// `Bw256` désactive le filtre : le gyroscope échantillonne alors à 8 kHz au lieu de 1 kHz.
+    #[derive(Serialize, Deserialize)]
+    pub enum DlpfBandwidth {
+        Bw256 = 0x00,
+        Bw188 = 0x01,
+        Bw98 = 0x02,
+        Bw42 = 0x03,
+        Bw20 = 0x04,
+        Bw10 = 0x05,
+        Bw5 = 0x06,
+    }
+}
+
+field_enum! {
+    /// Echelle du gyroscope (en °/s)
+    #[derive(Serialize, Deserialize)]
+    pub enum GyroRange {
+        Fs250 = 0x00,
+        Fs500 = 0x01,
+        Fs1000 = 0x02,
+        Fs2000 = 0x03,
+    }
+}
+
+field_enum! {
+    /// Echelle de l'accéléromètre (en g)
+    #[derive(Serialize, Deserialize)]
+    pub enum AccelRange {
+        Fs2 = 0x00,
+        Fs4 = 0x01,
+        Fs8 = 0x02,
+        Fs16 = 0x03,
+    }
+}
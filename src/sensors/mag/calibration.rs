@@ -0,0 +1,291 @@
+use std::f32::consts::PI;
+
+use anyhow::anyhow;
+use nalgebra::{DMatrix, DVector, Matrix1x3, Matrix3, SymmetricEigen, UnitQuaternion, Vector3};
+
+use crate::config::Config;
+
+/// Corrections appliquées aux mesures du magnétomètre, quel que soit le module
+pub(crate) struct MagCorrection {
+    mag_decl: f32,
+    hard_cal: Vector3<f32>,
+    soft_cal: Matrix3<f32>,
+    orientation: Matrix3<f32>,
+}
+
+impl MagCorrection {
+    // NOTE : Les données de calibration sont obtenues sur la voiture (switch `mag_calibration`, voir `MagCalibrator`)
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            mag_decl: config.mag_decl,
+            hard_cal: config.hard_cal,
+            soft_cal: config.soft_cal,
+            orientation: config.mag_orientation.matrix(),
+        }
+    }
+
+    /// Applique la calibration "Hard Iron" & "Soft Iron" à une mesure RAW
+    /// Le résultat est exprimé dans le repère de la voiture (`mag_orientation`).
+    pub(crate) fn calibrated_from_raw(&self, raw: Vector3<i16>) -> Vector3<f32> {
+        let hard_mag = Matrix1x3::new(
+            raw.x as f32 - self.hard_cal.x,
+            raw.y as f32 - self.hard_cal.y,
+            raw.z as f32 - self.hard_cal.z,
+        );
+        let corrected_mag = hard_mag * self.soft_cal;
+
+        self.orientation * Vector3::new(corrected_mag.x, corrected_mag.y, corrected_mag.z)
+    }
+
+    /// Calcul le heading à partir d'une mesure RAW
+    pub(crate) fn heading_from_raw(&self, raw: Vector3<i16>) -> f32 {
+        let corrected_mag = self.calibrated_from_raw(raw);
+
+        // Calcul du heading (repère de la voiture, même convention que l'AHRS), prend en compte la déclinaison magnétique
+        let heading = corrected_mag.y.atan2(corrected_mag.x) * (180.0 / PI) + self.mag_decl;
+        heading.rem_euclid(360.0)
+    }
+
+    /// Calcul le heading à partir d'une mesure RAW, compensé de l'inclinaison de la voiture
+    /// Le champ (3 axes) est ramené à l'horizontale avec le tangage et le roulis de l'IMU (en degrés),
+    /// le cap suit la convention de l'AHRS (sens horaire, 0 au nord, déclinaison comprise).
+    pub(crate) fn tilt_heading_from_raw(&self, raw: Vector3<i16>, pitch: f32, roll: f32) -> f32 {
+        let level = UnitQuaternion::from_euler_angles(roll.to_radians(), pitch.to_radians(), 0.0);
+        let horizontal = level.transform_vector(&self.calibrated_from_raw(raw));
+
+        (horizontal.y.atan2(horizontal.x).to_degrees() + self.mag_decl).rem_euclid(360.0)
+    }
+}
+
+/// Nombre minimal de mesures pour ajuster l'ellipsoïde
+const MIN_SAMPLES: usize = 100;
+
+/// Nombre maximal de mesures conservées (temps de calcul de l'ajustement)
+const MAX_SAMPLES: usize = 2000;
+
+/// Distance minimale (en LSB) avec la mesure précédente pour conserver une mesure
+const MIN_SAMPLE_SPACING: f32 = 10.0;
+
+/// Découpage de la sphère des directions : secteurs de cap, puis bandes de même surface en hauteur
+const COVERAGE_AZIMUTH_BINS: usize = 8;
+const COVERAGE_ELEVATION_BINS: usize = 4;
+
+/// Part minimale des directions couvertes pour accepter la calibration
+const MIN_COVERAGE: f32 = 0.5;
+
+/// Ecart relatif moyen (RMS) maximal entre les mesures corrigées et la sphère
+const MAX_RESIDUAL: f32 = 0.1;
+
+/// Résultat de la calibration du magnétomètre
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MagCalibration {
+    /// Centre de l'ellipsoïde (en LSB)
+    pub hard_cal: Vector3<f32>,
+    /// Matrice ramenant l'ellipsoïde à une sphère, appliquée après `hard_cal`
+    pub soft_cal: Matrix3<f32>,
+    /// Part des directions couvertes (0 à 1)
+    pub coverage: f32,
+    /// Ecart relatif moyen (RMS) entre les mesures corrigées et la sphère
+    pub residual: f32,
+    pub samples: usize,
+}
+
+/// Calibration sur la voiture : collecte les mesures RAW pendant que la voiture est tournée dans tous
+/// les sens, puis ajuste un ellipsoïde (moindres carrés) pour obtenir `hard_cal` et `soft_cal`
+pub(crate) struct MagCalibrator {
+    samples: Vec<Vector3<f32>>,
+    sum: Vector3<f32>,
+}
+
+impl MagCalibrator {
+    pub(crate) fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+            sum: Vector3::zeros(),
+        }
+    }
+
+    /// Ajoute une mesure RAW, ignorée si elle est trop proche de la précédente
+    pub(crate) fn add(&mut self, raw: Vector3<i16>) {
+        let sample = raw.map(|v| v as f32);
+        if self.samples.len() >= MAX_SAMPLES {
+            return;
+        }
+        if self.samples.last().is_some_and(|last| (sample - last).norm() < MIN_SAMPLE_SPACING) {
+            return;
+        }
+
+        self.sum += sample;
+        self.samples.push(sample);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Part des directions couvertes, vues depuis la moyenne des mesures (estimation du centre)
+    pub(crate) fn coverage(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+
+        let center = self.sum / self.samples.len() as f32;
+        coverage(self.samples.iter().map(|sample| sample - center))
+    }
+
+    /// Ajuste l'ellipsoïde et vérifie la qualité du résultat (couverture et écart à la sphère)
+    pub(crate) fn fit(&self) -> anyhow::Result<MagCalibration> {
+        if self.samples.len() < MIN_SAMPLES {
+            return Err(anyhow!("[MAG] Pas assez de mesures ({} / {})", self.samples.len(), MIN_SAMPLES));
+        }
+
+        let (hard_cal, soft_cal, radius) = fit_ellipsoid(&self.samples)
+            .ok_or(anyhow!("[MAG] Ajustement impossible, les mesures ne forment pas un ellipsoïde"))?;
+
+        let corrected: Vec<Vector3<f32>> = self.samples.iter().map(|sample| soft_cal * (sample - hard_cal)).collect();
+        let coverage = coverage(corrected.iter().copied());
+        let residual = (corrected.iter().map(|v| (v.norm() / radius - 1.0).powi(2)).sum::<f32>() / corrected.len() as f32).sqrt();
+
+        let calibration = MagCalibration {
+            hard_cal,
+            soft_cal,
+            coverage,
+            residual,
+            samples: self.samples.len(),
+        };
+
+        if coverage < MIN_COVERAGE {
+            return Err(anyhow!("[MAG] Couverture insuffisante ({:.0} %), tourner la voiture dans tous les sens", coverage * 100.0));
+        }
+        if residual > MAX_RESIDUAL {
+            return Err(anyhow!("[MAG] Mesures trop dispersées (écart {:.1} %), perturbations magnétiques ?", residual * 100.0));
+        }
+
+        Ok(calibration)
+    }
+}
+
+/// Part des cases de la sphère des directions contenant au moins une mesure
+fn coverage(directions: impl Iterator<Item = Vector3<f32>>) -> f32 {
+    let mut bins = [[false; COVERAGE_AZIMUTH_BINS]; COVERAGE_ELEVATION_BINS];
+
+    for direction in directions {
+        let norm = direction.norm();
+        if norm == 0.0 {
+            continue;
+        }
+
+        let azimuth = direction.y.atan2(direction.x).rem_euclid(2.0 * PI);
+        let azimuth = ((azimuth / (2.0 * PI) * COVERAGE_AZIMUTH_BINS as f32) as usize).min(COVERAGE_AZIMUTH_BINS - 1);
+
+        // Bandes de même surface : découpage régulier de la composante Z
+        let elevation = ((direction.z / norm + 1.0) / 2.0 * COVERAGE_ELEVATION_BINS as f32) as usize;
+        bins[elevation.min(COVERAGE_ELEVATION_BINS - 1)][azimuth] = true;
+    }
+
+    let hit = bins.iter().flatten().filter(|&&hit| hit).count();
+    hit as f32 / (COVERAGE_AZIMUTH_BINS * COVERAGE_ELEVATION_BINS) as f32
+}
+
+/// Ajuste la quadrique `ax² + by² + cz² + 2fyz + 2gxz + 2hxy + 2px + 2qy + 2rz = 1` aux mesures
+/// Retourne le centre, la matrice symétrique ramenant l'ellipsoïde à une sphère et le rayon de cette sphère
+/// (moyenne géométrique des demi-axes, pour conserver l'échelle des mesures).
+fn fit_ellipsoid(samples: &[Vector3<f32>]) -> Option<(Vector3<f32>, Matrix3<f32>, f32)> {
+    // Mesures ramenées autour de 1 pour le conditionnement du système
+    let scale = samples.iter().map(|s| s.amax()).fold(0.0, f32::max) as f64;
+    if scale == 0.0 {
+        return None;
+    }
+
+    let design = DMatrix::from_fn(samples.len(), 9, |row, col| {
+        let [x, y, z] = [0, 1, 2].map(|n| samples[row][n] as f64 / scale);
+        [x * x, y * y, z * z, 2.0 * y * z, 2.0 * x * z, 2.0 * x * y, 2.0 * x, 2.0 * y, 2.0 * z][col]
+    });
+    let ones = DVector::repeat(samples.len(), 1.0);
+    let v = design.svd(true, true).solve(&ones, 1e-12).ok()?;
+
+    let quadric = Matrix3::new(v[0], v[5], v[4], v[5], v[1], v[3], v[4], v[3], v[2]);
+    let center = -quadric.try_inverse()? * Vector3::new(v[6], v[7], v[8]);
+
+    // (x - centre)ᵀ A (x - centre) = 1 + centreᵀ A centre
+    let k = 1.0 + (center.transpose() * quadric * center)[0];
+    let shape = SymmetricEigen::new(quadric / k);
+    if shape.eigenvalues.iter().any(|&eigenvalue| eigenvalue <= 0.0) {
+        return None;
+    }
+
+    // Rayon de la sphère : moyenne géométrique des demi-axes (1 / √λ)
+    let radius = shape.eigenvalues.iter().product::<f64>().powf(-1.0 / 6.0);
+    let sqrt = Matrix3::from_diagonal(&shape.eigenvalues.map(f64::sqrt));
+    let soft = shape.eigenvectors * sqrt * shape.eigenvectors.transpose() * radius;
+
+    Some(((center * scale).cast(), soft.cast(), (radius * scale) as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heading_compensated_for_tilt() {
+        let correction = MagCorrection {
+            mag_decl: 0.0,
+            hard_cal: Vector3::zeros(),
+            soft_cal: Matrix3::identity(),
+            orientation: Matrix3::identity(),
+        };
+
+        // Champ terrestre : vers le nord, incliné de 60° vers le sol
+        let dip = 60.0_f32.to_radians();
+        let earth = Vector3::new(dip.cos(), 0.0, -dip.sin()) * 400.0;
+
+        // Voiture orientée à 60° (cap horaire), en montée et penchée
+        let (pitch, roll, heading) = (-20.0_f32, 15.0_f32, 60.0_f32);
+        let attitude = UnitQuaternion::from_euler_angles(roll.to_radians(), pitch.to_radians(), -heading.to_radians());
+        let raw = attitude.inverse_transform_vector(&earth).map(|v| v.round() as i16);
+
+        assert!((correction.tilt_heading_from_raw(raw, pitch, roll) - heading).abs() < 1.0);
+        assert!((correction.tilt_heading_from_raw(raw, 0.0, 0.0) - heading).abs() > 10.0);
+
+        // Déclinaison vers l'est ajoutée au cap magnétique, comme dans l'AHRS
+        let correction = MagCorrection { mag_decl: 2.44, ..correction };
+        let level = UnitQuaternion::from_euler_angles(0.0, 0.0, -heading.to_radians());
+        let raw = level.inverse_transform_vector(&earth).map(|v| v.round() as i16);
+        assert!((correction.heading_from_raw(raw) - 62.44).abs() < 0.5);
+        assert!((correction.tilt_heading_from_raw(raw, 0.0, 0.0) - 62.44).abs() < 0.5);
+    }
+
+    #[test]
+    fn ellipsoid_fitted_from_rotation() {
+        // Champ de 400 LSB déformé (soft iron) puis décalé (hard iron)
+        let hard = Vector3::new(120.0, -80.0, 40.0);
+        let distortion = Matrix3::new(1.2, 0.1, 0.0, 0.1, 0.9, 0.05, 0.0, 0.05, 1.0);
+
+        let mut calibrator = MagCalibrator::new();
+        for elevation in -8..=8 {
+            for azimuth in 0..36 {
+                let (elevation, azimuth) = (elevation as f32 * PI / 18.0, azimuth as f32 * PI / 18.0);
+                let field = Vector3::new(elevation.cos() * azimuth.cos(), elevation.cos() * azimuth.sin(), elevation.sin()) * 400.0;
+                calibrator.add((distortion * field + hard).map(|v| v.round() as i16));
+            }
+        }
+        assert!(calibrator.coverage() > 0.9);
+
+        let calibration = calibrator.fit().unwrap();
+        assert!((calibration.hard_cal - hard).norm() < 2.0);
+        assert!(calibration.residual < 0.01);
+        assert_eq!(calibration.coverage, 1.0);
+
+        // La correction ramène les mesures sur une sphère
+        let correction = calibration.soft_cal * distortion;
+        assert!((correction - Matrix3::identity() * correction[(0, 0)]).amax() < 0.02 * correction[(0, 0)]);
+
+        // Voiture tournée à plat uniquement : couverture insuffisante
+        let mut flat = MagCalibrator::new();
+        for azimuth in 0..360 {
+            let azimuth = azimuth as f32 * PI / 180.0;
+            flat.add((Vector3::new(azimuth.cos(), azimuth.sin(), 0.3) * 400.0).map(|v| v.round() as i16));
+        }
+        assert!(flat.fit().is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use anyhow::anyhow;
+use nalgebra::Vector3;
+
+use crate::config::Config;
+use crate::i2c::manager::{I2CDevice, I2CManager};
+use crate::i2c::{I2CBit, I2CBus};
+use crate::sensors::imu::driver::AuxMagnetometer;
+use crate::sensors::mag::calibration::MagCorrection;
+use crate::sensors::mag::hmc8553l::HMC8553L;
+use crate::sensors::mag::qmc5883l::QMC5883L;
+use crate::sensors::mag::registry;
+pub(crate) use crate::sensors::mag::settings::MagChip;
+
+/// Mesure saturée : le champ dépasse la pleine échelle du module (gain trop élevé, aimant proche, ...)
+#[derive(Debug)]
+pub(crate) struct MagOverflow;
+
+impl fmt::Display for MagOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[MAG] Mesure saturée")
+    }
+}
+
+impl std::error::Error for MagOverflow {}
+
+/// Pilote d'un magnétomètre : mesures brutes et surveillance du module
+pub(crate) trait MagDriver {
+    /// Corrections (calibration, orientation, déclinaison) appliquées aux mesures
+    fn correction(&self) -> &MagCorrection;
+
+    /// Applique la calibration de la configuration, sans réinitialiser le module
+    fn recalibrate(&mut self, config: &Config);
+
+    /// Récupére les données raw (axes X, Y, Z du module), `None` si aucune nouvelle mesure n'est prête
+    /// Une mesure saturée retourne l'erreur `MagOverflow`.
+    fn get_mag_axes_raw(&mut self, i2c: &mut dyn I2CBus) -> anyhow::Result<Option<Vector3<i16>>>;
+
+    /// Vérifie que le module répond toujours et n'a pas été réinitialisé (perte d'alimentation, ...)
+    /// Relance l'initialisation si la configuration relue ne correspond plus. Retourne vrai si c'est le cas.
+    fn check_and_recover(&mut self, i2c: &mut dyn I2CBus) -> anyhow::Result<bool>;
+
+    /// Lecture des mesures par le maître I2C de l'IMU, quand le module est sur son bus auxiliaire
+    fn aux_magnetometer(&self) -> AuxMagnetometer;
+
+    /// Remets dans l'ordre X, Y, Z les valeurs lues par le maître I2C de l'IMU (octet de poids fort en premier)
+    fn raw_from_words(&self, words: [i16; 3]) -> Vector3<i16>;
+
+    /// Sensibilité de la pleine échelle configurée (LSB par Gauss)
+    fn lsb_per_gauss(&self) -> f32;
+
+    /// Vrai si une mesure RAW est saturée (lecture par le maître I2C de l'IMU, sans registre de statut)
+    fn is_overflow(&self, _raw: Vector3<i16>) -> bool {
+        false
+    }
+
+    /// Intensité du champ (en Gauss) d'une mesure RAW, après calibration
+    fn field_strength(&self, raw: Vector3<i16>) -> f32 {
+        self.calibrated_from_raw(raw).norm() / self.lsb_per_gauss()
+    }
+
+    /// Applique la calibration à une mesure RAW, dans le repère de la voiture
+    fn calibrated_from_raw(&self, raw: Vector3<i16>) -> Vector3<f32> {
+        self.correction().calibrated_from_raw(raw)
+    }
+
+    /// Calcul le heading à partir d'une mesure RAW
+    fn heading_from_raw(&self, raw: Vector3<i16>) -> f32 {
+        self.correction().heading_from_raw(raw)
+    }
+
+    /// Calcul le heading à partir d'une mesure RAW, compensé du tangage et du roulis (en degrés)
+    fn tilt_heading_from_raw(&self, raw: Vector3<i16>, pitch: f32, roll: f32) -> f32 {
+        self.correction().tilt_heading_from_raw(raw, pitch, roll)
+    }
+}
+
+/// Cherche le magnétomètre présent sur le bus (registres d'identification)
+pub(crate) fn detect_chip<B: I2CBus>(i2c_bus: &I2CManager<B>) -> Option<MagChip> {
+    let mut hmc = i2c_bus.device(registry::HMC8553L_MAG_ADDR);
+    if hmc.lecture_words::<3>(registry::HMC8553L_ID_A).ok() == Some(registry::HMC8553L_ID) {
+        return Some(MagChip::Hmc5883l);
+    }
+
+    let mut qmc = i2c_bus.device(registry::QMC5883L_MAG_ADDR);
+    match qmc.lecture_reg(registry::QMC5883L_CHIP_ID) {
+        Ok(id) if id as u8 == registry::QMC5883L_ID => Some(MagChip::Qmc5883l),
+        _ => None,
+    }
+}
+
+/// Créer le pilote du magnétomètre choisi dans la configuration (`mag_chip`), ou détecté sur le bus
+pub(crate) fn detect<B: I2CBus>(i2c_bus: &I2CManager<B>, config: &Config) -> anyhow::Result<(Box<dyn MagDriver>, I2CDevice<B>)> {
+    let chip = match config.mag_chip {
+        Some(chip) => chip,
+        None => detect_chip(i2c_bus).ok_or(anyhow!("[MAG] Aucun magnétomètre détecté"))?,
+    };
+
+    println!("[MAG] Puce: {:?}", chip);
+    let (driver, i2c): (Box<dyn MagDriver>, _) = match chip {
+        MagChip::Hmc5883l => {
+            let mut i2c = i2c_bus.device(registry::HMC8553L_MAG_ADDR);
+            (Box::new(HMC8553L::new(&mut i2c, config.clone())?), i2c)
+        }
+        MagChip::Qmc5883l => {
+            let mut i2c = i2c_bus.device(registry::QMC5883L_MAG_ADDR);
+            (Box::new(QMC5883L::new(&mut i2c, config)?), i2c)
+        }
+    };
+
+    Ok((driver, i2c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+
+    #[test]
+    fn chip_detected_by_identification_register() {
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::HMC8553L_ID_A, &registry::HMC8553L_ID);
+        bus.attach(registry::HMC8553L_MAG_ADDR, device);
+        assert_eq!(detect_chip(&I2CManager::new(bus)), Some(MagChip::Hmc5883l));
+
+        let mut bus = SimBus::new();
+        let mut device = SimDevice::new();
+        device.set_registers(registry::QMC5883L_CHIP_ID.address(), &[registry::QMC5883L_ID]);
+        bus.attach(registry::QMC5883L_MAG_ADDR, device);
+        assert_eq!(detect_chip(&I2CManager::new(bus)), Some(MagChip::Qmc5883l));
+
+        assert_eq!(detect_chip(&I2CManager::new(SimBus::new())), None);
+    }
+}
@@ -1,35 +1,54 @@
 #![allow(unused)]
 
 use crate::config::Config;
-use crate::i2c::I2CBit;
+use crate::i2c::{I2CBit, I2CBus};
+use crate::sensors::imu::driver::AuxMagnetometer;
+use crate::sensors::mag::calibration::MagCorrection;
+use crate::sensors::mag::driver::{MagDriver, MagOverflow};
 use crate::sensors::mag::registry;
+use crate::sensors::mag::registry::{HmcBias, HmcDataRate, HmcGain, HmcMode, HmcSamples};
 use anyhow::anyhow;
-use nalgebra::Matrix1x3;
-use nalgebra::Matrix3;
-use nalgebra::{Matrix1, Vector3};
-use rppal::i2c::I2c;
+use nalgebra::Vector3;
 use std::fmt;
 use std::thread::sleep;
 use std::time::Duration;
 use std::time::Instant;
-use std::{error::Error, f32::consts::PI};
+use std::error::Error;
 
 pub (crate) struct HMC8553L {
-    mag_decl: f32,
-    hard_cal: Vector3<f32>,
-    soft_cal: Matrix3<f32>,
+    correction: MagCorrection,
+    averaging: HmcSamples,
+    data_rate: HmcDataRate,
+    gain: HmcGain,
+    mode: HmcMode,
+    /// Instant de la dernière mesure lue (mode continu)
+    last_sample: Option<Instant>,
 }
 
 impl HMC8553L {
+    /// Adresse du module sur le bus I2C
+    pub(crate) const ADDR: u16 = registry::HMC8553L_MAG_ADDR;
+
     /// Constructeur
-    pub (crate) fn new(i2c: &mut I2c, config: Config) -> anyhow::Result<Self> {
+    pub (crate) fn new<B: I2CBus>(i2c: &mut B, config: Config) -> anyhow::Result<Self> {
+        // Le mode "Idle" ne produit aucune mesure
+        if config.mag_mode == HmcMode::Idle {
+            return Err(anyhow!("[HMC8554L] Mode de mesure {:?} non supporté", config.mag_mode));
+        }
+
+        // La mesure unique est relancée après chaque lecture par l'hôte, pas par le maître I2C de l'IMU
+        if config.mag_mode == HmcMode::Single && config.mag_aux_bus {
+            return Err(anyhow!("[HMC8554L] Mode de mesure {:?} incompatible avec le bus auxiliaire de l'IMU", config.mag_mode));
+        }
+
         // Créer l'objet et commence l'initialisation
-        // NOTE : Pour obtenir les données de calibration, utiliser la partie "RAW" sur l'UI puis
-        // le script : https://github.com/nliaudat/magnetometer_calibration/
         let mut mag = Self {
-            mag_decl: config.mag_decl,
-            hard_cal: config.hard_cal,
-            soft_cal: config.soft_cal,
+            correction: MagCorrection::new(&config),
+            averaging: config.mag_averaging,
+            data_rate: config.mag_data_rate,
+            gain: config.mag_gain,
+            mode: config.mag_mode,
+            last_sample: None,
         };
 
         // Prépare le module à être utilisé
@@ -39,95 +58,253 @@ impl HMC8553L {
         Ok(mag)
     }
 
-    pub (crate) fn recalibrate(&mut self, config: Config) {
+    pub (crate) fn recalibrate(&mut self, config: &Config) {
         println!("[HMC8554L] Recalibration ...");
-        self.mag_decl = config.mag_decl;
-        self.hard_cal = config.hard_cal;
-        self.soft_cal = config.soft_cal;
+        self.correction = MagCorrection::new(config);
     }
 
-    fn set_slave(&self, i2c: &mut I2c) -> anyhow::Result<()> {
-        i2c.set_slave_address(registry::HMC8553L_MAG_ADDR);
+    fn set_slave<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        i2c.set_slave_address(registry::HMC8553L_MAG_ADDR)?;
         Ok(())
     }
 
-    /// Initialise rapidement le module avec des valeurs pré-défini
-    fn init_module(&mut self, i2c: &mut I2c) -> anyhow::Result<()> {
+    /// Initialise le module avec les réglages de la configuration
+    fn init_module<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
         println!("[HMC8554L] Initialisation (CONF A) ...");
-
-        // Configuration par défaut pour le HMC8553L
-        i2c.ecriture_word(registry::HMC8553L_CONF_A, 0x10)?;
+        i2c.ecriture_field(registry::HMC8553L_CONF_A_MA, self.averaging)?;
+        i2c.ecriture_field(registry::HMC8553L_CONF_A_DO, self.data_rate)?;
+        i2c.ecriture_field(registry::HMC8553L_CONF_A_MS, HmcBias::Normal)?;
 
         println!("[HMC8554L] Initialisation (CONF B) ...");
-        i2c.ecriture_word(registry::HMC8553L_CONF_B, 0x20)?;
+        i2c.ecriture_field(registry::HMC8553L_CONF_B_GN, self.gain)?;
 
-        // Activation de la mesure continue
+        // Mesure continue, ou première mesure unique
         println!("[HMC8554L] Initialisation (MODE) ...");
-        i2c.ecriture_word(registry::HMC8553L_MODE, 0x00)?;
+        i2c.ecriture_field(registry::HMC8553L_MODE_MD, self.mode)?;
 
         println!("[HMC8554L] Fin d'initialisation.");
 
         Ok(())
     }
 
-    /// Récupére la valeur en X (RAW)
-    fn get_mag_x_raw(&self, i2c: &mut I2c) -> anyhow::Result<i16> {
-        let mag_x_h = i2c.lecture_word(registry::HMC8553L_X_H)?;
-        let mag_x_l = i2c.lecture_word(registry::HMC8553L_X_L)?;
-        Ok(((mag_x_h as i16) << 8) | mag_x_l as i16)
+    /// Intervalle entre deux mesures en mode continu
+    fn output_period(&self) -> Duration {
+        let hz = match self.data_rate {
+            HmcDataRate::Hz0_75 => 0.75,
+            HmcDataRate::Hz1_5 => 1.5,
+            HmcDataRate::Hz3 => 3.0,
+            HmcDataRate::Hz7_5 => 7.5,
+            HmcDataRate::Hz15 => 15.0,
+            HmcDataRate::Hz30 => 30.0,
+            HmcDataRate::Hz75 => 75.0,
+        };
+        Duration::from_secs_f32(1.0 / hz)
     }
 
-    /// Récupére la valeur en Y
-    fn get_mag_y_raw(&self, i2c: &mut I2c) -> anyhow::Result<i16> {
-        let mag_y_h = i2c.lecture_word(registry::HMC8553L_Y_H)?;
-        let mag_y_l = i2c.lecture_word(registry::HMC8553L_Y_L)?;
-        Ok(((mag_y_h as i16) << 8) | mag_y_l as i16)
+    /// Relis la configuration appliquée par `init_module`
+    fn is_configured<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool> {
+        let conf_a = i2c.lecture_reg(registry::HMC8553L_CONF_A)?;
+        let conf_b = i2c.lecture_reg(registry::HMC8553L_CONF_B)?;
+        let mode = i2c.lecture_field(registry::HMC8553L_MODE_MD)?;
+
+        // Au démarrage le module repasse en mode "mesure unique", puis "Idle" une fois la mesure faite
+        let mode_ok = match self.mode {
+            HmcMode::Single => mode != HmcMode::Continuous,
+            _ => mode == self.mode,
+        };
+
+        Ok(registry::HMC8553L_CONF_A_MA.decode(conf_a) == Some(self.averaging)
+            && registry::HMC8553L_CONF_A_DO.decode(conf_a) == Some(self.data_rate)
+            && registry::HMC8553L_CONF_A_MS.decode(conf_a) == Some(HmcBias::Normal)
+            && registry::HMC8553L_CONF_B_GN.decode(conf_b) == Some(self.gain)
+            && mode_ok)
     }
 
-    /// Récupére la valeur en Z
-    fn get_mag_z_raw(&self, i2c: &mut I2c) -> anyhow::Result<i16> {
-        let mag_z_h = i2c.lecture_word(registry::HMC8553L_Z_H)?;
-        let mag_z_l = i2c.lecture_word(registry::HMC8553L_Z_L)?;
-        Ok(((mag_z_h as i16) << 8) | mag_z_l as i16)
+    /// Vérifie que le module répond toujours et n'a pas été réinitialisé (perte d'alimentation, ...)
+    /// Relance l'initialisation si la configuration relue ne correspond plus. Retourne vrai si c'est le cas.
+    pub (crate) fn check_and_recover<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<bool> {
+        self.set_slave(i2c)?;
+
+        let id = i2c.lecture_words::<3>(registry::HMC8553L_ID_A)?;
+        if id != registry::HMC8553L_ID {
+            return Err(anyhow!("[HMC8554L] Capteur non reconnu (ID: {:02x?})", id));
+        }
+
+        if self.is_configured(i2c)? {
+            return Ok(false);
+        }
+
+        println!("[HMC8554L] Configuration perdue, réinitialisation du module ...");
+        self.init_module(i2c)?;
+        Ok(true)
     }
 
-    /// Récupére les données raw
-    pub (crate) fn get_mag_axes_raw(&self, i2c: &mut I2c) -> anyhow::Result<Vector3<i16>> {
+    /// Récupére les données raw, `None` si aucune nouvelle mesure n'est prête (bit RDY, fréquence des mesures)
+    /// Un axe saturé retourne l'erreur `MagOverflow`.
+    pub (crate) fn get_mag_axes_raw<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<Option<Vector3<i16>>> {
+        // Mode continu : RDY reste à 1 entre deux mesures, la mesure suivante arrive à la fréquence configurée
+        if self.mode == HmcMode::Continuous && self.last_sample.is_some_and(|last| last.elapsed() < self.output_period()) {
+            return Ok(None);
+        }
+
         // Défini mon capteur sur le bus I2C
         self.set_slave(i2c)?;
 
-        // Récupére les valeurs RAW.
-        let raw_x = self.get_mag_x_raw(i2c)?;
-        let raw_y = self.get_mag_y_raw(i2c)?;
-        let raw_z = self.get_mag_z_raw(i2c)?;
+        // Registres en cours d'écriture par le module
+        if !i2c.lecture_field(registry::HMC8553L_STATUS_RDY)? {
+            return Ok(None);
+        }
+
+        // Récupére les valeurs RAW en une seule lecture
+        let words = i2c.lecture_i16_be::<3>(registry::HMC8553L_X_H)?;
+        self.last_sample = Some(Instant::now());
+
+        // Mesure suivante en mode "mesure unique"
+        if self.mode == HmcMode::Single {
+            i2c.ecriture_field(registry::HMC8553L_MODE_MD, HmcMode::Single)?;
+        }
+
+        let raw = self.raw_from_words(words);
+        if self.is_overflow(raw) {
+            return Err(MagOverflow.into());
+        }
 
-        Ok(Vector3::new(raw_x, raw_y, raw_z))
+        Ok(Some(raw))
     }
 
     /// Récupére le heading
-    pub (crate) fn get_heading(&self, i2c: &mut I2c) -> anyhow::Result<f32> {
-        // Défini mon capteur sur le bus I2C
-        self.set_slave(i2c)?;
+    pub (crate) fn get_heading<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<Option<f32>> {
+        let raw = self.get_mag_axes_raw(i2c)?;
+        Ok(raw.map(|raw| self.heading_from_raw(raw)))
+    }
+}
 
-        // Récupération des données brut
-        let raw_x = self.get_mag_x_raw(i2c)? as f32;
-        let raw_y = self.get_mag_y_raw(i2c)? as f32;
-        let raw_z = self.get_mag_z_raw(i2c)? as f32;
-
-        // Correction "Hard Iron" & "Soft Iron"
-        let hard_mag = Matrix1x3::new(
-            raw_x - self.hard_cal.x,
-            raw_y - self.hard_cal.y,
-            raw_z - self.hard_cal.z,
-        );
-        let corrected_mag = hard_mag * self.soft_cal;
-
-        // Calcul du heading, prend en compte la déclinaison magnétique
-        let mut heading = (-((corrected_mag.x.atan2(corrected_mag.y) * (180.0 / PI)) + self.mag_decl)) + 180.0;
-        if (heading < 0.0) {
-            heading = heading + 360.0;
+impl MagDriver for HMC8553L {
+    fn correction(&self) -> &MagCorrection {
+        &self.correction
+    }
+
+    fn recalibrate(&mut self, config: &Config) {
+        HMC8553L::recalibrate(self, config)
+    }
+
+    fn get_mag_axes_raw(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<Option<Vector3<i16>>> {
+        HMC8553L::get_mag_axes_raw(self, &mut i2c)
+    }
+
+    fn check_and_recover(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<bool> {
+        HMC8553L::check_and_recover(self, &mut i2c)
+    }
+
+    /// Lecture des mesures par le maître I2C de l'IMU, quand le module est sur son bus auxiliaire
+    fn aux_magnetometer(&self) -> AuxMagnetometer {
+        AuxMagnetometer {
+            addr: registry::HMC8553L_MAG_ADDR as u8,
+            reg: registry::HMC8553L_X_H,
         }
+    }
+
+    /// Remets dans l'ordre X, Y, Z les valeurs lues à partir de X_H (ordre des registres : X, Z, Y)
+    fn raw_from_words(&self, words: [i16; 3]) -> Vector3<i16> {
+        let [raw_x, raw_z, raw_y] = words;
+        Vector3::new(raw_x, raw_y, raw_z)
+    }
+
+    /// Sensibilité du gain configuré
+    fn lsb_per_gauss(&self) -> f32 {
+        match self.gain {
+            HmcGain::G0_88 => 1370.0,
+            HmcGain::G1_3 => 1090.0,
+            HmcGain::G1_9 => 820.0,
+            HmcGain::G2_5 => 660.0,
+            HmcGain::G4_0 => 440.0,
+            HmcGain::G4_7 => 390.0,
+            HmcGain::G5_6 => 330.0,
+            HmcGain::G8_1 => 230.0,
+        }
+    }
+
+    /// Un axe saturé vaut -4096
+    fn is_overflow(&self, raw: Vector3<i16>) -> bool {
+        raw.iter().any(|&axis| axis == registry::HMC8553L_OVERFLOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+
+    fn hmc_bus(x: i16, y: i16, z: i16) -> SimBus {
+        let mut device = SimDevice::new();
+        let data: Vec<u8> = [x, z, y].iter().flat_map(|v| v.to_be_bytes()).collect();
+        device.set_registers(registry::HMC8553L_X_H, &data);
+        device.set_registers(registry::HMC8553L_STATUS.address(), &[0x01]);
+        device.set_registers(registry::HMC8553L_ID_A, &registry::HMC8553L_ID);
+
+        let mut bus = SimBus::new();
+        bus.attach(registry::HMC8553L_MAG_ADDR, device);
+        bus
+    }
+
+    #[test]
+    fn axes_in_register_order() {
+        let mut bus = hmc_bus(100, -200, 300);
+        let mut mag = HMC8553L::new(&mut bus, Config::new()).unwrap();
+
+        assert_eq!(mag.get_mag_axes_raw(&mut bus).unwrap(), Some(Vector3::new(100, -200, 300)));
+
+        // RDY reste à 1 en mode continu : la même mesure n'est pas relue avant la suivante (15 Hz)
+        assert_eq!(mag.get_mag_axes_raw(&mut bus).unwrap(), None);
+        sleep(Duration::from_millis(70));
+        assert_eq!(mag.get_mag_axes_raw(&mut bus).unwrap(), Some(Vector3::new(100, -200, 300)));
+
+        // Configuration par défaut : 15 Hz, gain 1.3 Ga, mesure continue
+        let device = bus.device(registry::HMC8553L_MAG_ADDR).unwrap();
+        assert_eq!(device.registers(registry::HMC8553L_CONF_A.address(), 3), &[0x10, 0x20, 0x00]);
+    }
+
+    #[test]
+    fn settings_data_ready_and_overflow() {
+        let mut config = Config::new();
+        config.mag_averaging = HmcSamples::S8;
+        config.mag_data_rate = HmcDataRate::Hz75;
+        config.mag_gain = HmcGain::G4_7;
+        config.mag_mode = HmcMode::Single;
+
+        // Personne ne relancerait la mesure unique sur le bus auxiliaire de l'IMU
+        let mut bus = hmc_bus(390, 0, 0);
+        let aux_config = Config { mag_aux_bus: true, ..config.clone() };
+        assert!(HMC8553L::new(&mut bus, aux_config).is_err());
+
+        let mut mag = HMC8553L::new(&mut bus, config).unwrap();
+        assert_eq!(mag.lsb_per_gauss(), 390.0);
+
+        let device = bus.device(registry::HMC8553L_MAG_ADDR).unwrap();
+        assert_eq!(device.registers(registry::HMC8553L_CONF_A.address(), 3), &[0x78, 0xA0, 0x01]);
+
+        // Perte d'alimentation : valeurs de démarrage, la configuration est réécrite
+        device.set_registers(registry::HMC8553L_CONF_A.address(), &[0x10, 0x20, 0x01]);
+        assert!(mag.check_and_recover(&mut bus).unwrap());
+        let device = bus.device(registry::HMC8553L_MAG_ADDR).unwrap();
+        assert_eq!(device.registers(registry::HMC8553L_CONF_A.address(), 3), &[0x78, 0xA0, 0x01]);
+
+        // Mesure unique terminée : le module repasse en "Idle", la lecture relance une mesure
+        device.set_registers(registry::HMC8553L_MODE.address(), &[0x02]);
+        assert!(!mag.check_and_recover(&mut bus).unwrap());
+        assert!(mag.get_mag_axes_raw(&mut bus).unwrap().is_some());
+        let device = bus.device(registry::HMC8553L_MAG_ADDR).unwrap();
+        assert_eq!(device.registers(registry::HMC8553L_MODE.address(), 1), &[0x01]);
+
+        // Mesure en cours d'écriture (RDY à 0)
+        device.set_registers(registry::HMC8553L_STATUS.address(), &[0x00]);
+        assert_eq!(mag.get_mag_axes_raw(&mut bus).unwrap(), None);
 
-        Ok(heading)
+        // Axe Y saturé
+        let device = bus.device(registry::HMC8553L_MAG_ADDR).unwrap();
+        device.set_registers(registry::HMC8553L_STATUS.address(), &[0x01]);
+        device.set_registers(registry::HMC8553L_Y_H, &registry::HMC8553L_OVERFLOW.to_be_bytes());
+        assert!(mag.get_mag_axes_raw(&mut bus).unwrap_err().is::<MagOverflow>());
+        assert!(mag.is_overflow(mag.raw_from_words([0, 0, registry::HMC8553L_OVERFLOW])));
     }
 }
@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::sensors::mag::wmm::EarthField;
+
+/// Ecart relatif toléré entre l'intensité mesurée et l'intensité attendue
+const INTENSITY_TOLERANCE: f32 = 0.15;
+
+/// Ecart toléré (en degrés) entre l'inclinaison mesurée et l'inclinaison attendue
+const INCLINATION_TOLERANCE: f32 = 10.0;
+
+/// Ecarts considérés comme du bruit de mesure : la confiance reste maximale en dessous
+const INTENSITY_DEADBAND: f32 = 0.05;
+const INCLINATION_DEADBAND: f32 = 3.0;
+
+/// Intensités (en Gauss) plausibles pour le champ terrestre, seules à servir de première référence
+const EARTH_INTENSITY_RANGE: std::ops::RangeInclusive<f32> = 0.2..=0.7;
+
+/// Durée pendant laquelle le magnétomètre reste ignoré après la dernière mesure perturbée
+const INTERFERENCE_HOLD: Duration = Duration::from_millis(500);
+
+/// Poids d'une mesure non perturbée dans la référence apprise (sans position GPS)
+const BASELINE_LEARN_RATE: f32 = 0.01;
+
+/// Détection des perturbations magnétiques (courants du moteur, structures métalliques, ...)
+/// L'intensité et l'inclinaison du champ calibré sont comparées au champ terrestre attendu (WMM à la
+/// position GPS), ou à défaut à une référence apprise sur les mesures non perturbées.
+pub(crate) struct InterferenceDetector {
+    expected: Option<EarthField>,
+    /// Intensité (en Gauss) et inclinaison (en degrés) apprises
+    baseline: Option<(f32, f32)>,
+    confidence: f32,
+    last_interference: Option<Instant>,
+}
+
+impl InterferenceDetector {
+    pub(crate) fn new() -> Self {
+        Self {
+            expected: None,
+            baseline: None,
+            confidence: 1.0,
+            last_interference: None,
+        }
+    }
+
+    /// Champ terrestre attendu à la position actuelle
+    pub(crate) fn set_expected(&mut self, expected: EarthField) {
+        self.expected = Some(expected);
+    }
+
+    /// Nouvelle mesure : champ calibré (en Gauss, repère de la voiture), tangage et roulis de l'IMU (en degrés)
+    pub(crate) fn update(&mut self, field: Vector3<f32>, pitch: f32, roll: f32) {
+        let level = UnitQuaternion::from_euler_angles(roll.to_radians(), pitch.to_radians(), 0.0);
+        let field = level.transform_vector(&field);
+
+        // Inclinaison positive vers le bas (axe Z de la voiture vers le haut)
+        let intensity = field.norm();
+        let inclination = (-field.z).atan2(field.xy().norm()).to_degrees();
+
+        let (expected_intensity, expected_inclination) = match (self.expected, self.baseline) {
+            (Some(expected), _) => (expected.intensity, expected.inclination),
+            (None, Some(baseline)) => baseline,
+            (None, None) => {
+                // Pas encore de référence : la première mesure plausible l'initialise
+                if EARTH_INTENSITY_RANGE.contains(&intensity) {
+                    self.baseline = Some((intensity, inclination));
+                    self.confidence = 1.0;
+                } else {
+                    self.confidence = 0.0;
+                    self.last_interference = Some(Instant::now());
+                }
+                return;
+            }
+        };
+
+        // 1 : champ conforme (au bruit près), 0 : écart au-delà de la tolérance
+        let intensity_error = Self::error((intensity / expected_intensity - 1.0).abs(), INTENSITY_DEADBAND, INTENSITY_TOLERANCE);
+        let inclination_error = Self::error((inclination - expected_inclination).abs(), INCLINATION_DEADBAND, INCLINATION_TOLERANCE);
+        self.confidence = (1.0 - intensity_error.max(inclination_error)).clamp(0.0, 1.0);
+
+        if self.confidence == 0.0 {
+            self.last_interference = Some(Instant::now());
+        }
+
+        // La référence n'apprend que des mesures sans aucun doute, une dérive lente ne peut pas l'entraîner
+        if let (None, Some((baseline_intensity, baseline_inclination))) = (self.expected, self.baseline) {
+            if self.confidence >= 1.0 {
+                self.baseline = Some((
+                    baseline_intensity + (intensity - baseline_intensity) * BASELINE_LEARN_RATE,
+                    baseline_inclination + (inclination - baseline_inclination) * BASELINE_LEARN_RATE,
+                ));
+            }
+        }
+    }
+
+    /// Ecart ramené à la tolérance (0 dans la zone de bruit, 1 à la tolérance)
+    fn error(deviation: f32, deadband: f32, tolerance: f32) -> f32 {
+        (deviation - deadband).max(0.0) / (tolerance - deadband)
+    }
+
+    /// Confiance (0 à 1) dans la dernière mesure
+    pub(crate) fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Vrai si le magnétomètre doit être ignoré (perturbation récente)
+    pub(crate) fn is_interference(&self) -> bool {
+        self.last_interference.is_some_and(|last| last.elapsed() < INTERFERENCE_HOLD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_compared_to_earth_model() {
+        let mut detector = InterferenceDetector::new();
+        detector.set_expected(EarthField { declination: 0.0, inclination: 60.0, intensity: 0.5 });
+
+        // Champ attendu, voiture à plat orientée au nord
+        let dip = 60.0_f32.to_radians();
+        let earth = Vector3::new(dip.cos(), 0.0, -dip.sin()) * 0.5;
+        detector.update(earth, 0.0, 0.0);
+        assert!(detector.confidence() > 0.99);
+        assert!(!detector.is_interference());
+
+        // Même champ, voiture en montée : l'inclinaison est corrigée par le tangage
+        let attitude = UnitQuaternion::from_euler_angles(0.0, -20.0_f32.to_radians(), 0.0);
+        detector.update(attitude.inverse_transform_vector(&earth), -20.0, 0.0);
+        assert!(detector.confidence() > 0.99);
+
+        // Intensité en hausse de 10 % : confiance réduite, pas encore de perturbation
+        detector.update(earth * 1.1, 0.0, 0.0);
+        assert!((detector.confidence() - 0.5).abs() < 0.01);
+        assert!(!detector.is_interference());
+
+        // Champ du moteur : intensité doublée
+        detector.update(earth * 2.0, 0.0, 0.0);
+        assert_eq!(detector.confidence(), 0.0);
+        assert!(detector.is_interference());
+
+        // Le magnétomètre reste ignoré quelques instants après la perturbation
+        detector.update(earth, 0.0, 0.0);
+        assert!(detector.confidence() > 0.99);
+        assert!(detector.is_interference());
+    }
+
+    #[test]
+    fn baseline_learned_from_plausible_field() {
+        let mut detector = InterferenceDetector::new();
+        let dip = 60.0_f32.to_radians();
+        let earth = Vector3::new(dip.cos(), 0.0, -dip.sin()) * 0.5;
+
+        // Démarrage moteur en marche : le champ mesuré ne peut pas être le champ terrestre
+        detector.update(earth * 3.0, 0.0, 0.0);
+        assert_eq!(detector.confidence(), 0.0);
+        assert!(detector.baseline.is_none());
+
+        detector.update(earth, 0.0, 0.0);
+        assert_eq!(detector.confidence(), 1.0);
+        let (intensity, inclination) = detector.baseline.unwrap();
+        assert!((intensity - 0.5).abs() < 1e-4 && (inclination - 60.0).abs() < 1e-3);
+
+        // Mesure douteuse : la référence ne bouge pas
+        detector.update(earth * 1.1, 0.0, 0.0);
+        assert!(detector.confidence() < 1.0);
+        assert_eq!(detector.baseline, Some((intensity, inclination)));
+
+        // Mesure dans le bruit : la référence la suit lentement
+        detector.update(earth * 1.02, 0.0, 0.0);
+        assert_eq!(detector.confidence(), 1.0);
+        assert!((detector.baseline.unwrap().0 - 0.5001).abs() < 1e-5);
+    }
+}
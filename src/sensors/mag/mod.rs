@@ -2,4 +2,14 @@
 mod registry;
 
 #[cfg(feature = "real-sensors")]
-pub(crate) mod hmc8553l;
\ No newline at end of file
+pub(crate) mod hmc8553l;
+#[cfg(feature = "real-sensors")]
+pub(crate) mod qmc5883l;
+#[cfg(feature = "real-sensors")]
+pub(crate) mod driver;
+pub(crate) mod calibration;
+pub(crate) mod settings;
+pub(crate) mod wmm;
+pub(crate) mod interference;
+
+pub(crate) use settings::{HmcDataRate, HmcGain, HmcMode, HmcSamples, MagChip};
\ No newline at end of file
@@ -0,0 +1,171 @@
+use anyhow::anyhow;
+use nalgebra::Vector3;
+
+use crate::config::Config;
+use crate::i2c::{I2CBit, I2CBus};
+use crate::sensors::imu::driver::AuxMagnetometer;
+use crate::sensors::mag::calibration::MagCorrection;
+use crate::sensors::mag::driver::{MagDriver, MagOverflow};
+use crate::sensors::mag::registry;
+use crate::sensors::mag::registry::{QmcDataRate, QmcMode, QmcOversampling, QmcRange};
+
+/// Configuration appliquée par `init_module` (mesure continue, 50 Hz, ±8 G, sur-échantillonnage 512)
+const MODE: QmcMode = QmcMode::Continuous;
+const DATA_RATE: QmcDataRate = QmcDataRate::Hz50;
+const RANGE: QmcRange = QmcRange::G8;
+const OVERSAMPLING: QmcOversampling = QmcOversampling::Osr512;
+
+pub(crate) struct QMC5883L {
+    correction: MagCorrection,
+}
+
+impl QMC5883L {
+    /// Constructeur
+    pub(crate) fn new<B: I2CBus>(i2c: &mut B, config: &Config) -> anyhow::Result<Self> {
+        let mut mag = Self {
+            correction: MagCorrection::new(config),
+        };
+
+        mag.set_slave(i2c)?;
+        mag.whoami(i2c)?;
+        mag.init_module(i2c)?;
+
+        Ok(mag)
+    }
+
+    fn set_slave<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        i2c.set_slave_address(registry::QMC5883L_MAG_ADDR)?;
+        Ok(())
+    }
+
+    /// Qui suis-je ?
+    fn whoami<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<()> {
+        let id = i2c.lecture_reg(registry::QMC5883L_CHIP_ID)? as u8;
+        if id != registry::QMC5883L_ID {
+            return Err(anyhow!("[QMC5883L] Capteur non reconnu (ID: {:#04x})", id));
+        }
+        Ok(())
+    }
+
+    /// Initialise le module en mesure continue
+    fn init_module<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<()> {
+        println!("[QMC5883L] Initialisation ...");
+
+        // Période de SET/RESET recommandée par le datasheet
+        i2c.ecriture_reg(registry::QMC5883L_SETRESET, registry::QMC5883L_SETRESET_PERIOD)?;
+
+        i2c.ecriture_field(registry::QMC5883L_SETTINGS_OSR, OVERSAMPLING)?;
+        i2c.ecriture_field(registry::QMC5883L_SETTINGS_RNG, RANGE)?;
+        i2c.ecriture_field(registry::QMC5883L_SETTINGS_ODR, DATA_RATE)?;
+        i2c.ecriture_field(registry::QMC5883L_SETTINGS_MODE, MODE)
+    }
+
+    /// Relis la configuration appliquée par `init_module`
+    fn is_configured<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<bool> {
+        let settings = i2c.lecture_reg(registry::QMC5883L_SETTINGS)?;
+        Ok(registry::QMC5883L_SETTINGS_MODE.decode(settings) == Some(MODE)
+            && registry::QMC5883L_SETTINGS_ODR.decode(settings) == Some(DATA_RATE)
+            && registry::QMC5883L_SETTINGS_RNG.decode(settings) == Some(RANGE)
+            && registry::QMC5883L_SETTINGS_OSR.decode(settings) == Some(OVERSAMPLING))
+    }
+
+    /// Récupére les données raw (X, Y, Z, octet de poids faible en premier)
+    pub(crate) fn get_mag_axes_raw<B: I2CBus>(&self, i2c: &mut B) -> anyhow::Result<Option<Vector3<i16>>> {
+        self.set_slave(i2c)?;
+
+        // X_L .. Z_H puis le statut, en une seule lecture
+        let data = i2c.lecture_words::<7>(registry::QMC5883L_X_L)?;
+        let status = data[6] as u16;
+        if registry::QMC5883L_INFO_OVL.decode(status) == Some(true) {
+            return Err(MagOverflow.into());
+        }
+
+        let axis = |n: usize| i16::from_le_bytes([data[2 * n], data[2 * n + 1]]);
+        Ok(Some(Vector3::new(axis(0), axis(1), axis(2))))
+    }
+
+    /// Vérifie que le module répond toujours et n'a pas été réinitialisé (perte d'alimentation, ...)
+    /// Relance l'initialisation si la configuration relue ne correspond plus. Retourne vrai si c'est le cas.
+    pub(crate) fn check_and_recover<B: I2CBus>(&mut self, i2c: &mut B) -> anyhow::Result<bool> {
+        self.set_slave(i2c)?;
+        self.whoami(i2c)?;
+
+        if self.is_configured(i2c)? {
+            return Ok(false);
+        }
+
+        println!("[QMC5883L] Configuration perdue, réinitialisation du module ...");
+        self.init_module(i2c)?;
+        Ok(true)
+    }
+}
+
+impl MagDriver for QMC5883L {
+    fn correction(&self) -> &MagCorrection {
+        &self.correction
+    }
+
+    fn recalibrate(&mut self, config: &Config) {
+        println!("[QMC5883L] Recalibration ...");
+        self.correction = MagCorrection::new(config);
+    }
+
+    fn get_mag_axes_raw(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<Option<Vector3<i16>>> {
+        QMC5883L::get_mag_axes_raw(self, &mut i2c)
+    }
+
+    fn check_and_recover(&mut self, mut i2c: &mut dyn I2CBus) -> anyhow::Result<bool> {
+        QMC5883L::check_and_recover(self, &mut i2c)
+    }
+
+    fn aux_magnetometer(&self) -> AuxMagnetometer {
+        AuxMagnetometer {
+            addr: registry::QMC5883L_MAG_ADDR as u8,
+            reg: registry::QMC5883L_X_L,
+        }
+    }
+
+    /// Le maître I2C de l'IMU lit les octets dans l'ordre, le QMC5883L envoie le poids faible en premier
+    fn raw_from_words(&self, words: [i16; 3]) -> Vector3<i16> {
+        let [x, y, z] = words.map(i16::swap_bytes);
+        Vector3::new(x, y, z)
+    }
+
+    fn lsb_per_gauss(&self) -> f32 {
+        match RANGE {
+            QmcRange::G2 => 12000.0,
+            QmcRange::G8 => 3000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::sim::{SimBus, SimDevice};
+
+    #[test]
+    fn little_endian_axes_and_overflow() {
+        let mut device = SimDevice::new();
+        device.set_registers(registry::QMC5883L_CHIP_ID.address(), &[registry::QMC5883L_ID]);
+        let mut data: Vec<u8> = [100i16, -200, 300].iter().flat_map(|v| v.to_le_bytes()).collect();
+        data.push(0x01);
+        device.set_registers(registry::QMC5883L_X_L, &data);
+
+        let mut bus = SimBus::new();
+        bus.attach(registry::QMC5883L_MAG_ADDR, device);
+        let mut mag = QMC5883L::new(&mut bus, &Config::new()).unwrap();
+
+        assert_eq!(mag.get_mag_axes_raw(&mut bus).unwrap(), Some(Vector3::new(100, -200, 300)));
+        assert_eq!(mag.raw_from_words([100i16, -200, 300].map(i16::swap_bytes)), Vector3::new(100, -200, 300));
+        assert!(!mag.check_and_recover(&mut bus).unwrap());
+
+        // Mesure continue, 50 Hz, ±8 G, sur-échantillonnage 512
+        let device = bus.device(registry::QMC5883L_MAG_ADDR).unwrap();
+        assert_eq!(device.registers(registry::QMC5883L_SETTINGS.address(), 1), &[0x15]);
+
+        // Saturation (OVL)
+        device.set_registers(registry::QMC5883L_INFO.address(), &[0x03]);
+        assert!(mag.get_mag_axes_raw(&mut bus).unwrap_err().is::<MagOverflow>());
+    }
+}
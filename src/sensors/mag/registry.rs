@@ -1,5 +1,8 @@
 #![allow(unused)]
 
+use crate::i2c::field::{field_enum, Field, Register, RO, RW};
+pub use crate::sensors::mag::settings::{HmcDataRate, HmcGain, HmcMode, HmcSamples};
+
 // QMC5883L
 pub const QMC5883L_MAG_ADDR: u16 = 0x0D;
 
@@ -10,32 +13,98 @@ pub const QMC5883L_Y_H: u8 = 0x03;
 pub const QMC5883L_Z_L: u8 = 0x04;
 pub const QMC5883L_Z_H: u8 = 0x05;
 
-pub const QMC5883L_INFO: u8 = 0x06;
-pub const QMC5883L_SETTINGS: u8 = 0x09;
-pub const QMC5883L_SETRESET: u8 = 0x0B;
-pub const QMC5883L_CHIP_ID: u8 = 0x0D;
+pub const QMC5883L_INFO: Register<RO> = Register::r8(0x06);
+pub const QMC5883L_SETTINGS: Register<RW> = Register::r8(0x09);
+pub const QMC5883L_SETRESET: Register<RW> = Register::r8(0x0B);
+pub const QMC5883L_CHIP_ID: Register<RO> = Register::r8(0x0D);
+
+pub const QMC5883L_ID: u8 = 0xFF;
+
+/// Période de SET/RESET recommandée
+pub const QMC5883L_SETRESET_PERIOD: u16 = 0x01;
+
+pub const QMC5883L_INFO_DRDY: Field<RO, bool> = Field::bit(QMC5883L_INFO, 0);
+pub const QMC5883L_INFO_OVL: Field<RO, bool> = Field::bit(QMC5883L_INFO, 1);
+pub const QMC5883L_INFO_DOR: Field<RO, bool> = Field::bit(QMC5883L_INFO, 2);
+
+pub const QMC5883L_SETTINGS_MODE: Field<RW, QmcMode> = Field::new(QMC5883L_SETTINGS, 0, 2);
+pub const QMC5883L_SETTINGS_ODR: Field<RW, QmcDataRate> = Field::new(QMC5883L_SETTINGS, 2, 2);
+pub const QMC5883L_SETTINGS_RNG: Field<RW, QmcRange> = Field::new(QMC5883L_SETTINGS, 4, 2);
+pub const QMC5883L_SETTINGS_OSR: Field<RW, QmcOversampling> = Field::new(QMC5883L_SETTINGS, 6, 2);
+
+field_enum! {
+    /// Mode de mesure du QMC5883L
+    pub enum QmcMode {
+        Standby = 0b00,
+        Continuous = 0b01,
+    }
+}
 
-pub const QMC5883L_INFO_DRDY_BIT: u8 = 0;
-pub const QMC5883L_INFO_OVL_BIT: u8 = 1;
-pub const QMC5883L_INFO_DOR_BIT: u8 = 2;
+field_enum! {
+    /// Fréquence des mesures du QMC5883L (en Hz)
+    pub enum QmcDataRate {
+        Hz10 = 0b00,
+        Hz50 = 0b01,
+        Hz100 = 0b10,
+        Hz200 = 0b11,
+    }
+}
 
-pub const QMC5883L_SETTINGS_MODE_BIT: u8 = 0;
-pub const QMC5883L_SETTINGS_ODR_BIT: u8 = 2;
-pub const QMC5883L_SETTINGS_RNG_BIT: u8 = 4;
-pub const QMC5883L_SETTINGS_OSR_BIT: u8 = 6;
-pub const QMC5883L_SETTINGS_SIZE: u8 = 2;
+field_enum! {
+    /// Pleine échelle du QMC5883L (en Gauss)
+    pub enum QmcRange {
+        G2 = 0b00,
+        G8 = 0b01,
+    }
+}
+
+field_enum! {
+    /// Sur-échantillonnage du QMC5883L
+    pub enum QmcOversampling {
+        Osr512 = 0b00,
+        Osr256 = 0b01,
+        Osr128 = 0b10,
+        Osr64 = 0b11,
+    }
+}
 
 
 // HMC8553L
 pub const HMC8553L_MAG_ADDR: u16 = 0x1E;
 
-pub const HMC8553L_CONF_A: u8 = 0x00;
-pub const HMC8553L_CONF_B: u8 = 0x01;
-pub const HMC8553L_MODE: u8 = 0x02;
+pub const HMC8553L_CONF_A: Register<RW> = Register::r8(0x00);
+pub const HMC8553L_CONF_B: Register<RW> = Register::r8(0x01);
+pub const HMC8553L_MODE: Register<RW> = Register::r8(0x02);
 pub const HMC8553L_X_H: u8 = 0x03;
 pub const HMC8553L_X_L: u8 = 0x04;
 pub const HMC8553L_Z_H: u8 = 0x05;
 pub const HMC8553L_Z_L: u8 = 0x06;
 pub const HMC8553L_Y_H: u8 = 0x07;
 pub const HMC8553L_Y_L: u8 = 0x08;
+pub const HMC8553L_STATUS: Register<RO> = Register::r8(0x09);
+pub const HMC8553L_ID_A: u8 = 0x0A;
+pub const HMC8553L_ID_B: u8 = 0x0B;
+pub const HMC8553L_ID_C: u8 = 0x0C;
+
+pub const HMC8553L_ID: [u8; 3] = *b"H43";
+
+/// Valeur d'un axe saturé (champ hors de la plage du gain)
+pub const HMC8553L_OVERFLOW: i16 = -4096;
+
+pub const HMC8553L_CONF_A_MA: Field<RW, HmcSamples> = Field::new(HMC8553L_CONF_A, 5, 2);
+pub const HMC8553L_CONF_A_DO: Field<RW, HmcDataRate> = Field::new(HMC8553L_CONF_A, 2, 3);
+pub const HMC8553L_CONF_A_MS: Field<RW, HmcBias> = Field::new(HMC8553L_CONF_A, 0, 2);
+pub const HMC8553L_CONF_B_GN: Field<RW, HmcGain> = Field::new(HMC8553L_CONF_B, 5, 3);
+pub const HMC8553L_MODE_MD: Field<RW, HmcMode> = Field::new(HMC8553L_MODE, 0, 2);
+pub const HMC8553L_STATUS_RDY: Field<RO, bool> = Field::bit(HMC8553L_STATUS, 0);
+pub const HMC8553L_STATUS_LOCK: Field<RO, bool> = Field::bit(HMC8553L_STATUS, 1);
+
+field_enum! {
+    /// Polarisation de la mesure (auto-test)
+    pub enum HmcBias {
+        Normal = 0b00,
+        Positive = 0b01,
+        Negative = 0b10,
+    }
+}
 
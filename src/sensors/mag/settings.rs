@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i2c::field::field_enum;
+
+/// Magnétomètres supportés
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum MagChip {
+    Hmc5883l,
+    /// Souvent vendu comme "HMC5883L" sur les modules bon marché
+    Qmc5883l,
+}
+
+field_enum! {
+    /// Nombre d'échantillons moyennés par mesure
+    #[derive(Serialize, Deserialize)]
+    pub enum HmcSamples {
+        S1 = 0b00,
+        S2 = 0b01,
+        S4 = 0b10,
+        S8 = 0b11,
+    }
+}
+
+field_enum! {
+    /// Fréquence des mesures en mode continu (en Hz)
+    #[derive(Serialize, Deserialize)]
+    pub enum HmcDataRate {
+        Hz0_75 = 0b000,
+        Hz1_5 = 0b001,
+        Hz3 = 0b010,
+        Hz7_5 = 0b011,
+        Hz15 = 0b100,
+        Hz30 = 0b101,
+        Hz75 = 0b110,
+    }
+}
+
+field_enum! {
+    /// Gain du capteur (pleine échelle en Gauss)
+    #[derive(Serialize, Deserialize)]
+    pub enum HmcGain {
+        G0_88 = 0b000,
+        G1_3 = 0b001,
+        G1_9 = 0b010,
+        G2_5 = 0b011,
+        G4_0 = 0b100,
+        G4_7 = 0b101,
+        G5_6 = 0b110,
+        G8_1 = 0b111,
+    }
+}
+
+field_enum! {
+    /// Mode de mesure
+    #[derive(Serialize, Deserialize)]
+    pub enum HmcMode {
+        Continuous = 0b00,
+        Single = 0b01,
+        Idle = 0b10,
+    }
+}
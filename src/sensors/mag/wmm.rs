@@ -0,0 +1,225 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Année de référence des coefficients (WMM2025, valable de 2025 à 2030)
+const WMM_EPOCH: f64 = 2025.0;
+
+/// Fin de validité des coefficients
+const WMM_END: f64 = 2030.0;
+
+/// Rayon de référence du modèle (en km)
+const WMM_RADIUS: f64 = 6371.2;
+
+/// Ellipsoïde WGS84 : demi-grand axe (en km) et aplatissement
+const WGS84_A: f64 = 6378.137;
+const WGS84_F: f64 = 1.0 / 298.257223563;
+
+/// Conversion des nT du modèle en Gauss
+const NT_PER_GAUSS: f64 = 100_000.0;
+
+/// Degré maximal du modèle
+const WMM_DEGREE: usize = 12;
+
+/// Coefficients de Gauss du World Magnetic Model 2025 (NOAA / BGS)
+/// (n, m, g, h en nT, variation séculaire de g et h en nT/an)
+#[rustfmt::skip]
+const WMM_COEFFICIENTS: [(usize, usize, f64, f64, f64, f64); 90] = [
+    (1, 0, -29351.8, 0.0, 12.0, 0.0),
+    (1, 1, -1410.8, 4545.4, 9.7, -21.5),
+    (2, 0, -2556.6, 0.0, -11.6, 0.0),
+    (2, 1, 2951.1, -3133.6, -5.2, -27.7),
+    (2, 2, 1649.3, -815.1, -8.0, -12.1),
+    (3, 0, 1361.0, 0.0, -1.3, 0.0),
+    (3, 1, -2404.1, -56.6, -4.2, 4.0),
+    (3, 2, 1243.8, 237.5, 0.4, -0.3),
+    (3, 3, 453.6, -549.5, -15.6, -4.1),
+    (4, 0, 895.0, 0.0, -1.6, 0.0),
+    (4, 1, 799.5, 278.6, -2.4, -1.1),
+    (4, 2, 55.7, -133.9, -6.0, 4.1),
+    (4, 3, -281.1, 212.0, 5.6, 1.6),
+    (4, 4, 12.1, -375.6, -7.0, -4.4),
+    (5, 0, -233.2, 0.0, 0.6, 0.0),
+    (5, 1, 368.9, 45.4, 1.4, -0.5),
+    (5, 2, 187.2, 220.2, 0.0, 2.2),
+    (5, 3, -138.7, -122.9, 0.6, 0.4),
+    (5, 4, -142.0, 43.0, 2.2, 1.7),
+    (5, 5, 20.9, 106.1, 0.9, 1.9),
+    (6, 0, 64.4, 0.0, -0.2, 0.0),
+    (6, 1, 63.8, -18.4, -0.4, 0.3),
+    (6, 2, 76.9, 16.8, 0.9, -1.6),
+    (6, 3, -115.7, 48.8, 1.2, -0.4),
+    (6, 4, -40.9, -59.8, -0.9, 0.9),
+    (6, 5, 14.9, 10.9, 0.3, 0.7),
+    (6, 6, -60.7, 72.7, 0.9, 0.9),
+    (7, 0, 79.5, 0.0, -0.0, 0.0),
+    (7, 1, -77.0, -48.9, -0.1, 0.6),
+    (7, 2, -8.8, -14.4, -0.1, 0.5),
+    (7, 3, 59.3, -1.0, 0.5, -0.8),
+    (7, 4, 15.8, 23.4, -0.1, 0.0),
+    (7, 5, 2.5, -7.4, -0.8, -1.0),
+    (7, 6, -11.1, -25.1, -0.8, 0.6),
+    (7, 7, 14.2, -2.3, 0.8, -0.2),
+    (8, 0, 23.2, 0.0, -0.1, 0.0),
+    (8, 1, 10.8, 7.1, 0.2, -0.2),
+    (8, 2, -17.5, -12.6, 0.0, 0.5),
+    (8, 3, 2.0, 11.4, 0.5, -0.4),
+    (8, 4, -21.7, -9.7, -0.1, 0.4),
+    (8, 5, 16.9, 12.7, 0.3, -0.5),
+    (8, 6, 15.0, 0.7, 0.2, -0.6),
+    (8, 7, -16.8, -5.2, -0.0, 0.3),
+    (8, 8, 0.9, 3.9, 0.2, 0.2),
+    (9, 0, 4.6, 0.0, -0.0, 0.0),
+    (9, 1, 7.8, -24.8, -0.1, -0.3),
+    (9, 2, 3.0, 12.2, 0.1, 0.3),
+    (9, 3, -0.2, 8.3, 0.3, -0.3),
+    (9, 4, -2.5, -3.4, -0.0, 0.1),
+    (9, 5, -13.1, -5.3, 0.0, 0.2),
+    (9, 6, 2.4, 7.2, 0.3, -0.1),
+    (9, 7, 8.6, -0.6, -0.1, -0.2),
+    (9, 8, -8.7, 0.8, 0.1, 0.4),
+    (9, 9, -12.9, 10.0, -0.1, 0.1),
+    (10, 0, -1.3, 0.0, 0.1, 0.0),
+    (10, 1, -6.4, 3.3, 0.0, 0.0),
+    (10, 2, 0.2, 0.0, 0.1, -0.0),
+    (10, 3, 2.0, 2.4, 0.1, -0.2),
+    (10, 4, -1.0, 5.3, -0.0, 0.1),
+    (10, 5, -0.6, -9.1, -0.3, -0.1),
+    (10, 6, -0.9, 0.4, 0.0, 0.1),
+    (10, 7, 1.5, -4.2, -0.1, 0.0),
+    (10, 8, 0.9, -3.8, -0.1, -0.1),
+    (10, 9, -2.7, 0.9, -0.0, 0.2),
+    (10, 10, -3.9, -9.1, -0.0, -0.0),
+    (11, 0, 2.9, 0.0, 0.0, 0.0),
+    (11, 1, -1.5, 0.0, -0.0, -0.0),
+    (11, 2, -2.5, 2.9, 0.0, 0.1),
+    (11, 3, 2.4, -0.6, 0.0, -0.0),
+    (11, 4, -0.6, 0.2, 0.0, 0.1),
+    (11, 5, -0.1, 0.5, -0.1, -0.0),
+    (11, 6, -0.6, -0.3, 0.0, -0.0),
+    (11, 7, -0.1, -1.2, -0.0, 0.1),
+    (11, 8, 1.1, -1.7, -0.1, -0.0),
+    (11, 9, -1.0, -2.9, -0.1, 0.0),
+    (11, 10, -0.2, -1.8, -0.1, 0.0),
+    (11, 11, 2.6, -2.3, -0.1, 0.0),
+    (12, 0, -2.0, 0.0, 0.0, 0.0),
+    (12, 1, -0.2, -1.3, 0.0, -0.0),
+    (12, 2, 0.3, 0.7, -0.0, 0.0),
+    (12, 3, 1.2, 1.0, -0.0, -0.1),
+    (12, 4, -1.3, -1.4, -0.0, 0.1),
+    (12, 5, 0.6, -0.0, -0.0, -0.0),
+    (12, 6, 0.6, 0.6, 0.1, -0.0),
+    (12, 7, 0.5, -0.1, -0.0, -0.0),
+    (12, 8, -0.1, 0.8, 0.0, 0.0),
+    (12, 9, -0.4, 0.1, 0.0, -0.0),
+    (12, 10, -0.2, -1.0, -0.1, -0.0),
+    (12, 11, -1.3, 0.1, -0.0, 0.0),
+    (12, 12, -0.7, 0.2, -0.1, -0.1),
+];
+
+/// Champ magnétique terrestre attendu à une position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct EarthField {
+    /// Déclinaison (en degrés, positive vers l'est)
+    pub declination: f32,
+    /// Inclinaison (en degrés, positive vers le bas)
+    pub inclination: f32,
+    /// Intensité totale (en Gauss)
+    pub intensity: f32,
+}
+
+/// Champ magnétique terrestre au niveau de la mer
+/// `latitude` et `longitude` : position GPS (en degrés), `year` : date en années décimales (ex: 2025.5),
+/// ramenée dans la période de validité du modèle
+pub(crate) fn field(latitude: f64, longitude: f64, year: f64) -> EarthField {
+    // Position géocentrique (sphérique) à partir de la position géodésique (WGS84)
+    let (latitude, longitude) = (latitude.to_radians(), longitude.to_radians());
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let n = WGS84_A / (1.0 - e2 * latitude.sin().powi(2)).sqrt();
+    let (p, z) = (n * latitude.cos(), n * (1.0 - e2) * latitude.sin());
+    let radius = p.hypot(z);
+    let geocentric = z.atan2(p);
+
+    // Fonctions de Legendre associées (semi-normalisées de Schmidt) et leurs dérivées par rapport à la colatitude
+    let (mu, s) = (geocentric.sin(), geocentric.cos());
+    let mut legendre = [[0.0; WMM_DEGREE + 1]; WMM_DEGREE + 1];
+    let mut derivative = [[0.0; WMM_DEGREE + 1]; WMM_DEGREE + 1];
+    legendre[0][0] = 1.0;
+    for n in 1..=WMM_DEGREE {
+        for m in 0..=n {
+            if m == n {
+                let k = if n == 1 { 1.0 } else { (1.0 - 1.0 / (2.0 * n as f64)).sqrt() };
+                legendre[n][n] = k * s * legendre[n - 1][n - 1];
+                derivative[n][n] = k * (mu * legendre[n - 1][n - 1] + s * derivative[n - 1][n - 1]);
+            } else {
+                let k = (((n - 1) * (n - 1) - m * m) as f64).sqrt();
+                let norm = ((n * n - m * m) as f64).sqrt();
+                let (previous, before) = (legendre[n - 1][m], if n >= 2 { legendre[n - 2][m] } else { 0.0 });
+                let d_before = if n >= 2 { derivative[n - 2][m] } else { 0.0 };
+                legendre[n][m] = ((2 * n - 1) as f64 * mu * previous - k * before) / norm;
+                derivative[n][m] = ((2 * n - 1) as f64 * (mu * derivative[n - 1][m] - s * previous) - k * d_before) / norm;
+            }
+        }
+    }
+
+    // Champ dans le repère géocentrique : nord (X), est (Y), bas (Z)
+    let dt = year.clamp(WMM_EPOCH, WMM_END) - WMM_EPOCH;
+    let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+    for (n, m, g, h, g_dot, h_dot) in WMM_COEFFICIENTS {
+        let (g, h) = (g + dt * g_dot, h + dt * h_dot);
+        let scale = (WMM_RADIUS / radius).powi(n as i32 + 2);
+        let (cos, sin) = ((m as f64 * longitude).cos(), (m as f64 * longitude).sin());
+
+        x += scale * (g * cos + h * sin) * derivative[n][m];
+        y += scale * m as f64 * (g * sin - h * cos) * legendre[n][m] / s;
+        z -= scale * (n + 1) as f64 * (g * cos + h * sin) * legendre[n][m];
+    }
+
+    // Composantes nord et verticale dans le repère géodésique
+    let tilt = geocentric - latitude;
+    let north = x * tilt.cos() - z * tilt.sin();
+    let down = x * tilt.sin() + z * tilt.cos();
+    let horizontal = north.hypot(y);
+
+    EarthField {
+        declination: y.atan2(north).to_degrees() as f32,
+        inclination: down.atan2(horizontal).to_degrees() as f32,
+        intensity: (horizontal.hypot(down) / NT_PER_GAUSS) as f32,
+    }
+}
+
+/// Date en années décimales
+pub(crate) fn decimal_year(time: SystemTime) -> f64 {
+    const SECONDS_PER_YEAR: f64 = 365.2425 * 24.0 * 3600.0;
+    let seconds = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+    1970.0 + seconds / SECONDS_PER_YEAR
+}
+
+/// Date en années décimales, `None` hors de la période de validité du modèle (ex: horloge non réglée)
+pub(crate) fn model_year(time: SystemTime) -> Option<f64> {
+    Some(decimal_year(time)).filter(|year| (WMM_EPOCH..=WMM_END).contains(year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declination_from_position_and_date() {
+        // Valeur de test du modèle (2025.0, 80° N, 0° E)
+        assert!((field(80.0, 0.0, 2025.0).declination - 1.28).abs() < 0.05);
+
+        // Ordres de grandeur connus en 2025
+        let lyon = field(45.76, 4.84, 2025.5);
+        assert!((lyon.declination - 2.6).abs() < 0.2);
+        assert!((lyon.inclination - 61.7).abs() < 0.5);
+        assert!((lyon.intensity - 0.477).abs() < 0.005);
+        assert!((field(40.0, -105.25, 2025.5).declination - 7.8).abs() < 0.2, "Boulder");
+        assert!((field(-33.9, 151.2, 2025.5).declination - 12.8).abs() < 0.2, "Sydney");
+        assert!(field(-33.9, 151.2, 2025.5).inclination < 0.0, "Sydney");
+
+        assert!((decimal_year(UNIX_EPOCH + std::time::Duration::from_secs(1_751_328_000)) - 2025.5).abs() < 0.01);
+
+        // Horloge non réglée (Pi sans RTC ni NTP) : date refusée, et ramenée dans la validité du modèle
+        assert_eq!(model_year(UNIX_EPOCH), None);
+        assert_eq!(field(45.76, 4.84, 1970.0), field(45.76, 4.84, 2025.0));
+    }
+}
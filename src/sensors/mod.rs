@@ -1,3 +1,5 @@
+pub mod ahrs;
+pub mod orientation;
 pub mod gps;
 pub mod imu;
 pub mod analog;
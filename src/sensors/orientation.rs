@@ -0,0 +1,60 @@
+use nalgebra::{Matrix3, Rotation3};
+use serde::{Deserialize, Serialize};
+
+/// Orientation d'un capteur dans la voiture (repère de la voiture : X vers l'avant, Z vers le haut)
+/// Les rotations nommées sont celles du capteur par rapport à la voiture, lacet compté dans le sens trigonométrique.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Orientation {
+    /// Axes du capteur alignés sur ceux de la voiture
+    Normal,
+    Yaw90,
+    Yaw180,
+    Yaw270,
+    /// Capteur retourné (rotation de 180° autour de l'axe X)
+    UpsideDown,
+    UpsideDownYaw90,
+    UpsideDownYaw180,
+    UpsideDownYaw270,
+    /// Matrice de passage quelconque : voiture = matrice * capteur
+    Matrix(Matrix3<f32>),
+}
+
+impl Orientation {
+    /// Matrice de passage du repère du capteur à celui de la voiture
+    pub(crate) fn matrix(&self) -> Matrix3<f32> {
+        let (upside_down, yaw): (bool, f32) = match self {
+            Self::Normal => (false, 0.0),
+            Self::Yaw90 => (false, 90.0),
+            Self::Yaw180 => (false, 180.0),
+            Self::Yaw270 => (false, 270.0),
+            Self::UpsideDown => (true, 0.0),
+            Self::UpsideDownYaw90 => (true, 90.0),
+            Self::UpsideDownYaw180 => (true, 180.0),
+            Self::UpsideDownYaw270 => (true, 270.0),
+            Self::Matrix(matrix) => return *matrix,
+        };
+
+        let roll: f32 = if upside_down { 180.0 } else { 0.0 };
+        let rotation = Rotation3::from_euler_angles(roll.to_radians(), 0.0, yaw.to_radians());
+
+        // Quarts de tour : coefficients exacts (0, 1 ou -1)
+        rotation.into_inner().map(f32::round)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn named_orientations_are_rotations() {
+        // Capteur tourné d'un quart de tour : son axe X pointe vers la gauche de la voiture
+        assert_eq!(Orientation::Yaw90.matrix() * Vector3::x(), Vector3::y());
+        assert_eq!(Orientation::UpsideDown.matrix() * Vector3::new(1.0, 2.0, 3.0), Vector3::new(1.0, -2.0, -3.0));
+        assert_eq!(Orientation::UpsideDownYaw180.matrix() * Vector3::new(1.0, 2.0, 3.0), Vector3::new(-1.0, 2.0, -3.0));
+
+        let custom = Matrix3::new(0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+        assert_eq!(Orientation::Matrix(custom).matrix(), custom);
+    }
+}
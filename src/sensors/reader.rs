@@ -1,17 +1,40 @@
 use futures::Stream;
 use nmea_parser::gnss::GgaQualityIndicator;
 use nmea_parser::ParsedMessage;
-use rppal::i2c::I2c;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::task::Poll;
 use std::thread;
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
 use crate::config::Config;
+use crate::i2c::I2CBus;
+use crate::i2c::manager::{I2CManager, RetryPolicy};
+use crate::sensors::imu::calibration::{FusionCalibrationStatus, ImuCalibration};
+use crate::sensors::imu::events::ImuEvent;
+use crate::sensors::imu::self_test::SelfTestReport;
+use crate::sensors::mag::calibration::{MagCalibration, MagCalibrator};
+#[cfg(feature = "real-sensors")]
+use crate::sensors::mag::driver::MagOverflow;
+use crate::sensors::mag::interference::InterferenceDetector;
+use crate::sensors::mag::wmm;
 use crate::sensors::{analog, gps, imu, mag};
 
+/// Période de vérification des capteurs I2C (réinitialisation après une perte d'alimentation)
+const HEALTH_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+/// Période de mise à jour du champ terrestre (déclinaison, intensité, inclinaison) à partir de la position GPS
+const EARTH_FIELD_UPDATE_PERIOD: Duration = Duration::from_secs(600);
+
+/// Ecart minimal (en degrés) avec la déclinaison utilisée pour l'appliquer
+const DECLINATION_TOLERANCE: f32 = 0.05;
+
+/// Nombre maximal d'événements de l'IMU conservés entre deux envois
+const MAX_PENDING_EVENTS: usize = 100;
+
 #[derive(Clone, Copy, Serialize, Deserialize)]
 pub(crate) struct ModemData {
     pub quality: u32,
@@ -21,12 +44,76 @@ pub(crate) struct ModemData {
 pub(crate) struct MagData {
     pub raw: (i16, i16, i16),
     pub heading: f32,
+    /// Cap compensé de l'inclinaison (tangage et roulis de l'IMU), même convention que le cap de l'AHRS
+    pub tilt_heading: f32,
+    /// Intensité du champ (en Gauss), après calibration
+    pub field: f32,
+    /// Vrai si la dernière mesure était saturée (ignorée)
+    pub overflow: bool,
+    /// Vrai si le champ est perturbé (moteur, métal, ...) : le magnétomètre est ignoré par la fusion
+    pub interference: bool,
+    /// Confiance (0 à 1) dans la dernière mesure, d'après l'intensité et l'inclinaison attendues
+    pub confidence: f32,
+    /// Part des directions couvertes (0 à 1) pendant une calibration sur la voiture
+    pub calibration_coverage: Option<f32>,
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
 pub(crate) struct ImuData {
+    /// Tangage, roulis et cap (en degrés) issus de l'AHRS
     pub angles: (f32, f32, f32),
+    /// Orientation (w, x, y, z)
+    pub quaternion: (f32, f32, f32, f32),
+    /// Accélération (en g, axes X, Y, Z de la voiture, gravité comprise)
+    pub accel: (f32, f32, f32),
+    /// Vitesse angulaire calibrée (en °/s, axes X, Y, Z de la voiture)
+    pub gyro: (f32, f32, f32),
+    /// Mesures brutes du dernier échantillon, si `imu_raw_data` est activé
+    pub raw: Option<ImuRawData>,
+    /// Calibration de la fusion réalisée par la puce (BNO055)
+    pub fusion_calibration: Option<FusionCalibrationStatus>,
+    /// Vrai si le détecteur d'immobilité ne voit plus aucun mouvement
+    pub parked: bool,
     pub temp: f32,
+    /// Instant du dernier échantillon (en secondes, horloge de l'IMU)
+    pub timestamp: f64,
+}
+
+impl ImuData {
+    /// Valeurs publiées après la mise à jour de l'IMU, mesures brutes comprises si `raw_data`
+    #[cfg(feature = "real-sensors")]
+    fn from_driver(imu: &dyn imu::driver::ImuDriver, raw_data: bool) -> Self {
+        let angles = imu.get_angles();
+        let accel = imu.get_acceleration();
+        let gyro = imu.get_angular_rate();
+
+        let raw = raw_data.then(|| {
+            let (accel, gyro) = imu.get_raw();
+            ImuRawData {
+                accel: (accel.x, accel.y, accel.z),
+                gyro: (gyro.x, gyro.y, gyro.z),
+            }
+        });
+
+        ImuData {
+            angles: (angles.x, angles.y, angles.z),
+            quaternion: imu.get_quaternion(),
+            accel: (accel.x, accel.y, accel.z),
+            gyro: (gyro.x, gyro.y, gyro.z),
+            raw,
+            fusion_calibration: imu.get_fusion_calibration(),
+            parked: imu.is_parked(),
+            temp: imu.get_temp(),
+            timestamp: imu.get_timestamp().as_secs_f64(),
+        }
+    }
+}
+
+/// Mesures brutes de l'IMU (LSB, dépendent de l'échelle configurée)
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct ImuRawData {
+    pub accel: (i16, i16, i16),
+    pub gyro: (i16, i16, i16),
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
@@ -56,17 +143,26 @@ pub(crate) struct SensorsData {
     pub analog: AnalogData,
     pub gps: GpsData,
     pub hall: HallData,
+    /// Evénements de l'IMU (chocs, sauts, arrêts) depuis le dernier envoi
+    pub events: Vec<ImuEvent>,
+    /// Résultat de l'auto-test de l'IMU au démarrage, publié une seule fois
+    pub self_test: Option<SelfTestReport>,
     pub time: u64,
 }
 
 pub(crate) struct Reader {
     data: Arc<Mutex<SensorsData>>,
+    imu_calibration: Arc<Mutex<Option<ImuCalibration>>>,
+    mag_calibration: Arc<Mutex<Option<MagCalibration>>>,
+    mag_calibration_mode: Arc<AtomicBool>,
+    events: Arc<Mutex<Vec<ImuEvent>>>,
+    self_test: Arc<Mutex<Option<SelfTestReport>>>,
     token: CancellationToken,
 }
 
 impl Reader {
     #[cfg(feature = "real-sensors")]
-    pub(crate) fn new(token: CancellationToken, config: Config) -> anyhow::Result<Self> {
+    pub(crate) fn new(token: CancellationToken, config: Config, i2c: Box<dyn I2CBus + Send>) -> anyhow::Result<Self> {
         // Initalisation des données
         use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -75,11 +171,24 @@ impl Reader {
             mag: MagData {
                 raw: (0, 0, 0),
                 heading: 0.0,
+                tilt_heading: 0.0,
+                field: 0.0,
+                overflow: false,
+                interference: false,
+                confidence: 1.0,
+                calibration_coverage: None,
             },
 
             imu: ImuData {
                 angles: (0.0, 0.0, 0.0),
+                quaternion: (1.0, 0.0, 0.0, 0.0),
+                accel: (0.0, 0.0, 0.0),
+                gyro: (0.0, 0.0, 0.0),
+                raw: None,
+                fusion_calibration: None,
+                parked: false,
                 temp: 0.0,
+                timestamp: 0.0,
             },
 
             analog: AnalogData {
@@ -99,65 +208,228 @@ impl Reader {
                 speed: 0.0,
             },
 
+            events: Vec::new(),
+            self_test: None,
+
             time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
         };
 
         // Gestion des données
         let data: Arc<Mutex<SensorsData>> = Arc::new(Mutex::new(current_data.clone()));
         let data_thread: Arc<Mutex<SensorsData>> = data.clone();
+        let imu_calibration = Arc::new(Mutex::new(None));
+        let imu_calibration_thread = imu_calibration.clone();
+        let mag_calibration = Arc::new(Mutex::new(None));
+        let mag_calibration_thread = mag_calibration.clone();
+        let mag_calibration_mode = Arc::new(AtomicBool::new(false));
+        let mag_calibration_mode_thread = mag_calibration_mode.clone();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_thread = events.clone();
+        let self_test = Arc::new(Mutex::new(None));
+        let self_test_thread = self_test.clone();
         let thread_token = token.clone();
-        let reader = Reader { data, token };
+        let reader = Reader { data, imu_calibration, mag_calibration, mag_calibration_mode, events, self_test, token };
 
         // I2C
-        let mut i2c_bus = I2c::new().expect("[I2C] Erreur de bus");
+        let retry = RetryPolicy {
+            retries: config.i2c_retries,
+            delay: Duration::from_millis(config.i2c_retry_delay_ms),
+        };
+        let i2c_bus = I2CManager::with_retry(i2c, retry);
+        let mut analog_i2c = i2c_bus.device(analog::analog::Analog::ADDR);
 
         println!("[CAPTEURS] Démarrage de la tâche ...");
         thread::spawn(move || {
             let mut current_data = current_data;
-
-            let mut imu = imu::imu::IMU::new(&mut i2c_bus).expect("[IMU] Capteur non disponible.");
-            let mag = mag::hmc8553l::HMC8553L::new(&mut i2c_bus, config).expect("[MAG] Capteur non disponible.");
-            let mut analog = analog::analog::Analog::new(&mut  i2c_bus).expect("[ANALOG] Capteur indisponible.");
+            let mut config = config;
+
+            let (mut imu, mut imu_i2c) = imu::driver::detect(&i2c_bus, &config).expect("[IMU] Capteur non disponible.");
+            let mut data_ready = config.imu_int_pin.map(|pin| imu::data_ready::DataReady::new(pin).expect("[IMU] Broche INT indisponible."));
+            let data_ready_timeout = imu.get_sample_period() * 4;
+            *self_test_thread.lock().unwrap() = imu.get_self_test();
+            let imu_raw_data = config.imu_raw_data;
+            // Le BNO055 a son propre magnétomètre, le magnétomètre externe est facultatif
+            let mut mag = match mag::driver::detect(&i2c_bus, &config) {
+                Ok(mag) => Some(mag),
+                Err(e) if imu.chip() == imu::ImuChip::Bno055 => {
+                    println!("[MAG] Pas de magnétomètre externe ({}), cap du BNO055.", e);
+                    None
+                }
+                Err(e) => panic!("[MAG] Capteur non disponible: {}", e),
+            };
+            let mag_aux_bus = config.mag_aux_bus && mag.is_some();
+            if let (true, Some((mag, _))) = (mag_aux_bus, mag.as_ref()) {
+                imu.set_aux_magnetometer(&mut imu_i2c, Some(mag.aux_magnetometer())).expect("[MAG] Bus auxiliaire de l'IMU indisponible.");
+            }
+            let mut analog = analog::analog::Analog::new(&mut analog_i2c).expect("[ANALOG] Capteur indisponible.");
             let mut gps = gps::GPS::new().expect("[GPS] Capteur indisponible.");
             let mut hall = hall::Hall::new().expect("[HALL] Capteur indisponible.");
-            
+            let mut mag_calibrator: Option<MagCalibrator> = None;
+            let mut last_earth_field: Option<Instant> = None;
+            let mut gps_time: Option<SystemTime> = None;
+            let mut interference = InterferenceDetector::new();
+
             println!("[CAPTEURS] Initialisation terminée. Lecture des données.");
+            let mut last_check = Instant::now();
 
             while !thread_token.is_cancelled() {
-                // Capteur: Magnétique
-                let heading: Result<f32, anyhow::Error> = mag.get_heading(&mut i2c_bus);
-                let raw = mag.get_mag_axes_raw(&mut i2c_bus);
-
-                if heading.is_ok() || raw.is_ok() {
-                    let heading: f32 = heading.unwrap();
-                    current_data.mag = MagData {
-                        heading,
-                        raw: raw.map(|x| (x.x, x.y, x.z)).unwrap(),
-                    };
-                } else {
-                    println!("[MAG] Erreur lors de la récupération des données.");
+                // Vérification périodique des capteurs I2C
+                let check = last_check.elapsed() >= HEALTH_CHECK_PERIOD;
+                if check {
+                    last_check = Instant::now();
+                }
+
+                // Capteur: Magnétique (lu par l'IMU s'il est sur son bus auxiliaire)
+                let mut mag_field = None;
+                let mut mag_raw = None;
+                if let Some((mag, mag_i2c)) = mag.as_mut() {
+                    if mag_aux_bus {
+                        if check {
+                            let result = imu.with_bypass(&mut imu_i2c, &mut || mag.check_and_recover(mag_i2c));
+                            log_recovery("MAG", result);
+                        }
+                    } else {
+                        match mag.get_mag_axes_raw(mag_i2c) {
+                            Ok(raw) => {
+                                // Sans nouvelle mesure (bit "data ready"), les dernières valeurs sont conservées
+                                if let Some(raw) = raw {
+                                    mag_field = Some(mag.calibrated_from_raw(raw));
+                                    mag_raw = Some(raw);
+                                    current_data.mag = MagData {
+                                        heading: mag.heading_from_raw(raw),
+                                        field: mag.field_strength(raw),
+                                        overflow: false,
+                                        raw: (raw.x, raw.y, raw.z),
+                                        ..current_data.mag
+                                    };
+                                }
+                                if check {
+                                    log_recovery("MAG", mag.check_and_recover(mag_i2c));
+                                }
+                            }
+                            Err(e) if e.is::<MagOverflow>() => {
+                                current_data.mag.overflow = true;
+                                if check {
+                                    log_recovery("MAG", mag.check_and_recover(mag_i2c));
+                                }
+                            }
+                            Err(e) => {
+                                println!("[MAG] Erreur lors de la récupération des données: {}", e);
+                                log_recovery("MAG", mag.check_and_recover(mag_i2c));
+                            }
+                        }
+                    }
+                }
+
+                // Capteur: IMU (attend le prochain échantillon si la broche INT est câblée)
+                if let Some(data_ready) = data_ready.as_mut() {
+                    if let Err(e) = data_ready.wait(data_ready_timeout) {
+                        println!("[IMU] Erreur sur la broche INT: {}", e);
+                    }
                 }
 
-                // Capteur: IMU
-                imu.set_speed(current_data.gps.speed_kmh);
-                if let Err(e) = imu.update(&mut i2c_bus) {
+                // Magnétomètre ignoré par la fusion pendant une perturbation
+                let mag_ignored = interference.is_interference();
+                let calibrate = |words| {
+                    let (mag, _) = mag.as_ref()?;
+                    let raw = mag.raw_from_words(words);
+                    (!mag_ignored && !mag.is_overflow(raw)).then(|| mag.calibrated_from_raw(raw))
+                };
+                let mag_input = match mag_aux_bus {
+                    true => imu::driver::MagInput::Aux(&calibrate),
+                    false => imu::driver::MagInput::Field(mag_field.filter(|_| !mag_ignored)),
+                };
+
+                if let Err(e) = imu.update(&mut imu_i2c, mag_input) {
                     println!("[IMU] Erreur de calcul: {}", e);
+                    log_recovery("IMU", imu.check_and_recover(&mut imu_i2c));
                 } else {
+                    if check {
+                        log_recovery("IMU", imu.check_and_recover(&mut imu_i2c));
+                    }
+
                     let angles = imu.get_angles();
-                    let temp: f32 = imu.get_temp();
 
-                    current_data.imu = ImuData {
-                        angles: (angles.x, angles.y, angles.z),
-                        temp,
+                    if let (Some(words), Some((mag, _))) = (imu.get_aux_mag(), mag.as_ref()) {
+                        let raw = mag.raw_from_words(words);
+                        if mag.is_overflow(raw) {
+                            current_data.mag.overflow = true;
+                        } else {
+                            mag_raw = Some(raw);
+                            current_data.mag = MagData {
+                                heading: mag.heading_from_raw(raw),
+                                field: mag.field_strength(raw),
+                                overflow: false,
+                                raw: (raw.x, raw.y, raw.z),
+                                ..current_data.mag
+                            };
+                        }
+                    }
+
+                    // Cap compensé de l'inclinaison et détection des perturbations, avec l'orientation qui vient d'être mise à jour
+                    if let (Some(raw), Some((mag, _))) = (mag_raw, mag.as_ref()) {
+                        current_data.mag.tilt_heading = mag.tilt_heading_from_raw(raw, angles.x, angles.y);
+                        interference.update(mag.calibrated_from_raw(raw) / mag.lsb_per_gauss(), angles.x, angles.y);
+                    }
+                    current_data.mag.interference = interference.is_interference();
+                    current_data.mag.confidence = interference.confidence();
+
+                    if let Some(calibration) = imu.take_new_calibration() {
+                        *imu_calibration_thread.lock().unwrap() = Some(calibration);
+                    }
+
+                    let new_events = imu.take_events();
+                    if !new_events.is_empty() {
+                        let mut events = events_thread.lock().unwrap();
+                        events.extend(new_events);
+                        let overflow = events.len().saturating_sub(MAX_PENDING_EVENTS);
+                        events.drain(..overflow);
+                    }
+
+                    current_data.imu = ImuData::from_driver(imu.as_ref(), imu_raw_data);
+                }
+
+                // Calibration du magnétomètre sur la voiture, tant que le switch `mag_calibration` est actif
+                let calibration_mode = mag_calibration_mode_thread.load(Ordering::Relaxed);
+                if calibration_mode && mag_calibrator.is_none() {
+                    println!("[MAG] Calibration: tourner la voiture dans tous les sens ...");
+                    mag_calibrator = Some(MagCalibrator::new());
+                }
+                if let (Some(calibrator), Some(raw)) = (mag_calibrator.as_mut(), mag_raw) {
+                    calibrator.add(raw);
+                }
+                current_data.mag.calibration_coverage = mag_calibrator.as_ref().map(|calibrator| calibrator.coverage());
+
+                if !calibration_mode {
+                    if let Some(calibrator) = mag_calibrator.take() {
+                        match calibrator.fit() {
+                            Ok(calibration) => {
+                                println!(
+                                    "[MAG] Calibration terminée: {} mesures, couverture {:.0} %, écart à la sphère {:.1} %",
+                                    calibration.samples, calibration.coverage * 100.0, calibration.residual * 100.0
+                                );
+                                config.hard_cal = calibration.hard_cal;
+                                config.soft_cal = calibration.soft_cal;
+                                if let Some((mag, _)) = mag.as_mut() {
+                                    mag.recalibrate(&config);
+                                }
+                                *mag_calibration_thread.lock().unwrap() = Some(calibration);
+                            }
+                            Err(e) => println!("[MAG] Calibration refusée ({} mesures, couverture {:.0} %): {}", calibrator.len(), calibrator.coverage() * 100.0, e),
+                        }
                     }
                 }
 
                 // Capteur: Analog
-                let battery = analog.get_battery(&mut i2c_bus);
+                let battery = analog.get_battery(&mut analog_i2c);
                 if let Err(e)  = battery {
                     println!("[ANALOG] Erreur: {}", e);
+                    log_recovery("ANALOG", analog.check_and_recover(&mut analog_i2c));
                 } else {
                     current_data.analog.battery = battery.unwrap();
+                    if check {
+                        log_recovery("ANALOG", analog.check_and_recover(&mut analog_i2c));
+                    }
                 }
 
                 // Capteur: Hall
@@ -184,6 +456,12 @@ impl Reader {
                                     current_data.gps.fix = gga.quality == GgaQualityIndicator::GpsFix;
 
                                 }
+                                ParsedMessage::Rmc(rmc) => {
+                                    // Date du GPS : l'horloge du Pi n'est pas réglée sans RTC ni NTP
+                                    if let Some(timestamp) = rmc.timestamp {
+                                        gps_time = Some(UNIX_EPOCH + Duration::from_secs(timestamp.timestamp().max(0) as u64));
+                                    }
+                                }
                                 ParsedMessage::Vtg(vtg) => {
                                     current_data.gps.speed_kmh = vtg.sog_kph.unwrap_or(0.0);
                                     current_data.gps.heading = vtg.cog_true.unwrap_or(0.0);
@@ -196,6 +474,30 @@ impl Reader {
                     }
                 }
 
+                // Champ terrestre à la position GPS (WMM) : déclinaison et référence de la détection des perturbations
+                // `mag_decl` est conservée sans fix, ou sans date valide (GPS, sinon horloge du système)
+                let earth_field_due = last_earth_field.is_none_or(|last| last.elapsed() >= EARTH_FIELD_UPDATE_PERIOD);
+                if current_data.gps.fix && earth_field_due {
+                    last_earth_field = Some(Instant::now());
+                    match gps_time.or(Some(SystemTime::now())).and_then(wmm::model_year) {
+                        Some(year) => {
+                            let earth_field = wmm::field(current_data.gps.latitude, current_data.gps.longitude, year);
+                            interference.set_expected(earth_field);
+
+                            let declination = earth_field.declination;
+                            if config.mag_decl_auto && (declination - config.mag_decl).abs() >= DECLINATION_TOLERANCE {
+                                println!("[MAG] Déclinaison magnétique: {:.2}° (WMM, position GPS)", declination);
+                                config.mag_decl = declination;
+                                if let Some((mag, _)) = mag.as_mut() {
+                                    mag.recalibrate(&config);
+                                }
+                                imu.set_mag_decl(declination);
+                            }
+                        }
+                        None => println!("[MAG] Date hors de la validité du modèle (WMM), déclinaison inchangée."),
+                    }
+                }
+
                 *data_thread.lock().unwrap() = current_data.clone();
             }
 
@@ -213,11 +515,24 @@ impl Reader {
             mag: MagData {
                 raw: (0, 0, 0),
                 heading: 0.0,
+                tilt_heading: 0.0,
+                field: 0.0,
+                overflow: false,
+                interference: false,
+                confidence: 1.0,
+                calibration_coverage: None,
             },
 
             imu: ImuData {
                 angles: (0.0, 0.0, 0.0),
+                quaternion: (1.0, 0.0, 0.0, 0.0),
+                accel: (0.0, 0.0, 0.0),
+                gyro: (0.0, 0.0, 0.0),
+                raw: None,
+                fusion_calibration: None,
+                parked: false,
                 temp: 0.0,
+                timestamp: 0.0,
             },
 
             analog: AnalogData {
@@ -231,14 +546,25 @@ impl Reader {
                 satellites: 0,
                 fix: false,
                 heading: 0.0,
-            }
+            },
+
+            events: Vec::new(),
+            self_test: None,
         };
 
         // Gestion des données
         let data: Arc<Mutex<Data>> = Arc::new(Mutex::new(current_data.clone()));
         let data_thread = data.clone();
         let thread_token = token.clone();
-        let reader = Reader { data, token };
+        let reader = Reader {
+            data,
+            imu_calibration: Arc::new(Mutex::new(None)),
+            mag_calibration: Arc::new(Mutex::new(None)),
+            mag_calibration_mode: Arc::new(AtomicBool::new(false)),
+            events: Arc::new(Mutex::new(Vec::new())),
+            self_test: Arc::new(Mutex::new(None)),
+            token,
+        };
 
         println!("[CAPTEURS] Démarrage du thread [FAKE] .");
         thread::spawn(move || {
@@ -258,6 +584,31 @@ impl Reader {
 
         Ok(reader)
     }
+
+    /// Récupére la dernière calibration de l'IMU pas encore enregistrée
+    pub(crate) fn take_imu_calibration(&self) -> Option<ImuCalibration> {
+        self.imu_calibration.lock().unwrap().take()
+    }
+
+    /// Récupére la dernière calibration du magnétomètre pas encore enregistrée
+    pub(crate) fn take_mag_calibration(&self) -> Option<MagCalibration> {
+        self.mag_calibration.lock().unwrap().take()
+    }
+
+    /// Active la calibration du magnétomètre : les mesures sont collectées jusqu'à la désactivation,
+    /// puis l'ellipsoïde est ajusté et la calibration appliquée si sa qualité est suffisante
+    pub(crate) fn mag_calibration_mode(&self) -> Arc<AtomicBool> {
+        self.mag_calibration_mode.clone()
+    }
+}
+
+/// Affiche le résultat de la vérification d'un capteur
+fn log_recovery(name: &str, result: anyhow::Result<bool>) {
+    match result {
+        Ok(true) => println!("[{}] Capteur réinitialisé.", name),
+        Ok(false) => {}
+        Err(e) => println!("[{}] Capteur indisponible: {}", name, e),
+    }
 }
 
 impl Stream for Reader {
@@ -271,7 +622,44 @@ impl Stream for Reader {
             return Poll::Ready(None);
         }
 
-        let data = self.data.lock().unwrap().clone();
+        let mut data = self.data.lock().unwrap().clone();
+        data.events = std::mem::take(&mut *self.events.lock().unwrap());
+        data.self_test = self.self_test.lock().unwrap().take();
         Poll::Ready(Some(Ok(data)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "real-sensors")]
+    fn imu_vectors_published() {
+        use crate::i2c::sim::{SimBus, SimDevice, SimResponse};
+        use crate::sensors::imu::driver::{ImuDriver, MagInput};
+        use crate::sensors::imu::imu::IMU;
+
+        let mut bus = SimBus::new();
+        bus.attach(0x68, SimDevice::new());
+        let mut imu = IMU::new(&mut bus, &Config::new(), imu::ImuChip::Mpu6050).unwrap();
+
+        // Un échantillon dans la FIFO (FIFO_COUNTH, FIFO_R_W) : accélération, température puis gyroscope
+        let device = bus.device(0x68).unwrap();
+        device.set_registers(0x72, &14u16.to_be_bytes());
+        let data: Vec<u8> = [0i16, 0, 16384, 0, 131, -262, 0].iter().flat_map(|v| v.to_be_bytes()).collect();
+        device.script_read(0x74, SimResponse::Data(data));
+        assert_eq!(imu.update(&mut bus, MagInput::Field(None)).unwrap(), 1);
+
+        let published = ImuData::from_driver(&imu, true);
+        assert_eq!(published.accel, (0.0, 0.0, 1.0));
+        assert_eq!(published.gyro, (1.0, -2.0, 0.0));
+        let raw = published.raw.unwrap();
+        assert_eq!(raw.accel, (0, 0, 16384));
+        assert_eq!(raw.gyro, (131, -262, 0));
+        assert_eq!(published.timestamp, imu.get_sample_period().as_secs_f64());
+
+        // Mesures brutes publiées uniquement si `imu_raw_data` est activé
+        assert!(ImuData::from_driver(&imu, false).raw.is_none());
+    }
+}